@@ -4,10 +4,10 @@ use crate::file_manager::page::Page;
 use crate::file_manager::block::Block_ID;
 use crate::file_manager::file_manager::File_manager;
 use crate::file_manager::page::Page_type;
+use crate::file_manager::page::PAGE_HEADER_SIZE;
 
 use std::collections::HashMap;
-
-const CHUNK_SIZE: usize = (16 * 1024)- 17;
+use std::io::Error;
 
 #[derive(Debug)]
 pub struct Page_table_entry{
@@ -72,7 +72,7 @@ impl Page_table{
         if let Some(entry) = fetch {
             
             if *entry >= number {
-                return ()
+                return
             }else{
                 self.largest_page_map.insert(file_name, number);
             }
@@ -86,21 +86,70 @@ impl Page_table{
 
 
 
+    //returns the number of a new page at the end of the file. Pages which are only in memory are
+    //accounted for via the largest page map.
+    pub fn new_page_num(&mut self, file_name: &String, file_manager: &mut File_manager) -> Result<u32, std::io::Error>{
+        let total_blocks = file_manager.total_blocks(file_name)?;
+
+        let number = match self.largest_page_map.get(file_name){
+            Some(largest)   => std::cmp::max(*largest + 1, total_blocks),
+            None            => total_blocks,
+        };
+
+        self.update_largest_page_map(file_name.clone(), number);
+
+        return Ok(number)
+    }
+
+
+
+    //creates an empty page at the end of the file and adds it to memory.
+    pub fn new_page(&mut self, file_name: &String, page_type: Page_type, file_manager: &mut File_manager) -> Result<u32, std::io::Error>{
+        let number = self.new_page_num(file_name, file_manager)?;
+        let page = Page::new(self.page_size, number, page_type);
+
+        self.add_page(page, &Block_ID{file_name: file_name.clone(), number}, file_manager)?;
+
+        return Ok(number)
+    }
+
+
+
+    //bytes of data an overflow page can hold, the end of the page is kept for its record index.
+    pub fn chunk_size(&self) -> usize{
+        return (self.page_size - PAGE_HEADER_SIZE - 4) as usize
+    }
+
+
+
     pub fn set_dirty(&mut self, block: &Block_ID){
         
-        let fetch = self.pages_in_memory.get_mut(block);
         //ADD ERROR CHECKING 
-        match fetch{
+        if let Some(entry) = self.pages_in_memory.get_mut(block){
+            entry.dirty = true;
+        }
+    }
+    
 
-            Some(entry)    =>  {
-                        entry.dirty = true;
-                        return ()
-                        },   
 
-            None    =>  return (),
+
+    //keeps a page in memory until it is unpinned as often as it was pinned. Pages that are not in
+    //memory are left alone.
+    pub fn pin(&mut self, block: &Block_ID){
+        if let Some(entry) = self.pages_in_memory.get_mut(block){
+            entry.pin_count += 1;
         }
     }
-    
+
+
+
+    pub fn unpin(&mut self, block: &Block_ID){
+        if let Some(entry) = self.pages_in_memory.get_mut(block)
+            && entry.pin_count > 0{
+            entry.pin_count -= 1;
+        }
+    }
+
 
 
 
@@ -112,8 +161,8 @@ impl Page_table{
 
             Some(entry)    =>  {
                         entry.pin_count += 1;
-                        let mut page = &entry.page;
-                        let result = file_manager.write(block, &mut page);
+                        let page = &entry.page;
+                        let result = file_manager.write(block, page);
                         entry.dirty = false;
                         entry.pin_count -= 1;
                         return result
@@ -126,113 +175,93 @@ impl Page_table{
 
 
 
-    pub fn write_all(&mut self, file_manager: &mut File_manager){
+    pub fn write_all(&mut self, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
         let blocks: Vec<_> = self.pages_in_memory.keys().cloned().collect();
         for block in blocks{
-            self.write_to_disk(&block, file_manager);
+            self.write_to_disk(&block, file_manager)?;
         }
     
+        return Ok(1)
+    }
+
+
+
+    //forgets every page of a file without writing them, for when the file is deleted.
+    pub fn remove_file(&mut self, file_name: &String){
+        self.pages_in_memory.retain(|block, _| block.file_name != *file_name);
+        self.largest_page_map.remove(file_name);
     }
 
 
 
     //creates a page in between two pages
-    pub fn create_overflow_page(&mut self, old_block: &Block_ID, page_type: Page_type, next_page_num: u32, overflow_bytes: &[u8], overflow_page_num: Option<u32>, file_manager: &mut File_manager){
+    pub fn create_overflow_page(&mut self, old_block: &Block_ID, page_type: Page_type, next_page_num: u32, overflow_bytes: &[u8], overflow_page_num: Option<u32>, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
         //create the page and fill in the appropriate data.
-        
-        //create a new page at the end of the file.
-        let overflow_page_num = 
-            if overflow_page_num == None{
-                match self.largest_page_map.get(&old_block.file_name.clone()){
-                    Some(number)    =>  *number,
-                    None            => { let n = file_manager.total_blocks(&old_block.file_name).unwrap();
-                                         self.update_largest_page_map(old_block.file_name.clone(), n);
-                                         n
-                                        },
 
-                }
-            }else{
-                overflow_page_num.unwrap()
-            };
+        //create a new page at the end of the file.
+        let overflow_page_num = match overflow_page_num{
+            None            => self.new_page_num(&old_block.file_name, file_manager)?,
+            Some(number)    => number,
+        };
 
         let mut overflow_page = Page::new(self.page_size, overflow_page_num, page_type);
         let byte_length = overflow_bytes.len() as u16;
 
-        overflow_page.write(17, overflow_bytes.to_vec() );
+        overflow_page.write(PAGE_HEADER_SIZE, overflow_bytes.to_vec())?;
 
         overflow_page.data_end_point += byte_length;
 
-        overflow_page.set_next_page_num(next_page_num);
+        if next_page_num != 0{
+            overflow_page.set_next_page_num(next_page_num);
+        }
 
         overflow_page.set_previous_page_num(old_block.number);
 
-        let prev_page = self.get_mut_page(old_block.clone(), file_manager).unwrap();
+        let prev_page = match self.get_mut_page(old_block.clone(), file_manager){
+            None    => return Err(Error::other("Page before the overflow page could not be loaded.")),
+            Some(p) => p,
+        };
 
         prev_page.set_next_page_num(overflow_page_num);
-
-        
+        self.set_dirty(old_block);
 
         if next_page_num != 0{
             let next_block = Block_ID{file_name: old_block.file_name.clone(), number: next_page_num};
-            let next_page = if self.pages_in_memory.contains_key(&next_block){
-                self.get_mut_page(Block_ID{file_name: old_block.file_name.clone(), number: next_page_num}, file_manager).unwrap()
-            }
-            else{
-                let result = self.request_new_page(&next_block, file_manager);
-
-                if result.is_ok(){
-                    self.get_mut_page(Block_ID{file_name: old_block.file_name.clone(), number: next_page_num}, file_manager).unwrap()
-                }else{
-                    return ()
-                }
-
+            let next_page = match self.get_mut_page(next_block.clone(), file_manager){
+                None    => return Err(Error::other("Page after the overflow page could not be loaded.")),
+                Some(p) => p,
             };
+
             next_page.set_previous_page_num(overflow_page_num);
+            self.set_dirty(&next_block);
         };
-        self.add_page(overflow_page, &Block_ID{file_name: old_block.file_name.clone(), number: overflow_page_num}, file_manager);
+
+        return self.add_page(overflow_page, &Block_ID{file_name: old_block.file_name.clone(), number: overflow_page_num}, file_manager)
     }
 
 
 
 
-    pub fn create_multiple_overflow_pages_by_data(&mut self, data: &[u8], mut original_block: Block_ID, file_manager: &mut File_manager) -> (u32, u16){
-            let mut overflow_num = match self.largest_page_map.get_mut(&original_block.file_name.clone()){
-                Some(number)    => *number,
-                None            => { let n = file_manager.total_blocks(&original_block.file_name).unwrap();
-                                     self.update_largest_page_map(original_block.file_name.clone(), n);
-                                     n
-                                    },
+    //spreads the data over new pages linked in straight after the original block. Returns the last
+    //new page and how many bytes are left free in it.
+    pub fn create_multiple_overflow_pages_by_data(&mut self, data: &[u8], mut original_block: Block_ID, file_manager: &mut File_manager) -> Result<(u32, u16), std::io::Error>{
+        let next_page_num = match self.get_mut_page(original_block.clone(), file_manager){
+            None        => return Err(Error::other("Page before the overflow pages could not be loaded.")),
+            Some(page)  => page.next_index.unwrap_or(0),
+        };
 
-            }; 
-        
+        let chunk_size = self.chunk_size();
+        let mut last_chunk_size = 0;
 
-        
-        let block = original_block.clone();
-        
+        for chunk in data.chunks(chunk_size){
+            let overflow_num = self.new_page_num(&original_block.file_name, file_manager)?;
 
-        for chunk in data.chunks_exact(CHUNK_SIZE){
-            
-            self.create_overflow_page(&original_block, Page_type::Data, 0, chunk, Some(overflow_num), file_manager); 
+            self.create_overflow_page(&original_block, Page_type::Data, next_page_num, chunk, Some(overflow_num), file_manager)?;
             original_block.number = overflow_num;
-            overflow_num += 1;
-        
+            last_chunk_size = chunk.len();
         }
 
-        let remainder = data.chunks_exact(CHUNK_SIZE).remainder();
-
-        if !remainder.is_empty() {
-            overflow_num+=1;
-            self.update_largest_page_map(original_block.file_name.clone(), overflow_num);
-            self.create_overflow_page(&original_block, Page_type::Data, 0, remainder, Some(overflow_num), file_manager);
-            return (overflow_num, (CHUNK_SIZE - remainder.len()) as u16)
-        }
-
-        self.update_largest_page_map(original_block.file_name.clone(), overflow_num);
-
-        return(overflow_num, 0);
-
-        
-
+        return Ok((original_block.number, (chunk_size - last_chunk_size) as u16))
     }
 
 
@@ -255,13 +284,7 @@ impl Page_table{
                     
                     None => false,
 
-                    Some(entry) => {
-                            if entry.dirty{
-                                true
-                            }else{
-                                false
-                            }
-                                },
+                    Some(entry) => entry.dirty,
 
                 }
             };
@@ -290,7 +313,7 @@ impl Page_table{
 
         self.pages_in_memory.insert(new_block_ID.clone(), entry );
 
-        return file_manager.read(&new_block_ID, &mut self.pages_in_memory.get_mut(&new_block_ID).unwrap().page)
+        return file_manager.read(new_block_ID, &mut self.pages_in_memory.get_mut(new_block_ID).unwrap().page)
     }
 
 
@@ -299,20 +322,20 @@ impl Page_table{
     pub fn get_mut_page(&mut self, block: Block_ID, file_manager: &mut File_manager) -> Option<&mut Page>{
 
        if !self.pages_in_memory.contains_key(&block){
-            let res = self.request_new_page(&block, file_manager); 
+            let _ = self.request_new_page(&block, file_manager); 
        };
 
         let fetch = self.pages_in_memory.get_mut(&block);
 
         return match fetch{
         
-            None => return None,
+            None => None,
 
             Some(entry) => {
                 //WHEN MULTITHREADING UNCOMMENT THIS... 
                 //entry.pin_count += 1;
                 entry.referenced = true;
-                return Some(&mut entry.page)
+                Some(&mut entry.page)
                         },
 
        }
@@ -346,7 +369,7 @@ impl Page_table{
     pub fn add_page(&mut self, page: Page, block: &Block_ID, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
         let block_to_replace = self.find_next_replaceable_page();
         
-        if block_to_replace != None{
+        if block_to_replace.is_some(){
 
             let Some(block_to_replace) = block_to_replace else{return Ok(0)};
        
@@ -358,13 +381,7 @@ impl Page_table{
                     
                     None => false,
 
-                    Some(entry) => {
-                            if entry.dirty{
-                                true
-                            }else{
-                                false
-                            }
-                                },
+                    Some(entry) => entry.dirty,
 
                 }
             };
@@ -398,44 +415,60 @@ impl Page_table{
 
 
 
+    //picks the page to evict once the buffer pool is full, with the clock algorithm: a page that
+    //was referenced since the last sweep gets another chance. None while there is room, and also
+    //when every page is pinned, the pool then holds more pages than its size until some are
+    //unpinned.
     pub fn find_next_replaceable_page(&mut self) -> Option<Block_ID>{
-
-        //println!("max count: {:?}, page num in memory: {:?}", self.max_page_count, self.pages_in_memory.len());
-        
         if self.max_page_count > self.pages_in_memory.len().try_into().unwrap(){
             return None
         }
 
-        else{
-
-            loop {
-
-                for (block, content) in self.pages_in_memory.iter_mut(){
-                    
-                    //self.clock_index = (self.clock_index + 1) % self.page_table_size as usize;
-
-                    if content.pin_count <= 0 && !content.referenced{
-                        return Some(block.clone());
-
-                    }else{
-                        
-                        content.referenced = false;
-                    };
+        //the first sweep clears the referenced bits, so the second finds any page that is not
+        //pinned.
+        for _ in 0..2{
+            for (block, content) in self.pages_in_memory.iter_mut(){
+                if content.pin_count == 0 && !content.referenced{
+                    return Some(block.clone());
+                }else{
+                    content.referenced = false;
                 };
-
             };
-
         };
 
-        return None;
-
+        return None
     }
 
+}
 
 
 
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::file_manager::file_manager::build_file_manager;
+    use crate::test_utils::{temp_directory, TEST_PAGE_SIZE};
 
-}
 
+    #[test]
+    fn a_pool_of_pinned_pages_has_no_page_to_replace(){
+        let name = "pinned_pages".to_string();
+        let mut fm = build_file_manager(TEST_PAGE_SIZE, temp_directory(&name));
+        let mut pt = Page_table::new(TEST_PAGE_SIZE as u32 * 2, TEST_PAGE_SIZE, vec![name.clone()], &mut fm);
 
+        let blocks: Vec<Block_ID> = (0..2).map(|_| Block_ID{file_name: name.clone(), number: pt.new_page(&name, Page_type::Data, &mut fm).unwrap()}).collect();
 
+        for block in blocks.iter(){
+            pt.pin(block);
+        }
+        assert_eq!(pt.find_next_replaceable_page(), None);
+
+        //a page can still be added, the pool grows past its size while its pages are pinned.
+        let third = pt.new_page(&name, Page_type::Data, &mut fm).unwrap();
+        assert_eq!(pt.pages_in_memory.len(), 3);
+
+        pt.unpin(&blocks[1]);
+        pt.pin(&Block_ID{file_name: name.clone(), number: third});
+        assert_eq!(pt.find_next_replaceable_page(), Some(blocks[1].clone()));
+    }
+}
@@ -1,28 +1,857 @@
-pub struct Database {
-    pub tables: HashMap<String, Table>;
+//A database is a directory of table files. The catalog file in the directory lists the names of
+//the tables, one per line, and every table is stored in the file with its name. All tables share a
+//single File_manager and Page_table.
+//
+//Foreign keys between the tables are enforced here, so records of tables with foreign keys, or
+//which other tables refer to, should be added, modified and removed through the Database. Changes
+//made to a table through table_mut are not checked against the foreign keys.
+//
+//What happens to the records that refer to a removed record is set by the On_delete of their
+//foreign key. There is no ON UPDATE: the primary key of a record that other records refer to
+//cannot be changed, modify_record returns a Foreign_key_violation instead.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::file_manager::file_manager::File_manager;
+use crate::file_manager::file_manager::build_file_manager;
+use crate::buffer_pool::page_table::Page_table;
+use crate::table::table::Table;
+use crate::table::table::Column;
+use crate::table::table::Data_type;
+use crate::table::table::Table_error;
+use crate::table::table::Value;
+use crate::table::table::Record_ID;
+use crate::table::table::Added_record;
+use crate::table::table::On_delete;
+
+
+pub const CATALOG_FILE_NAME: &str = "catalog";
+pub const PAGE_SIZE: u16 = 16384;
+pub const BUFFER_POOL_SIZE: u32 = 16384 * 1024;
 
+
+#[derive(Debug)]
+pub enum Database_error{
+    Io(std::io::Error),
+    Table(Table_error),
+    Table_exists(String),
+    Table_not_found(String),
+    Invalid_table_name(String),
+    Invalid_foreign_key(String),
+    Foreign_key_violation{table_name: String, column_name: String},
+    Table_referenced(String),
 }
 
+impl From<std::io::Error> for Database_error{
+    fn from(error: std::io::Error) -> Self{
+        Database_error::Io(error)
+    }
+}
+
+impl From<Table_error> for Database_error{
+    fn from(error: Table_error) -> Self{
+        Database_error::Table(error)
+    }
+}
+
+
+pub struct Database {
+    pub directory:      String,
+    pub(crate) tables:  HashMap<String, Table>,
+    pub file_manager:   File_manager,
+    pub page_table:     Page_table,
+}
 
 
 
 
 impl Database{
-    pub fn new(&mut file_manager, &mut page_table){
 
-        //Initialise the table that stores IDs that link variable data.
+    //opens the database stored in a directory, creating the directory and an empty catalog if
+    //they do not exist yet.
+    pub fn open(directory: &str) -> Result<Database, Database_error>{
+        std::fs::create_dir_all(directory)?;
+
+        let catalog_path = format!("{}/{}", directory, CATALOG_FILE_NAME);
+        let table_names: Vec<String> = if Path::new(&catalog_path).exists(){
+            std::fs::read_to_string(&catalog_path)?
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(|line| line.to_string())
+                .collect()
+        }else{
+            std::fs::write(&catalog_path, "")?;
+            Vec::new()
+        };
+
+        let mut file_manager = build_file_manager(PAGE_SIZE, directory.to_string());
+        let mut page_table = Page_table::new(BUFFER_POOL_SIZE, PAGE_SIZE, table_names.clone(), &mut file_manager);
+
+        let mut tables = HashMap::new();
+
+        for name in table_names{
+            let table = Table::open(name.clone(), &mut page_table, &mut file_manager)?;
+
+            tables.insert(name, table);
+        }
+
+        return Ok(Database{
+            directory:      directory.to_string(),
+            tables:         tables,
+            file_manager:   file_manager,
+            page_table:     page_table,
+        })
+    }
+
+
+
+    //writes the names of the tables to the catalog file.
+    fn write_catalog(&self) -> Result<u8, std::io::Error>{
+        let mut names: Vec<&String> = self.tables.keys().collect();
+        names.sort();
+
+        let mut catalog = String::new();
+        for name in names{
+            catalog.push_str(name);
+            catalog.push('\n');
+        }
+
+        std::fs::write(format!("{}/{}", self.directory, CATALOG_FILE_NAME), catalog)?;
+
+        return Ok(1)
+    }
+
+
+
+    //creates a new table file with the given columns and adds it to the catalog.
+    pub fn create_table(&mut self, name: &str, columns: Vec<Column>) -> Result<&mut Table, Database_error>{
+        if name.is_empty() || name.len() > 255 || name == CATALOG_FILE_NAME || name.contains(['/', '\\', '\n', '\r']) || name.starts_with('.'){
+            return Err(Database_error::Invalid_table_name(name.to_string()))
+        }
+
+        if self.tables.contains_key(name){
+            return Err(Database_error::Table_exists(name.to_string()))
+        }
+
+        self.check_foreign_keys(name, &[(name, &columns)])?;
+
+        let name = name.to_string();
+
+        //a file left behind by a table that was not in the catalog is replaced.
+        self.page_table.remove_file(&name);
+        if Path::new(&format!("{}/{}", self.directory, name)).exists(){
+            self.file_manager.remove_file(&name)?;
+        }
+
+        let mut table = Table::open_or_create(name.clone(), &mut self.page_table, &mut self.file_manager)?;
+
+        for column in columns{
+            if let Err(error) = table.add_column_definition(column, &mut self.page_table, &mut self.file_manager){
+                self.page_table.remove_file(&name);
+                self.file_manager.remove_file(&name)?;
+                return Err(error.into())
+            }
+        }
+
+        self.tables.insert(name.clone(), table);
+        self.write_catalog()?;
+
+        return Ok(self.tables.get_mut(&name).unwrap())
+    }
+
+
+
+    //removes a table from the catalog and deletes its file. A table other tables refer to cannot be
+    //dropped.
+    pub fn drop_table(&mut self, name: &str) -> Result<u8, Database_error>{
+        if !self.tables.contains_key(name){
+            return Err(Database_error::Table_not_found(name.to_string()))
+        }
+
+        if let Some((child_name, _, _)) = self.references_to(name).into_iter().find(|(child_name, _, _)| child_name != name){
+            return Err(Database_error::Table_referenced(child_name))
+        }
+
+        self.tables.remove(name);
+
+        self.write_catalog()?;
+
+        let name = name.to_string();
+        self.page_table.remove_file(&name);
+        self.file_manager.remove_file(&name)?;
+
+        return Ok(1)
+    }
+
+
+
+    pub fn table(&self, name: &str) -> Option<&Table>{
+        return self.tables.get(name)
+    }
+
+
+
+    //the table for changes the Database does not make itself, e.g. creating an index. Records
+    //added, modified or removed through it bypass the foreign key checks.
+    pub fn table_mut(&mut self, name: &str) -> Option<&mut Table>{
+        return self.tables.get_mut(name)
+    }
+
+
+
+    pub fn table_names(&self) -> Vec<String>{
+        let mut names: Vec<String> = self.tables.keys().cloned().collect();
+        names.sort();
+        return names
+    }
+
+
+
+    //rewrites at most limit records that were written with an older schema version of their
+    //table, returning how many were rewritten. Can be called whenever there is time until it
+    //returns 0.
+    pub fn rewrite_old_records(&mut self, limit: usize) -> Result<usize, Database_error>{
+        let mut rewritten = 0;
+
+        for table in self.tables.values_mut(){
+            rewritten += table.rewrite_old_records(limit - rewritten, &mut self.page_table, &mut self.file_manager)?;
+
+            if rewritten == limit{
+                break
+            }
+        }
+
+        return Ok(rewritten)
+    }
+
+
+
+    //adds a column to a table after checking its foreign key. The records already in the table
+    //take the column's default, so it has to be a key of the referenced table.
+    pub fn add_column(&mut self, table_name: &str, column: Column) -> Result<u8, Database_error>{
+        let mut columns = match self.tables.get(table_name){
+            None    => return Err(Database_error::Table_not_found(table_name.to_string())),
+            Some(t) => t.column_schema.clone(),
+        };
+
+        columns.push(column.clone());
+        self.check_foreign_keys(table_name, &[(table_name, &columns)])?;
+
+        if let Some(default) = &column.default{
+            self.check_reference(table_name, &column, default)?;
+        }
+
+        let table = self.tables.get_mut(table_name).unwrap();
+        table.add_column_definition(column, &mut self.page_table, &mut self.file_manager)?;
+
+        return Ok(1)
+    }
+
+
+
+    //drops a column from a table, see Table::remove_column. The primary key of a table other
+    //columns refer to cannot be dropped.
+    pub fn remove_column(&mut self, table_name: &str, column_name: &str) -> Result<u8, Database_error>{
+        let table = match self.tables.get(table_name){
+            None    => return Err(Database_error::Table_not_found(table_name.to_string())),
+            Some(t) => t,
+        };
+
+        let referenced = table.primary_key_index().is_some_and(|k| table.column_schema[k].column_name == column_name);
+
+        if referenced && let Some((child_name, _, _)) = self.references_to(table_name).into_iter().next(){
+            return Err(Database_error::Table_referenced(child_name))
+        }
+
+        let table = self.tables.get_mut(table_name).unwrap();
+        table.remove_column(column_name.to_string(), &mut self.page_table, &mut self.file_manager)?;
+
+        return Ok(1)
+    }
+
+
+
+    //renames a column of a table, see Table::modify_column_name.
+    pub fn modify_column_name(&mut self, table_name: &str, old_name: &str, new_name: &str) -> Result<u8, Database_error>{
+        let table = match self.tables.get_mut(table_name){
+            None    => return Err(Database_error::Table_not_found(table_name.to_string())),
+            Some(t) => t,
+        };
+
+        table.modify_column_name(old_name.to_string(), new_name.to_string(), &mut self.page_table, &mut self.file_manager)?;
+
+        return Ok(1)
+    }
+
+
+
+    //checks that every foreign key of a table's columns refers to a table with a primary key of a
+    //single column of the same type. A table can refer to itself. The columns given in schemas are
+    //used instead of the current columns of their tables, so a change can be checked before it is
+    //made.
+    fn check_foreign_keys(&self, table_name: &str, schemas: &[(&str, &[Column])]) -> Result<u8, Database_error>{
+        let schema = |name: &str| -> &[Column] {
+            return match schemas.iter().find(|(schema_name, _)| *schema_name == name){
+                Some((_, columns))  => columns,
+                None                => self.tables.get(name).map_or(&[], |t| t.column_schema.as_slice()),
+            }
+        };
+
+        for column in schema(table_name){
+            let foreign_key = match &column.references{
+                None    => continue,
+                Some(f) => f,
+            };
+
+            let key_columns: Vec<&Column> = schema(&foreign_key.table_name).iter().filter(|other| other.primary_key).collect();
+
+            let valid = match key_columns[..]{
+                [key]   => key.data_type == column.data_type && !(column.not_null && foreign_key.on_delete == On_delete::Set_null),
+                _       => false,
+            };
+
+            if !valid{
+                return Err(Database_error::Invalid_foreign_key(column.column_name.clone()))
+            }
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //changes the type of a column, see Table::modify_column_type. The foreign keys of the table and
+    //of the tables that refer to it have to hold with the new type.
+    pub fn modify_column_type(&mut self, table_name: &str, column_name: &str, new_type: Data_type) -> Result<u8, Database_error>{
+        let mut columns = match self.tables.get(table_name){
+            None    => return Err(Database_error::Table_not_found(table_name.to_string())),
+            Some(t) => t.column_schema.clone(),
+        };
+
+        match columns.iter_mut().find(|column| column.column_name == column_name){
+            None            => return Err(Table_error::Column_not_found(column_name.to_string()).into()),
+            Some(column)    => column.data_type = new_type.clone(),
+        };
+
+        let schemas = [(table_name, columns.as_slice())];
+        self.check_foreign_keys(table_name, &schemas)?;
+
+        for (child_name, _, _) in self.references_to(table_name){
+            self.check_foreign_keys(&child_name, &schemas)?;
+        }
+
+        let table = self.tables.get_mut(table_name).unwrap();
+        table.modify_column_type(column_name.to_string(), new_type, &mut self.page_table, &mut self.file_manager)?;
+
+        return Ok(1)
+    }
+
+
+
+    //checks that a value of a column is the primary key of a record in the table the column refers
+    //to, if it does.
+    fn check_reference(&mut self, table_name: &str, column: &Column, value: &Value) -> Result<u8, Database_error>{
+        let foreign_key = match &column.references{
+            None    => return Ok(0),
+            Some(f) => f,
+        };
+
+        if *value == Value::Null{
+            return Ok(0)
+        }
+
+        let parent = match self.tables.get(&foreign_key.table_name){
+            None    => return Err(Database_error::Table_not_found(foreign_key.table_name.clone())),
+            Some(t) => t,
+        };
+
+        if parent.find_record(std::slice::from_ref(value), &mut self.page_table, &mut self.file_manager)?.is_none(){
+            return Err(Database_error::Foreign_key_violation{table_name: table_name.to_string(), column_name: column.column_name.clone()})
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //the columns that refer to a table, as the name of their table, their position in its schema
+    //and what they do when a record they refer to is removed.
+    fn references_to(&self, table_name: &str) -> Vec<(String, usize, On_delete)>{
+        let mut references = Vec::new();
+
+        for (child_name, child) in self.tables.iter(){
+            for (i, column) in child.column_schema.iter().enumerate(){
+                if let Some(foreign_key) = &column.references && foreign_key.table_name == table_name{
+                    references.push((child_name.clone(), i, foreign_key.on_delete));
+                }
+            }
+        }
+
+        return references
+    }
+
+
+
+    //the records of a table with a value in one of their columns, found through the column's index
+    //if it has one.
+    fn records_with_value(&mut self, table_name: &str, column_index: usize, value: &Value) -> Result<Vec<Record_ID>, Database_error>{
+        let table = match self.tables.get(table_name){
+            None    => return Err(Database_error::Table_not_found(table_name.to_string())),
+            Some(t) => t,
+        };
+
+        let column_name = &table.column_schema[column_index].column_name;
+
+        return Ok(table.find_records(column_name, value, &mut self.page_table, &mut self.file_manager)?)
+    }
+
+
 
-        let variable_data_index_table = Table::new("Variable_Data_Index_Table".to_string());
-        //TODO: IMPLEMENT U32 and U16 data types for table.
-        variable_data_index_table.add_column("ID".to_string(), Data_Type::U32, page_table, file_manager);
-        variable_data_index_table.add_column("Page_Num".to_string(), Data_Type::U32, page_table, file_manager);
-        variable_data_index_table.add_column("Page_Index".to_string(), Data_Type::U16, page_table, file_manager);
+    //adds a record to a table after checking that its foreign keys refer to existing records.
+    pub fn add_record(&mut self, table_name: &str, record: Vec<Value>) -> Result<Added_record, Database_error>{
+        let columns = match self.tables.get(table_name){
+            None    => return Err(Database_error::Table_not_found(table_name.to_string())),
+            Some(t) => t.column_schema.clone(),
+        };
 
-        
-        //Continued if I need more initialisation code
+        //a record with the wrong number of values is rejected by the table.
+        if record.len() == columns.len(){
+            for (column, value) in columns.iter().zip(record.iter()){
+                self.check_reference(table_name, column, value)?;
+            }
+        }
 
+        let table = self.tables.get_mut(table_name).unwrap();
 
+        return Ok(table.add_record(record, &mut self.page_table, &mut self.file_manager)?)
     }
 
 
+
+    //adds a record given by column name to a table, see Table::add_named_record.
+    pub fn add_named_record(&mut self, table_name: &str, values: Vec<(String, Value)>) -> Result<Added_record, Database_error>{
+        let record = match self.tables.get(table_name){
+            None    => return Err(Database_error::Table_not_found(table_name.to_string())),
+            Some(t) => t.named_record(values)?,
+        };
+
+        return self.add_record(table_name, record)
+    }
+
+
+
+    //changes columns of a record after checking the new foreign keys. The primary key of a record
+    //other records refer to cannot change, the change is rejected rather than passed on to them.
+    pub fn modify_record(&mut self, table_name: &str, record_id: Record_ID, changes: Vec<(String, Value)>) -> Result<u8, Database_error>{
+        let table = match self.tables.get(table_name){
+            None    => return Err(Database_error::Table_not_found(table_name.to_string())),
+            Some(t) => t,
+        };
+
+        let columns = table.column_schema.clone();
+        let key_index = table.primary_key_index();
+
+        for (column_name, value) in changes.iter(){
+            let column_index = match columns.iter().position(|column| column.column_name == *column_name){
+                None    => return Err(Table_error::Column_not_found(column_name.clone()).into()),
+                Some(i) => i,
+            };
+
+            self.check_reference(table_name, &columns[column_index], value)?;
+
+            if Some(column_index) != key_index{
+                continue
+            }
+
+            let old_key = self.tables[table_name].get_record(record_id, &mut self.page_table, &mut self.file_manager)?.swap_remove(column_index);
+
+            if *value == old_key{
+                continue
+            }
+
+            for (child_name, child_column, _) in self.references_to(table_name){
+                if !self.records_with_value(&child_name, child_column, &old_key)?.is_empty(){
+                    let column_name = self.tables[&child_name].column_schema[child_column].column_name.clone();
+                    return Err(Database_error::Foreign_key_violation{table_name: child_name, column_name})
+                }
+            }
+        }
+
+        let table = self.tables.get_mut(table_name).unwrap();
+        table.modify_record(record_id, changes, &mut self.page_table, &mut self.file_manager)?;
+
+        return Ok(1)
+    }
+
+
+
+    //removes a record and deals with the records referring to it as their foreign keys say:
+    //RESTRICT stops the removal, CASCADE removes them as well and SET NULL clears the column.
+    //Everything that would be removed is found first, so nothing changes if a RESTRICT is hit.
+    pub fn remove_record(&mut self, table_name: &str, record_id: Record_ID) -> Result<u8, Database_error>{
+        if !self.tables.contains_key(table_name){
+            return Err(Database_error::Table_not_found(table_name.to_string()))
+        }
+
+        let mut removals: Vec<(String, Record_ID)> = vec![(table_name.to_string(), record_id)];
+        let mut removal_set: HashSet<(String, Record_ID)> = removals.iter().cloned().collect();
+        let mut set_nulls: Vec<(String, Record_ID, String)> = Vec::new();
+        let mut restricted: Vec<(String, Record_ID, String)> = Vec::new();
+
+        let mut i = 0;
+        while i < removals.len(){
+            let (table_name, record_id) = removals[i].clone();
+            i += 1;
+
+            let table = &self.tables[&table_name];
+
+            let key = match table.primary_key_index(){
+                None    => continue,
+                Some(k) => table.get_record(record_id, &mut self.page_table, &mut self.file_manager)?.swap_remove(k),
+            };
+
+            for (child_name, column_index, on_delete) in self.references_to(&table_name){
+                let column_name = self.tables[&child_name].column_schema[column_index].column_name.clone();
+
+                for child_id in self.records_with_value(&child_name, column_index, &key)?{
+                    match on_delete{
+                        On_delete::Restrict => restricted.push((child_name.clone(), child_id, column_name.clone())),
+                        On_delete::Set_null => set_nulls.push((child_name.clone(), child_id, column_name.clone())),
+                        On_delete::Cascade  => {
+                            if removal_set.insert((child_name.clone(), child_id)){
+                                removals.push((child_name.clone(), child_id));
+                            }
+                        },
+                    }
+                }
+            }
+        }
+
+        //a record that refers to a removed record is fine if it is removed too.
+        if let Some((child_name, _, column_name)) = restricted.into_iter().find(|(child_name, child_id, _)| !removal_set.contains(&(child_name.clone(), *child_id))){
+            return Err(Database_error::Foreign_key_violation{table_name: child_name, column_name})
+        }
+
+        for (child_name, child_id, column_name) in set_nulls{
+            if !removal_set.contains(&(child_name.clone(), child_id)){
+                let child = self.tables.get_mut(&child_name).unwrap();
+                child.modify_record(child_id, vec![(column_name, Value::Null)], &mut self.page_table, &mut self.file_manager)?;
+            }
+        }
+
+        for (table_name, record_id) in removals{
+            let table = self.tables.get_mut(&table_name).unwrap();
+            table.remove_record(record_id, &mut self.page_table, &mut self.file_manager)?;
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //writes every page in memory to disk, see Table::checkpoint.
+    pub fn checkpoint(&mut self) -> Result<u8, Database_error>{
+        self.page_table.write_all(&mut self.file_manager)?;
+
+        return Ok(1)
+    }
+
+
+
+    //checkpoints the database and closes its files.
+    pub fn close(mut self) -> Result<u8, Database_error>{
+        self.checkpoint()?;
+        self.file_manager.close_all();
+
+        return Ok(1)
+    }
+
+
+}
+
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::table::table::Foreign_key;
+    use crate::test_utils::temp_directory;
+
+
+    fn key_column(name: &str) -> Column{
+        let mut column = Column::new(name.to_string(), Data_type::Int);
+        column.primary_key = true;
+        return column
+    }
+
+    fn reference(name: &str, table_name: &str, on_delete: On_delete) -> Column{
+        let mut column = Column::new(name.to_string(), Data_type::Int);
+        column.references = Some(Foreign_key{table_name: table_name.to_string(), on_delete});
+        return column
+    }
+
+    fn add(database: &mut Database, table_name: &str, values: &[Option<i64>]) -> Record_ID{
+        let record = values.iter().map(|value| value.map_or(Value::Null, Value::Int)).collect();
+        return database.add_record(table_name, record).unwrap().record_id
+    }
+
+    //the values of every record of a table, ordered by the first column.
+    fn records(database: &mut Database, table_name: &str) -> Vec<Vec<Option<i64>>>{
+        let mut records: Vec<Vec<Option<i64>>> = database.tables[table_name].scan(&mut database.page_table, &mut database.file_manager)
+            .map(|item| item.unwrap().1.iter().map(|value| match value{
+                Value::Int(n)   => Some(*n),
+                _               => None,
+            }).collect())
+            .collect();
+
+        records.sort();
+        return records
+    }
+
+
+
+    #[test]
+    fn remove_record_cascades_down_a_chain(){
+        let mut database = Database::open(&temp_directory("cascade_chain")).unwrap();
+        database.create_table("country", vec![key_column("id")]).unwrap();
+        database.create_table("city", vec![key_column("id"), reference("country", "country", On_delete::Cascade)]).unwrap();
+        database.create_table("street", vec![key_column("id"), reference("city", "city", On_delete::Cascade)]).unwrap();
+
+        let removed = add(&mut database, "country", &[Some(1)]);
+        add(&mut database, "country", &[Some(2)]);
+
+        for (city, country) in [(10, 1), (11, 1), (20, 2)]{
+            add(&mut database, "city", &[Some(city), Some(country)]);
+        }
+
+        for (street, city) in [(100, 10), (101, 10), (110, 11), (200, 20)]{
+            add(&mut database, "street", &[Some(street), Some(city)]);
+        }
+
+        database.remove_record("country", removed).unwrap();
+
+        assert_eq!(records(&mut database, "country"), vec![vec![Some(2)]]);
+        assert_eq!(records(&mut database, "city"), vec![vec![Some(20), Some(2)]]);
+        assert_eq!(records(&mut database, "street"), vec![vec![Some(200), Some(20)]]);
+    }
+
+
+
+    #[test]
+    fn remove_record_cascades_through_a_self_reference(){
+        let mut database = Database::open(&temp_directory("cascade_self")).unwrap();
+        database.create_table("employee", vec![key_column("id"), reference("manager", "employee", On_delete::Cascade)]).unwrap();
+
+        let boss = add(&mut database, "employee", &[Some(1), None]);
+        for (id, manager) in [(2, Some(1)), (3, Some(2)), (4, None), (5, Some(2))]{
+            add(&mut database, "employee", &[Some(id), manager]);
+        }
+
+        //a record that refers to itself is removed once.
+        let own_manager = add(&mut database, "employee", &[Some(6), None]);
+        database.modify_record("employee", own_manager, vec![("manager".to_string(), Value::Int(6))]).unwrap();
+
+        database.remove_record("employee", boss).unwrap();
+        assert_eq!(records(&mut database, "employee"), vec![vec![Some(4), None], vec![Some(6), Some(6)]]);
+
+        database.remove_record("employee", own_manager).unwrap();
+        assert_eq!(records(&mut database, "employee"), vec![vec![Some(4), None]]);
+    }
+
+
+
+    #[test]
+    fn remove_record_sets_references_to_null(){
+        let mut database = Database::open(&temp_directory("set_null")).unwrap();
+        database.create_table("parent", vec![key_column("id")]).unwrap();
+        database.create_table("child", vec![key_column("id"), reference("parent", "parent", On_delete::Set_null)]).unwrap();
+
+        let removed = add(&mut database, "parent", &[Some(1)]);
+        add(&mut database, "parent", &[Some(2)]);
+
+        for (child, parent) in [(10, 1), (11, 1), (20, 2)]{
+            add(&mut database, "child", &[Some(child), Some(parent)]);
+        }
+
+        database.remove_record("parent", removed).unwrap();
+
+        assert_eq!(records(&mut database, "parent"), vec![vec![Some(2)]]);
+        assert_eq!(records(&mut database, "child"), vec![vec![Some(10), None], vec![Some(11), None], vec![Some(20), Some(2)]]);
+    }
+
+
+
+    #[test]
+    fn remove_record_restrict_changes_nothing(){
+        let mut database = Database::open(&temp_directory("restrict")).unwrap();
+        database.create_table("a", vec![key_column("id")]).unwrap();
+        database.create_table("b", vec![key_column("id"), reference("a", "a", On_delete::Cascade)]).unwrap();
+        database.create_table("c", vec![key_column("id"), reference("b", "b", On_delete::Restrict)]).unwrap();
+        database.create_table("d", vec![key_column("id"), reference("a", "a", On_delete::Set_null)]).unwrap();
+
+        let removed = add(&mut database, "a", &[Some(1)]);
+        add(&mut database, "b", &[Some(10), Some(1)]);
+        add(&mut database, "c", &[Some(100), Some(10)]);
+        add(&mut database, "d", &[Some(1000), Some(1)]);
+
+        let before: Vec<_> = ["a", "b", "c", "d"].iter().map(|name| records(&mut database, name)).collect();
+
+        //c refers to the record of b the removal would cascade to.
+        match database.remove_record("a", removed){
+            Err(Database_error::Foreign_key_violation{table_name, column_name}) => assert_eq!((table_name.as_str(), column_name.as_str()), ("c", "b")),
+            other                                                               => panic!("{:?}", other),
+        }
+
+        let after: Vec<_> = ["a", "b", "c", "d"].iter().map(|name| records(&mut database, name)).collect();
+        assert_eq!(after, before);
+    }
+
+
+
+    #[test]
+    fn modify_column_type_checks_foreign_keys(){
+        let mut database = Database::open(&temp_directory("modify_type_foreign_keys")).unwrap();
+        database.create_table("parent", vec![key_column("id"), Column::new("name".to_string(), Data_type::Int)]).unwrap();
+        database.create_table("child", vec![key_column("id"), reference("parent", "parent", On_delete::Restrict)]).unwrap();
+
+        add(&mut database, "parent", &[Some(1), Some(5)]);
+        add(&mut database, "child", &[Some(10), Some(1)]);
+
+        //the key a column refers to and the column have to keep the same type.
+        for (table_name, column_name) in [("parent", "id"), ("child", "parent")]{
+            match database.modify_column_type(table_name, column_name, Data_type::U32){
+                Err(Database_error::Invalid_foreign_key(column_name))   => assert_eq!(column_name, "parent"),
+                other                                                   => panic!("{:?}", other),
+            }
+        }
+
+        assert_eq!(database.tables["parent"].column_schema[0].data_type, Data_type::Int);
+        assert_eq!(database.tables["child"].column_schema[1].data_type, Data_type::Int);
+
+        database.modify_column_type("parent", "name", Data_type::U32).unwrap();
+        assert_eq!(database.tables["parent"].column_schema[1].data_type, Data_type::U32);
+    }
+
+
+
+    #[test]
+    fn tables_are_opened_again_from_the_catalog(){
+        let directory = temp_directory("reopen_database");
+        let mut database = Database::open(&directory).unwrap();
+        database.create_table("parent", vec![key_column("id")]).unwrap();
+        database.create_table("child", vec![key_column("id"), reference("parent", "parent", On_delete::Cascade)]).unwrap();
+
+        for i in 0..100{
+            add(&mut database, "parent", &[Some(i)]);
+            add(&mut database, "child", &[Some(i + 1000), Some(i)]);
+        }
+
+        //everything the reopened database needs is on disk after a checkpoint.
+        database.checkpoint().unwrap();
+        drop(database);
+
+        let mut database = Database::open(&directory).unwrap();
+        assert_eq!(database.table_names(), vec!["child".to_string(), "parent".to_string()]);
+        assert_eq!(records(&mut database, "parent"), (0..100).map(|i| vec![Some(i)]).collect::<Vec<_>>());
+        assert_eq!(records(&mut database, "child"), (0..100).map(|i| vec![Some(i + 1000), Some(i)]).collect::<Vec<_>>());
+
+        //the foreign key still holds after the reopen.
+        let parent = database.tables["parent"].find_record(&[Value::Int(0)], &mut database.page_table, &mut database.file_manager).unwrap().unwrap();
+        database.remove_record("parent", parent).unwrap();
+        assert_eq!(records(&mut database, "child").len(), 99);
+    }
+
+
+
+    #[test]
+    fn drop_table_removes_its_file_and_catalog_entry(){
+        let directory = temp_directory("drop_table");
+        let mut database = Database::open(&directory).unwrap();
+        database.create_table("parent", vec![key_column("id")]).unwrap();
+        database.create_table("child", vec![key_column("id"), reference("parent", "parent", On_delete::Restrict)]).unwrap();
+        add(&mut database, "parent", &[Some(1)]);
+
+        match database.drop_table("parent"){
+            Err(Database_error::Table_referenced(table_name))   => assert_eq!(table_name, "child"),
+            other                                               => panic!("{:?}", other),
+        }
+
+        database.drop_table("child").unwrap();
+        database.drop_table("parent").unwrap();
+
+        assert!(database.table_names().is_empty());
+        assert!(!Path::new(&format!("{}/parent", directory)).exists());
+        assert!(!Path::new(&format!("{}/child", directory)).exists());
+        assert_eq!(std::fs::read_to_string(format!("{}/{}", directory, CATALOG_FILE_NAME)).unwrap(), "");
+
+        //a table of the same name starts out empty.
+        database.close().unwrap();
+        let mut database = Database::open(&directory).unwrap();
+        assert!(database.table_names().is_empty());
+        database.create_table("parent", vec![key_column("id")]).unwrap();
+        assert!(records(&mut database, "parent").is_empty());
+    }
+
+
+
+    #[test]
+    fn old_records_follow_added_and_dropped_columns(){
+        let directory = temp_directory("schema_evolution");
+        let mut database = Database::open(&directory).unwrap();
+        database.create_table("person", vec![key_column("id"), Column::new("name".to_string(), Data_type::String)]).unwrap();
+
+        for i in 0..5{
+            database.add_record("person", vec![Value::Int(i), Value::String(format!("person {}", i))]).unwrap();
+        }
+
+        let mut age = Column::new("age".to_string(), Data_type::Int);
+        age.default = Some(Value::Int(18));
+        database.add_column("person", age).unwrap();
+        database.add_column("person", Column::new("note".to_string(), Data_type::String)).unwrap();
+
+        //a column the records already in the table cannot have a value for is rejected.
+        let mut nickname = Column::new("nickname".to_string(), Data_type::String);
+        nickname.not_null = true;
+        let mut code = Column::new("code".to_string(), Data_type::Int);
+        code.unique = true;
+        code.default = Some(Value::Int(1));
+
+        for column in [nickname, code]{
+            match database.add_column("person", column){
+                Err(Database_error::Table(Table_error::Not_null_violation(_) | Table_error::Unique_violation(_)))   => (),
+                other                                                                                               => panic!("{:?}", other),
+            }
+        }
+
+        let values = |database: &mut Database| -> Vec<Vec<Value>> {
+            return database.tables["person"].scan(&mut database.page_table, &mut database.file_manager).map(|item| item.unwrap().1).collect()
+        };
+
+        //records written before a column was added read its default, or null without one.
+        let old = |i: i64| vec![Value::Int(i), Value::String(format!("person {}", i)), Value::Int(18), Value::Null];
+        assert_eq!(values(&mut database), (0..5).map(old).collect::<Vec<_>>());
+
+        database.add_record("person", vec![Value::Int(5), Value::String("person 5".to_string()), Value::Int(30), Value::String("new".to_string())]).unwrap();
+        database.remove_column("person", "name").unwrap();
+
+        //and a dropped column is left out of every record.
+        let mut expected: Vec<Vec<Value>> = (0..5).map(|i| vec![Value::Int(i), Value::Int(18), Value::Null]).collect();
+        expected.push(vec![Value::Int(5), Value::Int(30), Value::String("new".to_string())]);
+        assert_eq!(values(&mut database), expected);
+
+        //the records are written again with the current schema a few at a time, after which the
+        //dropped column is gone from the schema.
+        assert_eq!(database.rewrite_old_records(4).unwrap(), 4);
+        assert_eq!(database.tables["person"].column_history.len(), 4);
+        assert_eq!(database.rewrite_old_records(4).unwrap(), 2);
+        assert_eq!(database.rewrite_old_records(4).unwrap(), 0);
+        assert_eq!(database.tables["person"].column_history.len(), 3);
+        assert_eq!(values(&mut database), expected);
+
+        database.close().unwrap();
+        let mut database = Database::open(&directory).unwrap();
+        assert_eq!(values(&mut database), expected);
+    }
 }
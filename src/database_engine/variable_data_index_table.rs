@@ -1,41 +1,325 @@
 //This will be a table with the column structure of 4 byte number for ID then 4 byte number for
 //page number and 2 bytes for the index position in the page of the data.
 //
+// 0 0 0 0 | 0 0
+// page num  index
 //
+//The table is kept in a chain of Variable_data_index pages. The ID of a piece of data is its
+//position in the chain, so the entry of ID n is the (n-1)th entry counting from the first page.
+//ID 0 is never handed out, records use it for empty data. An entry with page num 0 is unused and
+//its ID can be given out again. The first index page is stored in the table header and is only
+//created once the first ID is needed.
 
-pub struct variable_data_index_table{
-    table: Table,
+use std::io::Error;
+use std::io::ErrorKind;
+
+use crate::file_manager::page::Page_type;
+use crate::file_manager::page::PAGE_HEADER_SIZE;
+use crate::file_manager::block::Block_ID;
+use crate::file_manager::file_manager::File_manager;
+use crate::buffer_pool::page_table::Page_table;
+use crate::table::free_page_list::allocate_page;
+use crate::table::table::VARIABLE_DATA_INDEX_OFFSET;
+
+
+const ENTRY_SIZE: u16 = 6;
+
+
+#[derive(Debug)]
+pub struct Variable_data_index_table{
+    pub file_name:      String,
+    //the index pages in chain order.
+    pub page_nums:      Vec<u32>,
+    //IDs whose entries are unused.
+    free_ids:           Vec<u32>,
+    //entries each index page holds.
+    entries_per_page:   u32,
 }
 
 
 
 
-impl variable_data_index_table{
+impl Variable_data_index_table{
+
+
+    //loads the index page chain of a table file and collects the unused IDs.
+    pub fn initialise(file_name: String, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Variable_data_index_table, std::io::Error>{
+        let entries_per_page = ((page_table.page_size - PAGE_HEADER_SIZE - 2) as u32) / ENTRY_SIZE as u32;
+
+        let header = match page_table.get_mut_page(Block_ID{file_name: file_name.clone(), number: 0}, file_manager){
+            None    => return Err(Error::other("Table header page could not be loaded.")),
+            Some(p) => p,
+        };
+
+        let offset = VARIABLE_DATA_INDEX_OFFSET as usize;
+        let mut current = u32::from_be_bytes(header.bytes[offset .. offset + 4].try_into().unwrap());
 
+        let mut page_nums = Vec::new();
+        let mut free_ids = Vec::new();
 
-    pub fn initialise(&mut page_table, &mut file_manager){
+        while current != 0{
+            let page = match page_table.get_mut_page(Block_ID{file_name: file_name.clone(), number: current}, file_manager){
+                None    => return Err(Error::other("Variable data index page could not be loaded.")),
+                Some(p) => p,
+            };
 
-        //First create table
-        //
-        //Then initialise the correct column structure
-        //
-        //Save to file and return the struct
+            let first_id = page_nums.len() as u32 * entries_per_page + 1;
+            let mut index = PAGE_HEADER_SIZE;
+            while index + ENTRY_SIZE <= page.data_end_point{
+                let i = index as usize;
+                if u32::from_be_bytes(page.bytes[i .. i + 4].try_into().unwrap()) == 0{
+                    free_ids.push(first_id + ((index - PAGE_HEADER_SIZE) / ENTRY_SIZE) as u32);
+                }
+                index += ENTRY_SIZE;
+            }
 
+            page_nums.push(current);
+            current = page.next_index.unwrap_or(0);
+        }
+
+        //lowest IDs are handed out first.
+        free_ids.reverse();
+
+        return Ok(Variable_data_index_table{
+            file_name:          file_name,
+            page_nums:          page_nums,
+            free_ids:           free_ids,
+            entries_per_page:   entries_per_page,
+        })
     }
 
 
 
+    //the page and byte position of the entry of an ID.
+    fn entry_position(&self, id: u32) -> Option<(u32, u16)>{
+        if id == 0{
+            return None
+        }
+
+        let page = ((id - 1) / self.entries_per_page) as usize;
+        let entry = (id - 1) % self.entries_per_page;
+
+        let page_num = *self.page_nums.get(page)?;
+        return Some((page_num, PAGE_HEADER_SIZE + entry as u16 * ENTRY_SIZE))
+    }
 
 
 
+    //gives out an ID pointing at the page num and index of a piece of data.
+    pub fn insert(&mut self, page_num: u32, index: u16, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u32, std::io::Error>{
+        if let Some(id) = self.free_ids.pop(){
+            self.set(id, page_num, index, page_table, file_manager)?;
+            return Ok(id)
+        }
 
+        //the last index page is full (or there is none yet), so the chain grows.
+        let last_full = match self.page_nums.last(){
+            None            => true,
+            Some(number)    => {
+                match page_table.get_mut_page(Block_ID{file_name: self.file_name.clone(), number: *number}, file_manager){
+                    None    => return Err(Error::other("Variable data index page could not be loaded.")),
+                    Some(p) => p.data_end_point + ENTRY_SIZE > PAGE_HEADER_SIZE + self.entries_per_page as u16 * ENTRY_SIZE,
+                }
+            },
+        };
 
+        if last_full{
+            self.new_index_page(page_table, file_manager)?;
+        }
 
+        let last_page_num = *self.page_nums.last().unwrap();
+        let block = Block_ID{file_name: self.file_name.clone(), number: last_page_num};
 
+        let page = match page_table.get_mut_page(block.clone(), file_manager){
+            None    => return Err(Error::other("Variable data index page could not be loaded.")),
+            Some(p) => p,
+        };
+
+        let id = (self.page_nums.len() as u32 - 1) * self.entries_per_page + ((page.data_end_point - PAGE_HEADER_SIZE) / ENTRY_SIZE) as u32 + 1;
+
+        let mut entry = page_num.to_be_bytes().to_vec();
+        entry.extend_from_slice(&index.to_be_bytes());
+        page.write_at_end(entry);
+        page_table.set_dirty(&block);
+
+        return Ok(id)
+    }
 
 
 
+    //adds an empty index page to the end of the chain, or as the first page in the table header.
+    fn new_index_page(&mut self, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u32, std::io::Error>{
+        let page_num = allocate_page(&self.file_name, Page_type::Variable_data_index, page_table, file_manager)?;
+
+        match self.page_nums.last(){
+            None                        => {
+                let header_block = Block_ID{file_name: self.file_name.clone(), number: 0};
+                match page_table.get_mut_page(header_block.clone(), file_manager){
+                    None    => return Err(Error::other("Table header page could not be loaded.")),
+                    Some(p) => p.write(VARIABLE_DATA_INDEX_OFFSET, page_num.to_be_bytes().to_vec())?,
+                };
+                page_table.set_dirty(&header_block);
+            },
+
+            Some(previous_page_num)     => {
+                let previous_block = Block_ID{file_name: self.file_name.clone(), number: *previous_page_num};
+                match page_table.get_mut_page(previous_block.clone(), file_manager){
+                    None    => return Err(Error::other("Variable data index page could not be loaded.")),
+                    Some(p) => p.set_next_page_num(page_num),
+                };
+                page_table.set_dirty(&previous_block);
+
+                let block = Block_ID{file_name: self.file_name.clone(), number: page_num};
+                match page_table.get_mut_page(block.clone(), file_manager){
+                    None    => return Err(Error::other("Variable data index page could not be loaded.")),
+                    Some(p) => p.set_previous_page_num(*previous_page_num),
+                };
+                page_table.set_dirty(&block);
+            },
+        };
+
+        self.page_nums.push(page_num);
+
+        return Ok(page_num)
+    }
+
+
+
+    //the page num and index an ID points at.
+    pub fn get(&self, id: u32, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<(u32, u16), std::io::Error>{
+        let (page_num, offset) = match self.entry_position(id){
+            None    => return Err(Error::new(ErrorKind::NotFound, "Unknown variable data ID.")),
+            Some(p) => p,
+        };
+
+        let page = match page_table.get_mut_page(Block_ID{file_name: self.file_name.clone(), number: page_num}, file_manager){
+            None    => return Err(Error::other("Variable data index page could not be loaded.")),
+            Some(p) => p,
+        };
+
+        if offset + ENTRY_SIZE > page.data_end_point{
+            return Err(Error::new(ErrorKind::NotFound, "Unknown variable data ID."))
+        }
+
+        let i = offset as usize;
+        let data_page_num = u32::from_be_bytes(page.bytes[i .. i + 4].try_into().unwrap());
+        let index = u16::from_be_bytes(page.bytes[i + 4 .. i + 6].try_into().unwrap());
+
+        if data_page_num == 0{
+            return Err(Error::new(ErrorKind::NotFound, "Unknown variable data ID."))
+        }
+
+        return Ok((data_page_num, index))
+    }
+
+
 
+    //points an ID at the new location of its data.
+    pub fn set(&mut self, id: u32, page_num: u32, index: u16, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+        let (index_page_num, offset) = match self.entry_position(id){
+            None    => return Err(Error::new(ErrorKind::NotFound, "Unknown variable data ID.")),
+            Some(p) => p,
+        };
 
+        let block = Block_ID{file_name: self.file_name.clone(), number: index_page_num};
+        let page = match page_table.get_mut_page(block.clone(), file_manager){
+            None    => return Err(Error::other("Variable data index page could not be loaded.")),
+            Some(p) => p,
+        };
 
+        if offset + ENTRY_SIZE > page.data_end_point{
+            return Err(Error::new(ErrorKind::NotFound, "Unknown variable data ID."))
+        }
+
+        let mut entry = page_num.to_be_bytes().to_vec();
+        entry.extend_from_slice(&index.to_be_bytes());
+        page.write(offset, entry)?;
+        page_table.set_dirty(&block);
+
+        return Ok(1)
+    }
+
+
+
+    //marks the entry of an ID as unused so the ID can be given out again.
+    pub fn remove(&mut self, id: u32, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+        self.get(id, page_table, file_manager)?;
+        self.set(id, 0, 0, page_table, file_manager)?;
+        self.free_ids.push(id);
+
+        return Ok(1)
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::test_utils::open_test_table;
+
+
+    #[test]
+    fn id_zero_is_never_given_out(){
+        let (mut fm, mut pt, _) = open_test_table("index_table_zero");
+        let mut index_table = Variable_data_index_table::initialise("index_table_zero".to_string(), &mut pt, &mut fm).unwrap();
+
+        assert!(index_table.get(0, &mut pt, &mut fm).is_err());
+        assert!(index_table.set(0, 5, 0, &mut pt, &mut fm).is_err());
+        assert_eq!(index_table.insert(5, 0, &mut pt, &mut fm).unwrap(), 1);
+        assert!(index_table.get(0, &mut pt, &mut fm).is_err());
+    }
+
+
+
+    #[test]
+    fn freed_ids_are_given_out_again(){
+        let (mut fm, mut pt, _) = open_test_table("index_table_reuse");
+        let mut index_table = Variable_data_index_table::initialise("index_table_reuse".to_string(), &mut pt, &mut fm).unwrap();
+
+        for i in 1..=5{
+            assert_eq!(index_table.insert(5, i as u16, &mut pt, &mut fm).unwrap(), i);
+        }
+
+        index_table.remove(4, &mut pt, &mut fm).unwrap();
+        index_table.remove(2, &mut pt, &mut fm).unwrap();
+        assert!(index_table.get(2, &mut pt, &mut fm).is_err());
+        assert!(index_table.remove(2, &mut pt, &mut fm).is_err());
+
+        //the unused IDs are found again when the table is loaded, lowest first.
+        let mut index_table = Variable_data_index_table::initialise("index_table_reuse".to_string(), &mut pt, &mut fm).unwrap();
+
+        assert_eq!(index_table.insert(6, 0, &mut pt, &mut fm).unwrap(), 2);
+        assert_eq!(index_table.insert(6, 1, &mut pt, &mut fm).unwrap(), 4);
+        assert_eq!(index_table.insert(6, 2, &mut pt, &mut fm).unwrap(), 6);
+
+        assert_eq!(index_table.get(2, &mut pt, &mut fm).unwrap(), (6, 0));
+        assert_eq!(index_table.get(3, &mut pt, &mut fm).unwrap(), (5, 3));
+    }
+
+
+
+    #[test]
+    fn the_chain_grows_past_one_page(){
+        let (mut fm, mut pt, _) = open_test_table("index_table_chain");
+        let mut index_table = Variable_data_index_table::initialise("index_table_chain".to_string(), &mut pt, &mut fm).unwrap();
+
+        let entries_per_page = ((pt.page_size - PAGE_HEADER_SIZE - 2) / ENTRY_SIZE) as u32;
+
+        for id in 1..=entries_per_page * 2 + 1{
+            assert_eq!(index_table.insert(id, (id % 100) as u16, &mut pt, &mut fm).unwrap(), id);
+        }
+        assert_eq!(index_table.page_nums.len(), 3);
+
+        let mut index_table = Variable_data_index_table::initialise("index_table_chain".to_string(), &mut pt, &mut fm).unwrap();
+        assert_eq!(index_table.page_nums.len(), 3);
+
+        for id in [1, entries_per_page, entries_per_page + 1, entries_per_page * 2 + 1]{
+            assert_eq!(index_table.get(id, &mut pt, &mut fm).unwrap(), (id, (id % 100) as u16));
+        }
+
+        index_table.set(entries_per_page + 1, 7, 7, &mut pt, &mut fm).unwrap();
+        assert_eq!(index_table.get(entries_per_page + 1, &mut pt, &mut fm).unwrap(), (7, 7));
+        assert_eq!(index_table.insert(1, 1, &mut pt, &mut fm).unwrap(), entries_per_page * 2 + 2);
+    }
 }
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::collections::hash_map::Entry;
 use crate::file_manager::page::Page;
+use crate::file_manager::page::Page_type;
+use crate::file_manager::page::PAGE_HEADER_SIZE;
 use crate::file_manager::block::Block_ID;
 use std::fs::File;
 use std::io::SeekFrom;
@@ -8,7 +10,6 @@ use std::io::Read;
 use std::io::Write;
 use std::io::Seek;
 use std::io::Error;
-use std::io::ErrorKind;
 use std::path::Path;
 
 pub struct File_manager{
@@ -47,32 +48,38 @@ impl File_manager{
 
         if block_total < block.number {
             //block not in file error
-            return Err(Error::new(ErrorKind::Other, "Block number is too large for file."));
+            return Err(Error::other("Block number is too large for file."));
         }
 
 
-        file.seek(SeekFrom::Start(u64::from(block_size) * u64::from(block.number)));
+        file.seek(SeekFrom::Start(u64::from(block_size) * u64::from(block.number)))?;
 
         let mut write_buffer = vec![0; block_size as usize];
 
-        file.read(&mut write_buffer)?;
+        //a block at the very end of the file has been allocated but not written yet, so it stays
+        //zeroed.
+        if block.number < block_total {
+            file.read_exact(&mut write_buffer)?;
+        }
 
-        let page_num_bytes = &write_buffer[0..4];
-        let page_type = &write_buffer[4];
+        let page_type = write_buffer[4];
         let prev_page_bytes: [u8; 4] = write_buffer[5..9].try_into().expect("error with getting prev page bytes at line 63 in filemanager");
-        let next_page_bytes = &write_buffer[9..13];
+        let next_page_bytes: [u8; 4] = write_buffer[9..13].try_into().expect("error with getting next page bytes in filemanager");
         let data_end_point_bytes = &write_buffer[13..15];
         let record_index_end_point_bytes = &write_buffer[15..17];
-        let content = &write_buffer[17..];
 
         page.page_num = block.number;
 
+        if let Ok(page_type) = Page_type::try_from(page_type){
+            page.page_type = page_type;
+        }
+
         page.previous_index = match u32::from_be_bytes(prev_page_bytes){
                                 0 => None,
                                 x => Some(x),
         };
 
-        page.next_index = match u32::from_be_bytes(prev_page_bytes){
+        page.next_index = match u32::from_be_bytes(next_page_bytes){
                             0 => None,
                             x => Some(x),
         };
@@ -81,7 +88,13 @@ impl File_manager{
         page.data_end_point = (data_end_point_bytes[0] as u16) << 8 | data_end_point_bytes[1] as u16;
         page.record_index_end_point = (record_index_end_point_bytes[0] as u16) << 8 | record_index_end_point_bytes[1] as u16;
 
-        page.write(0, write_buffer.to_vec());
+        //a zeroed block has no meta data yet, keep the defaults of the empty page.
+        if page.data_end_point == 0{
+            page.data_end_point = PAGE_HEADER_SIZE;
+            page.record_index_end_point = block_size - 2;
+        }
+
+        page.write(0, write_buffer.to_vec())?;
 
         //println!("------------------------>{:?}<-------------------------", page);
 
@@ -99,22 +112,14 @@ impl File_manager{
     pub fn write(&mut self, block: &Block_ID, page: &Page) -> Result<u8, std::io::Error>{
 
         let block_size = self.block_size;
-        let block_total = self.total_blocks(&block.file_name)?;
         let file = self.get_file(&block.file_name)?;
 
-        if block_total < block.number {
-
-            let blocks_to_be_added_number = block.number - block_total + 1;
-            let data = vec![0; (blocks_to_be_added_number * block_size as u32) as usize ];
-    
-            file.write(&data)?;
-        }
-
-        file.seek(SeekFrom::Start(u64::from(block_size) * u64::from(block.number)));
+        //seeking past the end of the file and writing there extends the file with zeroed blocks.
+        file.seek(SeekFrom::Start(u64::from(block_size) * u64::from(block.number)))?;
 
         let mut data = vec![0; page.size()];
 
-        page.read(0, &mut data);
+        page.read(0, &mut data)?;
 
         //adding the meta data:
         data[0..4].copy_from_slice(&block.number.to_be_bytes());
@@ -141,7 +146,7 @@ impl File_manager{
 
 
 
-        file.write(&data)?;
+        file.write_all(&data)?;
 
         return Ok(1)
     }
@@ -154,17 +159,26 @@ impl File_manager{
 
     pub fn close_all(&mut self){
 
-        let opened_files_iter = self.opened_files.iter();
-        for file in opened_files_iter {
-            drop(file);
-        }
-
+        //dropping the files closes them.
         self.opened_files.clear();
     }
 
 
 
 
+    //closes a file and deletes it from the data directory.
+    pub fn remove_file(&mut self, file_name: &String) -> Result<u8, std::io::Error>{
+        self.opened_files.remove(file_name);
+
+        let path_string = format!("{}/{}", self.data_directory, file_name);
+        std::fs::remove_file(Path::new(&path_string))?;
+
+        return Ok(1)
+    }
+
+
+
+
     pub fn total_blocks(&mut self, file_name: &String) -> Result<u32, std::io::Error>{
 
          let file = self.get_file(file_name)?;
@@ -181,7 +195,7 @@ impl File_manager{
             Entry::Vacant(entry)    =>{
                 let path_string = format!("{}/{}", self.data_directory, file_name);
                 let path = Path::new(&path_string);
-                let file = File::options().read(true).write(true).create(true).open(&path)?;
+                let file = File::options().read(true).write(true).create(true).truncate(false).open(path)?;
 
                 Ok(entry.insert(file))
 
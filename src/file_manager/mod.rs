@@ -1,3 +1,4 @@
 pub mod page;
 pub mod block;
+#[allow(clippy::module_inception)]
 pub mod file_manager;
@@ -1,5 +1,7 @@
 use std::io::Error;
-use std::io::ErrorKind;
+
+//bytes of meta data at the start of every page, see Page.
+pub const PAGE_HEADER_SIZE: u16 = 17;
 
 #[derive(Debug)]
 pub struct Page{
@@ -23,7 +25,7 @@ pub fn build_page(size: u16, page_num: u32, page_type: Page_type) -> Page{
         page_type:              page_type,
         previous_index:         None,
         next_index:             None,
-        data_end_point:         17, // meta data ends at byte 17 (index 16)
+        data_end_point:         PAGE_HEADER_SIZE, // meta data ends at byte 17 (index 16)
         record_index_end_point: size-2,
 
 
@@ -40,10 +42,30 @@ pub enum Page_type{
     Data = 4,
     Free_space_tracker = 5,
     Variable_data_index = 6,
+    Free = 7,
 
     
 }
 
+
+
+impl std::convert::TryFrom<u8> for Page_type {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Page_type::Table_structure),
+            2 => Ok(Page_type::Record),
+            3 => Ok(Page_type::B_tree),
+            4 => Ok(Page_type::Data),
+            5 => Ok(Page_type::Free_space_tracker),
+            6 => Ok(Page_type::Variable_data_index),
+            7 => Ok(Page_type::Free),
+            _ => Err(()),
+        }
+    }
+}
+
 //Page metadata:
 //
 // 0 0 0 0 | 0 | 0 0 0 0 | 0 0 0 0 | 0 0 | 0 0 
@@ -66,7 +88,7 @@ impl Page{
             page_type:              page_type,
             previous_index:         None,
             next_index:             None,
-            data_end_point:         17, // meta data ends at byte 17 (index 16)
+            data_end_point:         PAGE_HEADER_SIZE, // meta data ends at byte 17 (index 16)
             record_index_end_point: size - 2,
 
 
@@ -77,25 +99,25 @@ impl Page{
     pub fn set_data_end_point(&mut self, value: u16){
         self.data_end_point = value;
         let bytes = value.to_be_bytes();
-        self.write(13, bytes.to_vec());
+        let _ = self.write(13, bytes.to_vec());
     }
 
     pub fn set_record_index_end_point(&mut self, value: u16){
         self.record_index_end_point = value;
         let bytes = value.to_be_bytes();
-        self.write(15, bytes.to_vec());
+        let _ = self.write(15, bytes.to_vec());
     }
 
     pub fn set_previous_page_num(&mut self, value: u32){
         self.previous_index = Some(value);
         let bytes = value.to_be_bytes();
-        self.write(5, bytes.to_vec());
+        let _ = self.write(5, bytes.to_vec());
     }
 
     pub fn set_next_page_num(&mut self, value: u32){
         self.next_index = Some(value);
         let bytes = value.to_be_bytes();
-        self.write(9, bytes.to_vec());
+        let _ = self.write(9, bytes.to_vec());
 
 
     }
@@ -106,7 +128,7 @@ impl Page{
     pub fn write(&mut self, offset: u16, data: Vec<u8>) -> Result<u8, std::io::Error> {
 
         if  usize::from(offset) + data.len()     >   self.size(){
-            return Err(Error::new(ErrorKind::Other, "Page size is too small for this data."))
+            return Err(Error::other("Page size is too small for this data."))
         }
 
         self.bytes[ usize::from(offset) .. data.len() + usize::from(offset) ]
@@ -118,9 +140,9 @@ impl Page{
     pub fn write_at_end(&mut self, data: Vec<u8>){
         let amount = data.len();
         if amount > (self.record_index_end_point - self.data_end_point).into(){
-            return ()
+            return
         };
-        self.write(self.data_end_point, data);
+        let _ = self.write(self.data_end_point, data);
         self.data_end_point += amount as u16;
 
     }
@@ -128,15 +150,15 @@ impl Page{
 
 
 
-    pub fn read(&self, offset: u16, dst: &mut Vec<u8>) -> Result<u8, std::io::Error> {
+    pub fn read(&self, offset: u16, dst: &mut [u8]) -> Result<u8, std::io::Error> {
 
         let end_point = dst.len();
 
         if usize::from(offset) > self.size() {
-            return Err(Error::new(ErrorKind::Other, "Offest exceeds page size.")) 
+            return Err(Error::other("Offest exceeds page size.")) 
 
         }else if usize::from(offset)  +  end_point   >  self.size() {
-            let end_point = self.size();
+            return Err(Error::other("Page size is too small for this read."))
         }
 
 
@@ -191,7 +213,7 @@ impl Page{
         let mut record_index: Vec<u16> = vec![];
         
 
-        for i in (0.. slice.len() - 2 as usize).step_by(2){
+        for i in (0.. slice.len() - 2).step_by(2){
             let big_endian = slice[i];
             let little_endian = slice[i+1];
             let result: u16 = ( (big_endian as u16) << 8) | little_endian as u16;
@@ -203,8 +225,70 @@ impl Page{
 
 
 
+    //number of entries in the record index.
+    pub fn get_record_index_count(&self) -> u16{
+        return (self.size() as u16 - 2 - self.record_index_end_point) / 2
+    }
+
+
+
+    //entries are numbered in the order they were added, entry 0 sits at the end of the page.
+    pub fn get_record_index_entry(&self, entry_num: u16) -> Option<u16>{
+        if entry_num >= self.get_record_index_count(){
+            return None
+        }
+
+        let position = self.size() - 4 - 2 * entry_num as usize;
+        return Some(u16::from_be_bytes([self.bytes[position], self.bytes[position + 1]]))
+    }
+
+
+
+    pub fn set_record_index_entry(&mut self, entry_num: u16, value: u16){
+        if entry_num >= self.get_record_index_count(){
+            return
+        }
+
+        let position = self.size() - 4 - 2 * entry_num as usize;
+        self.bytes[position .. position + 2].copy_from_slice(&value.to_be_bytes());
+    }
+
+
+
+    //first entry left empty by remove_record_index_entry_data, if any.
+    pub fn find_empty_record_index_entry(&self) -> Option<u16>{
+        for entry_num in 0..self.get_record_index_count(){
+            if self.get_record_index_entry(entry_num) == Some(0){
+                return Some(entry_num)
+            }
+        }
+        return None
+    }
+
+
+
+    //removes the bytes an entry points to and shifts the data after it down. The entry itself is
+    //set to 0 instead of being removed so the entries after it keep their numbers.
+    pub fn remove_record_index_entry_data(&mut self, entry_num: u16, size: u16){
+        let start = match self.get_record_index_entry(entry_num){
+            None | Some(0)  => return,
+            Some(n)         => n,
+        };
+
+        self.remove_data_range(start, start + size);
+
+        for other in 0..self.get_record_index_count(){
+            if let Some(value) = self.get_record_index_entry(other) && value > start{
+                self.set_record_index_entry(other, value - size);
+            }
+        }
+
+        self.set_record_index_entry(entry_num, 0);
+    }
+
+
+
     pub fn add_record_index(&mut self, entry: u16){
-        println!("{:?}", self);
         self.record_index_end_point -= 2;
         let bytes: [u8; 2] = [(entry >> 8) as u8, entry as u8];
 
@@ -230,7 +314,7 @@ impl Page{
             }
 
             loop{
-                let middle_index = ((left_pointer + right_pointer) / 2) as usize;
+                let middle_index = (left_pointer + right_pointer) / 2;
                 if value > record_indexes[middle_index]{
                     right_pointer = middle_index;
 
@@ -308,7 +392,7 @@ impl Page{
 
     pub fn update_records_after(&mut self, record_value: u16, incr: u16, positive: bool){
         let location = match self.find_record_index(record_value){
-            None    => return (), // UPDATE
+            None    => return, // UPDATE
             Some(n) => n,
         };
         self.update_record_index_range(0, location , incr, positive);
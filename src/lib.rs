@@ -0,0 +1,13 @@
+//The repo names types Capitalized_snake_case and writes out every return, so those lints are
+//off for the whole crate.
+#![allow(non_camel_case_types, non_snake_case)]
+#![allow(clippy::needless_return, clippy::redundant_field_names)]
+
+pub mod file_manager;
+pub mod buffer_pool;
+pub mod table;
+pub mod database_engine;
+pub mod sql;
+
+#[cfg(test)]
+mod test_utils;
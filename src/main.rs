@@ -1,53 +1,111 @@
+#![allow(clippy::needless_return)]
+
+
 // CLI entry point
 //
 //TODO LIST:
 //
-//-implement saving the u8 type and u64 size for data via variable_data_manager.
-//
-//
-//-implement a  ID to Data reference table so that I can link records to the data reference so that
-// if this changes it only needs to be updated at the table.
 
 ///BUG LIST:
 /// NONE FOR NOW ...
 ///
-mod file_manager;
-use crate::file_manager::file_manager::build_file_manager;
+use databaseProject::table::table::Value;
+use databaseProject::database_engine::database::Database;
+use databaseProject::sql::executor::Query_result;
+use databaseProject::sql::executor::run;
 
-mod buffer_pool;
-use crate::buffer_pool::page_table::Page_table;
-
-mod table;
-use crate::table::table::Table;
-use crate::table::table::open_table;
-use crate::table::variable_data_manager::Variable_data_manager;
-use std::time::Instant;
+use std::io::BufRead;
+use std::io::Write;
 
 
 fn main() {
-    let now = Instant::now();
-    let mut file_manager = build_file_manager(16384, "./files".to_string());
-    let mut file_list: Vec<String> = Vec::new();
-    file_list.push("Test_Table".to_string());
-    let mut page_table = Page_table::new(163840, 16384, file_list, &mut file_manager);
-
-    let table = open_table("Test_Table".to_string(), &mut file_manager, &mut page_table).unwrap();
-
-    table.add_column();
-    let mut variable_data_manager = Variable_data_manager::new("Test_Table".to_string(), table.first_data_page_num, &table.data_free_space_tracker_page_num, &mut page_table, &mut file_manager);
-    let d = "TestingTESTING123456789! ====MMMakndnwnoinfiowneio nri33nir12u848962389591y9248013hnp5rini2n3mrefs;';f#'eelfminwiorhhwrmm".as_bytes();
-    for i in 0..1000000{
-        variable_data_manager.add_data(&d, &mut page_table, &mut file_manager);
-        //page_table.write_all(&mut file_manager);
-        
+    //the database in the directory given is queried with SQL read from standard input.
+    match std::env::args().nth(1){
+        None            => eprintln!("Usage: databaseProject <database directory>"),
+        Some(directory) => sql_shell(&directory),
     }
+}
+
+
 
-    //TODO TEST ADDING DATA TO DATA PAGES.
+//reads statements from standard input, running them whenever a line ends with a semicolon, and
+//prints their results. The database is closed at the end of the input.
+fn sql_shell(directory: &str){
+    let mut database = match Database::open(directory){
+        Err(error)  => {
+            eprintln!("Could not open the database: {:?}", error);
+            return
+        },
+        Ok(d)       => d,
+    };
 
-    page_table.write_all(&mut file_manager);
-    //println!("................................................................................................................");
-    //println!("page_table: {:?}", page_table);
+    let mut sql = String::new();
+    prompt(&sql);
 
-    let duration = now.elapsed();
-    println!("Completed!, Time elapsed: {:?}", duration);
+    for line in std::io::stdin().lock().lines(){
+        let line = match line{
+            Err(_)  => break,
+            Ok(l)   => l,
+        };
+
+        sql.push_str(&line);
+        sql.push('\n');
+
+        if line.trim_end().ends_with(';'){
+            match run(&mut database, &sql){
+                Err(error)      => println!("Error: {:?}", error),
+                Ok(results)     => results.iter().for_each(print_result),
+            }
+            sql.clear();
+        }
+
+        prompt(&sql);
+    }
+
+    if let Err(error) = database.close(){
+        eprintln!("Could not close the database: {:?}", error);
+    }
+}
+
+
+
+fn prompt(sql: &str){
+    print!("{}", if sql.is_empty() { "sql> " } else { "...> " });
+    std::io::stdout().flush().ok();
+}
+
+
+
+fn print_result(result: &Query_result){
+    match result{
+        Query_result::Rows{column_names, rows}  => {
+            println!("{}", column_names.join(" | "));
+            for row in rows{
+                println!("{}", row.iter().map(format_value).collect::<Vec<String>>().join(" | "));
+            }
+            println!("({} rows)", rows.len());
+        },
+
+        Query_result::Records_changed(count)    => println!("{} records changed", count),
+        Query_result::Done                      => println!("Done"),
+    }
+}
+
+
+
+fn format_value(value: &Value) -> String{
+    return match value{
+        Value::Int(n)           => n.to_string(),
+        Value::Float(f)         => f.to_string(),
+        Value::String(s)
+        | Value::Enum(s)        => s.clone(),
+        Value::Datetime(n)      => n.to_string(),
+        Value::Date(n)
+        | Value::Time(n)
+        | Value::U32(n)         => n.to_string(),
+        Value::U16(n)           => n.to_string(),
+        Value::Bool(b)          => b.to_string(),
+        Value::Blob(bytes)      => bytes.iter().map(|byte| format!("{:02x}", byte)).collect(),
+        Value::Null             => "NULL".to_string(),
+    }
 }
@@ -0,0 +1,380 @@
+//Runs parsed statements against a Database. Tables are changed through the Database, so foreign
+//keys are checked like for any other change, and selections go through Table::select, which uses
+//the primary key or an index where the condition allows it. Ordering, LIMIT and OFFSET are done on
+//the selected records in memory.
+//
+//Values are given in the type the parser reads them as and are converted to the types of their
+//columns, see Value::convert, so 1 can be stored in a FLOAT or U16 column and '1' in an INT one.
+//A statement is not undone when it fails part way, the records it already inserted, updated or
+//deleted stay that way.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::table::table::Column;
+use crate::table::table::Table;
+use crate::table::table::Table_error;
+use crate::table::table::Value;
+use crate::table::predicate::Predicate;
+use crate::database_engine::database::Database;
+use crate::database_engine::database::Database_error;
+use crate::sql::parser::Sql_error;
+use crate::sql::parser::Statement;
+use crate::sql::parser::parse;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query_result{
+    //the selected columns and their values in every selected record.
+    Rows{column_names: Vec<String>, rows: Vec<Vec<Value>>},
+    //how many records were inserted, updated or deleted. Records removed by ON DELETE CASCADE are
+    //not counted.
+    Records_changed(usize),
+    //a table or column was created, dropped or renamed.
+    Done,
+}
+
+
+
+//parses and runs statements separated by semicolons, stopping at the first that fails.
+pub fn run(database: &mut Database, sql: &str) -> Result<Vec<Query_result>, Sql_error>{
+    let mut results = Vec::new();
+
+    for statement in parse(sql)?{
+        results.push(execute(database, statement)?);
+    }
+
+    return Ok(results)
+}
+
+
+
+pub fn execute(database: &mut Database, statement: Statement) -> Result<Query_result, Sql_error>{
+    return match statement{
+        Statement::Create_table{table_name, columns}              => {
+            database.create_table(&table_name, columns)?;
+            Ok(Query_result::Done)
+        },
+
+        Statement::Drop_table{table_name}                         => {
+            database.drop_table(&table_name)?;
+            Ok(Query_result::Done)
+        },
+
+        Statement::Add_column{table_name, column}                 => {
+            database.add_column(&table_name, column)?;
+            Ok(Query_result::Done)
+        },
+
+        Statement::Drop_column{table_name, column_name}           => {
+            database.remove_column(&table_name, &column_name)?;
+            Ok(Query_result::Done)
+        },
+
+        Statement::Rename_column{table_name, old_name, new_name}  => {
+            database.modify_column_name(&table_name, &old_name, &new_name)?;
+            Ok(Query_result::Done)
+        },
+
+        Statement::Insert{table_name, column_names, rows}         => insert(database, &table_name, column_names, rows),
+
+        Statement::Select{table_name, column_names, predicate, order_by, limit, offset} => {
+            select(database, &table_name, column_names, predicate, order_by, limit, offset)
+        },
+
+        Statement::Update{table_name, changes, predicate}         => update(database, &table_name, changes, predicate),
+        Statement::Delete{table_name, predicate}                  => delete(database, &table_name, predicate),
+    }
+}
+
+
+
+fn table<'a>(tables: &'a HashMap<String, Table>, table_name: &str) -> Result<&'a Table, Sql_error>{
+    return match tables.get(table_name){
+        None    => Err(Database_error::Table_not_found(table_name.to_string()).into()),
+        Some(t) => Ok(t),
+    }
+}
+
+
+
+//the position of a column in a schema.
+fn column_position(columns: &[Column], column_name: &str) -> Result<usize, Sql_error>{
+    return match columns.iter().position(|column| column.column_name == column_name){
+        None    => Err(Table_error::Column_not_found(column_name.to_string()).into()),
+        Some(i) => Ok(i),
+    }
+}
+
+
+
+//a value as the type of a column.
+fn convert(column: &Column, value: Value) -> Result<Value, Sql_error>{
+    return match value.convert(&column.data_type){
+        None    => Err(Table_error::Type_mismatch{column_name: column.column_name.clone()}.into()),
+        Some(v) => Ok(v),
+    }
+}
+
+
+
+//changes by column name with their values converted to the columns' types.
+fn convert_named(columns: &[Column], values: Vec<(String, Value)>) -> Result<Vec<(String, Value)>, Sql_error>{
+    let mut converted = Vec::with_capacity(values.len());
+
+    for (column_name, value) in values{
+        let value = convert(&columns[column_position(columns, &column_name)?], value)?;
+        converted.push((column_name, value));
+    }
+
+    return Ok(converted)
+}
+
+
+
+//adds every row as a record. Without column names a row has a value for every column, otherwise
+//the columns it leaves out take their defaults, see Table::named_record.
+fn insert(database: &mut Database, table_name: &str, column_names: Vec<String>, rows: Vec<Vec<Value>>) -> Result<Query_result, Sql_error>{
+    let columns = table(&database.tables, table_name)?.column_schema.clone();
+    let mut inserted = 0;
+
+    for row in rows{
+        let expected = if column_names.is_empty() { columns.len() } else { column_names.len() };
+
+        if row.len() != expected{
+            return Err(Table_error::Column_count_mismatch{expected, found: row.len()}.into())
+        }
+
+        if column_names.is_empty(){
+            let record = columns.iter().zip(row).map(|(column, value)| convert(column, value)).collect::<Result<Vec<Value>, Sql_error>>()?;
+            database.add_record(table_name, record)?;
+        }else{
+            let values = convert_named(&columns, column_names.iter().cloned().zip(row).collect())?;
+            database.add_named_record(table_name, values)?;
+        }
+
+        inserted += 1;
+    }
+
+    return Ok(Query_result::Records_changed(inserted))
+}
+
+
+
+//the selected records ordered by the columns in order_by, the first one deciding first. Values are
+//ordered like in an index, see Value::encode_key, so nulls come first unless the order is
+//descending.
+fn select(database: &mut Database, table_name: &str, column_names: Vec<String>, predicate: Option<Predicate>, order_by: Vec<(String, bool)>, limit: Option<usize>, offset: usize) -> Result<Query_result, Sql_error>{
+    let table = table(&database.tables, table_name)?;
+    let columns = &table.column_schema;
+
+    let column_names = if column_names.is_empty(){
+        columns.iter().map(|column| column.column_name.clone()).collect()
+    }else{
+        column_names
+    };
+
+    let projection = column_names.iter().map(|name| column_position(columns, name)).collect::<Result<Vec<usize>, Sql_error>>()?;
+
+    let mut order = Vec::with_capacity(order_by.len());
+    for (name, descending) in order_by.iter(){
+        order.push((column_position(columns, name)?, *descending));
+    }
+
+    let mut records: Vec<Vec<Value>> = table.select(&[], predicate.as_ref(), &mut database.page_table, &mut database.file_manager)?
+        .into_iter()
+        .map(|(_, record)| record)
+        .collect();
+
+    records.sort_by(|a, b| {
+        for (i, descending) in order.iter(){
+            let ordering = a[*i].encode_key().cmp(&b[*i].encode_key());
+            let ordering = if *descending { ordering.reverse() } else { ordering };
+
+            if ordering != Ordering::Equal{
+                return ordering
+            }
+        }
+
+        return Ordering::Equal
+    });
+
+    let rows = records.into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|record| projection.iter().map(|i| record[*i].clone()).collect())
+        .collect();
+
+    return Ok(Query_result::Rows{column_names, rows})
+}
+
+
+
+fn update(database: &mut Database, table_name: &str, changes: Vec<(String, Value)>, predicate: Option<Predicate>) -> Result<Query_result, Sql_error>{
+    let table = table(&database.tables, table_name)?;
+    let changes = convert_named(&table.column_schema, changes)?;
+
+    let selected = table.select(&[], predicate.as_ref(), &mut database.page_table, &mut database.file_manager)?;
+
+    for (record_id, _) in selected.iter(){
+        database.modify_record(table_name, *record_id, changes.clone())?;
+    }
+
+    return Ok(Query_result::Records_changed(selected.len()))
+}
+
+
+
+fn delete(database: &mut Database, table_name: &str, predicate: Option<Predicate>) -> Result<Query_result, Sql_error>{
+    let selected = table(&database.tables, table_name)?.select(&[], predicate.as_ref(), &mut database.page_table, &mut database.file_manager)?;
+    let mut deleted = 0;
+
+    for (record_id, _) in selected{
+        //a record can already be gone through the ON DELETE CASCADE of one removed before it.
+        match database.tables[table_name].get_record(record_id, &mut database.page_table, &mut database.file_manager){
+            Err(Table_error::Record_not_found(_))   => continue,
+            Err(error)                              => return Err(error.into()),
+            Ok(_)                                   => (),
+        }
+
+        database.remove_record(table_name, record_id)?;
+        deleted += 1;
+    }
+
+    return Ok(Query_result::Records_changed(deleted))
+}
+
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::test_utils::temp_directory;
+
+
+    fn open(name: &str) -> Database{
+        let mut database = Database::open(&temp_directory(name)).unwrap();
+        run(&mut database, "CREATE TABLE person (id INT PRIMARY KEY, name TEXT, age INT)").unwrap();
+        return database
+    }
+
+    //the rows of a SELECT.
+    fn rows(database: &mut Database, sql: &str) -> Vec<Vec<Value>>{
+        return match run(database, sql).unwrap().pop(){
+            Some(Query_result::Rows{rows, ..})  => rows,
+            other                               => panic!("{:?}", other),
+        }
+    }
+
+    fn person(id: i64, name: &str, age: Option<i64>) -> Vec<Value>{
+        return vec![Value::Int(id), Value::String(name.to_string()), age.map_or(Value::Null, Value::Int)]
+    }
+
+    //the ids of the selected rows.
+    fn ids(database: &mut Database, sql: &str) -> Vec<i64>{
+        return rows(database, sql).iter().map(|row| match row[0]{
+            Value::Int(id)  => id,
+            _               => panic!("{:?}", row),
+        }).collect()
+    }
+
+
+
+    #[test]
+    fn insert_with_and_without_column_names(){
+        let mut database = open("sql_insert");
+
+        let results = run(&mut database, "INSERT INTO person VALUES (1, 'a', 30), (2, 'b', NULL); INSERT INTO person (name, id) VALUES ('c', 3)").unwrap();
+        assert_eq!(results, vec![Query_result::Records_changed(2), Query_result::Records_changed(1)]);
+
+        //values are converted to the types of their columns.
+        run(&mut database, "INSERT INTO person (age, name, id) VALUES ('40', 'd', 4.0)").unwrap();
+
+        assert_eq!(rows(&mut database, "SELECT * FROM person ORDER BY id"), vec![
+            person(1, "a", Some(30)),
+            person(2, "b", None),
+            person(3, "c", None),
+            person(4, "d", Some(40)),
+        ]);
+
+        assert_eq!(run(&mut database, "SELECT name, id FROM person WHERE id = 3").unwrap(), vec![Query_result::Rows{
+            column_names: vec!["name".to_string(), "id".to_string()],
+            rows: vec![vec![Value::String("c".to_string()), Value::Int(3)]],
+        }]);
+
+        for sql in ["INSERT INTO person VALUES (5, 'e')", "INSERT INTO person (id, name) VALUES (5)"]{
+            assert!(matches!(run(&mut database, sql), Err(Sql_error::Database(Database_error::Table(Table_error::Column_count_mismatch{..})))), "{}", sql);
+        }
+
+        assert!(matches!(run(&mut database, "INSERT INTO person (id, height) VALUES (5, 1)"), Err(Sql_error::Database(Database_error::Table(Table_error::Column_not_found(_))))));
+        assert!(matches!(run(&mut database, "INSERT INTO person VALUES (5, 'e', 'old')"), Err(Sql_error::Database(Database_error::Table(Table_error::Type_mismatch{..})))));
+        assert!(matches!(run(&mut database, "INSERT INTO nobody VALUES (1)"), Err(Sql_error::Database(Database_error::Table_not_found(_)))));
+        assert_eq!(ids(&mut database, "SELECT * FROM person"), vec![1, 2, 3, 4]);
+    }
+
+
+
+    #[test]
+    fn update_and_delete_with_where(){
+        let mut database = open("sql_update_delete");
+        run(&mut database, "INSERT INTO person VALUES (1, 'a', 30), (2, 'b', NULL), (3, 'c', 50), (4, 'd', 20)").unwrap();
+
+        assert!(matches!(run(&mut database, "UPDATE person SET height = 2 WHERE id = 1"), Err(Sql_error::Database(Database_error::Table(Table_error::Column_not_found(_))))));
+
+        assert_eq!(run(&mut database, "UPDATE person SET age = 21 WHERE id >= 2 AND age < 40").unwrap(), vec![Query_result::Records_changed(1)]);
+        assert_eq!(run(&mut database, "UPDATE person SET name = 'x' WHERE age <= 30 OR age IS NULL").unwrap(), vec![Query_result::Records_changed(3)]);
+
+        assert_eq!(rows(&mut database, "SELECT * FROM person ORDER BY id"), vec![
+            person(1, "x", Some(30)),
+            person(2, "x", None),
+            person(3, "c", Some(50)),
+            person(4, "x", Some(21)),
+        ]);
+
+        //a comparison with null is unknown, so NOT leaves out the record with no age.
+        assert_eq!(run(&mut database, "DELETE FROM person WHERE NOT age BETWEEN 25 AND 45").unwrap(), vec![Query_result::Records_changed(2)]);
+        assert_eq!(ids(&mut database, "SELECT * FROM person ORDER BY id"), vec![1, 2]);
+
+        assert_eq!(run(&mut database, "DELETE FROM person").unwrap(), vec![Query_result::Records_changed(2)]);
+        assert!(rows(&mut database, "SELECT * FROM person").is_empty());
+    }
+
+
+
+    #[test]
+    fn order_by_limit_and_offset(){
+        let mut database = open("sql_order");
+        run(&mut database, "INSERT INTO person VALUES (1, 'a', 30), (2, 'b', NULL), (3, 'c', 20), (4, 'b', 30), (5, 'e', NULL)").unwrap();
+
+        //nulls come first, unless the order is descending.
+        assert_eq!(ids(&mut database, "SELECT * FROM person ORDER BY age, id"), vec![2, 5, 3, 1, 4]);
+        assert_eq!(ids(&mut database, "SELECT * FROM person ORDER BY age DESC, id"), vec![1, 4, 3, 2, 5]);
+        assert_eq!(ids(&mut database, "SELECT * FROM person ORDER BY age DESC, id DESC"), vec![4, 1, 3, 5, 2]);
+        assert_eq!(ids(&mut database, "SELECT id FROM person ORDER BY name DESC, age ASC"), vec![5, 3, 2, 4, 1]);
+
+        assert_eq!(ids(&mut database, "SELECT * FROM person ORDER BY id LIMIT 2"), vec![1, 2]);
+        assert_eq!(ids(&mut database, "SELECT * FROM person ORDER BY id LIMIT 2 OFFSET 2"), vec![3, 4]);
+        assert_eq!(ids(&mut database, "SELECT * FROM person ORDER BY id LIMIT 10 OFFSET 3"), vec![4, 5]);
+        assert_eq!(ids(&mut database, "SELECT * FROM person ORDER BY id LIMIT 2 OFFSET 5"), Vec::<i64>::new());
+        assert_eq!(ids(&mut database, "SELECT * FROM person ORDER BY id LIMIT 0"), Vec::<i64>::new());
+        assert_eq!(ids(&mut database, "SELECT * FROM person WHERE age IS NOT NULL ORDER BY age DESC LIMIT 1 OFFSET 2"), vec![3]);
+
+        assert!(matches!(run(&mut database, "SELECT * FROM person ORDER BY height"), Err(Sql_error::Database(Database_error::Table(Table_error::Column_not_found(_))))));
+    }
+
+
+
+    #[test]
+    fn delete_skips_records_removed_by_a_cascade(){
+        let mut database = Database::open(&temp_directory("sql_delete_cascade")).unwrap();
+        run(&mut database, "CREATE TABLE node (id INT PRIMARY KEY, parent INT REFERENCES node ON DELETE CASCADE)").unwrap();
+        run(&mut database, "INSERT INTO node VALUES (1, NULL), (2, 1), (3, 2), (4, NULL), (5, 4)").unwrap();
+
+        //1 is removed first, and its cascade removes 2 and 3 before the statement gets to them.
+        assert_eq!(run(&mut database, "DELETE FROM node WHERE id <= 3").unwrap(), vec![Query_result::Records_changed(1)]);
+        assert_eq!(ids(&mut database, "SELECT * FROM node ORDER BY id"), vec![4, 5]);
+
+        assert_eq!(run(&mut database, "DELETE FROM node").unwrap(), vec![Query_result::Records_changed(1)]);
+        assert!(rows(&mut database, "SELECT * FROM node").is_empty());
+    }
+}
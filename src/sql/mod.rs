@@ -0,0 +1,3 @@
+pub mod tokenizer;
+pub mod parser;
+pub mod executor;
@@ -0,0 +1,824 @@
+//Hand written recursive descent parser for the subset of SQL the engine understands:
+//
+// CREATE TABLE name (column type [constraint ...], ... [, PRIMARY KEY (column, ...)])
+// DROP TABLE name
+// ALTER TABLE name ADD [COLUMN] column type [constraint ...]
+// ALTER TABLE name DROP [COLUMN] column
+// ALTER TABLE name RENAME [COLUMN] column TO new_name
+// INSERT INTO name [(column, ...)] VALUES (value, ...), ...
+// SELECT * | column, ... FROM name [WHERE condition] [ORDER BY column [ASC | DESC], ...] [LIMIT n [OFFSET m]]
+// UPDATE name SET column = value, ... [WHERE condition]
+// DELETE FROM name [WHERE condition]
+//
+//The constraints are NOT NULL, NULL, UNIQUE, PRIMARY KEY, DEFAULT value and REFERENCES table
+//[ON DELETE RESTRICT | CASCADE | SET NULL]. Values are numbers, 'strings', TRUE, FALSE and NULL.
+//A condition compares a column with values using =, <>, !=, <, <=, >, >=, [NOT] BETWEEN,
+//[NOT] IN, IS [NOT] NULL and [NOT] LIKE, and joins comparisons with AND, OR, NOT and brackets.
+//Conditions become Predicates, see Table::select.
+
+use crate::table::table::Column;
+use crate::table::table::Data_type;
+use crate::table::table::Foreign_key;
+use crate::table::table::On_delete;
+use crate::table::table::Table_error;
+use crate::table::table::Value;
+use crate::table::predicate::Predicate;
+use crate::database_engine::database::Database_error;
+use crate::sql::tokenizer::Token;
+use crate::sql::tokenizer::tokenize;
+
+
+#[derive(Debug)]
+pub enum Sql_error{
+    Unexpected_character(char),
+    Unterminated_string,
+    Unexpected_token{expected: String, found: String},
+    Unexpected_end{expected: String},
+    Unknown_type(String),
+    Invalid_number(String),
+    Database(Database_error),
+}
+
+impl From<Database_error> for Sql_error{
+    fn from(error: Database_error) -> Self{
+        Sql_error::Database(error)
+    }
+}
+
+impl From<Table_error> for Sql_error{
+    fn from(error: Table_error) -> Self{
+        Sql_error::Database(Database_error::Table(error))
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub enum Statement{
+    Create_table{table_name: String, columns: Vec<Column>},
+    Drop_table{table_name: String},
+    Add_column{table_name: String, column: Column},
+    Drop_column{table_name: String, column_name: String},
+    Rename_column{table_name: String, old_name: String, new_name: String},
+    //no column names means a value for every column in schema order.
+    Insert{table_name: String, column_names: Vec<String>, rows: Vec<Vec<Value>>},
+    //no column names selects every column. The order is a column name and whether it is
+    //descending.
+    Select{table_name: String, column_names: Vec<String>, predicate: Option<Predicate>, order_by: Vec<(String, bool)>, limit: Option<usize>, offset: usize},
+    Update{table_name: String, changes: Vec<(String, Value)>, predicate: Option<Predicate>},
+    Delete{table_name: String, predicate: Option<Predicate>},
+}
+
+
+//words that cannot be a name unless they are in double quotes.
+const KEYWORDS: [&str; 41] = [
+    "ADD", "ALTER", "AND", "ASC", "BETWEEN", "BY", "CASCADE", "COLUMN", "CREATE", "DEFAULT", "DELETE",
+    "DESC", "DROP", "FALSE", "FROM", "IN", "INSERT", "INTO", "IS", "KEY", "LIKE", "LIMIT", "NOT",
+    "NULL", "OFFSET", "ON", "OR", "ORDER", "PRIMARY", "REFERENCES", "RENAME", "RESTRICT", "SELECT",
+    "SET", "TABLE", "TO", "TRUE", "UNIQUE", "UPDATE", "VALUES", "WHERE",
+];
+
+
+
+//the type a type name stands for, as in CREATE TABLE. Sizes such as VARCHAR(255) are read and
+//ignored by the parser, as every string can be of any length.
+pub fn data_type(name: &str) -> Option<Data_type>{
+    return match name.to_ascii_uppercase().as_str(){
+        "INT" | "INTEGER" | "BIGINT"            => Some(Data_type::Int),
+        "FLOAT" | "REAL" | "DOUBLE"             => Some(Data_type::Float),
+        "TEXT" | "STRING" | "VARCHAR" | "CHAR"  => Some(Data_type::String),
+        "DATETIME" | "TIMESTAMP"                => Some(Data_type::Datetime),
+        "DATE"                                  => Some(Data_type::Date),
+        "TIME"                                  => Some(Data_type::Time),
+        "BOOL" | "BOOLEAN"                      => Some(Data_type::Bool),
+        "ENUM"                                  => Some(Data_type::Enum),
+        "BLOB"                                  => Some(Data_type::Blob),
+        "U32"                                   => Some(Data_type::U32),
+        "U16"                                   => Some(Data_type::U16),
+        _                                       => None,
+    }
+}
+
+
+
+//parses statements separated by semicolons.
+pub fn parse(sql: &str) -> Result<Vec<Statement>, Sql_error>{
+    let mut parser = Parser{tokens: tokenize(sql)?, position: 0};
+    let mut statements = Vec::new();
+
+    loop{
+        while parser.accept_symbol(";"){}
+
+        if parser.peek().is_none(){
+            return Ok(statements)
+        }
+
+        statements.push(parser.statement()?);
+
+        if parser.peek().is_some(){
+            parser.expect_symbol(";")?;
+        }
+    }
+}
+
+
+
+struct Parser{
+    tokens:     Vec<Token>,
+    position:   usize,
+}
+
+
+
+impl Parser{
+
+    fn peek(&self) -> Option<&Token>{
+        return self.tokens.get(self.position)
+    }
+
+
+
+    //an error for the token at the current position, which is not what was expected.
+    fn unexpected(&self, expected: &str) -> Sql_error{
+        return match self.peek(){
+            None        => Sql_error::Unexpected_end{expected: expected.to_string()},
+            Some(token) => Sql_error::Unexpected_token{expected: expected.to_string(), found: token.describe()},
+        }
+    }
+
+
+
+    fn is_keyword(&self, keyword: &str) -> bool{
+        return matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+
+
+    fn accept_keyword(&mut self, keyword: &str) -> bool{
+        if self.is_keyword(keyword){
+            self.position += 1;
+            return true
+        }
+
+        return false
+    }
+
+
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<u8, Sql_error>{
+        if !self.accept_keyword(keyword){
+            return Err(self.unexpected(keyword))
+        }
+
+        return Ok(1)
+    }
+
+
+
+    fn accept_symbol(&mut self, symbol: &str) -> bool{
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol){
+            self.position += 1;
+            return true
+        }
+
+        return false
+    }
+
+
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<u8, Sql_error>{
+        if !self.accept_symbol(symbol){
+            return Err(self.unexpected(symbol))
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //the name of a table or column.
+    fn name(&mut self) -> Result<String, Sql_error>{
+        let name = match self.peek(){
+            Some(Token::Quoted_identifier(name))                                                => name.clone(),
+            Some(Token::Word(word)) if !KEYWORDS.iter().any(|k| word.eq_ignore_ascii_case(k))   => word.clone(),
+            _                                                                                   => return Err(self.unexpected("a name")),
+        };
+
+        self.position += 1;
+
+        return Ok(name)
+    }
+
+
+
+    //names separated by commas.
+    fn names(&mut self) -> Result<Vec<String>, Sql_error>{
+        let mut names = vec![self.name()?];
+
+        while self.accept_symbol(","){
+            names.push(self.name()?);
+        }
+
+        return Ok(names)
+    }
+
+
+
+    //a whole number that is not negative, as in LIMIT.
+    fn count(&mut self) -> Result<usize, Sql_error>{
+        let count = match self.peek(){
+            Some(Token::Number(number)) => match number.parse(){
+                Err(_)  => return Err(Sql_error::Invalid_number(number.clone())),
+                Ok(n)   => n,
+            },
+            _                           => return Err(self.unexpected("a number")),
+        };
+
+        self.position += 1;
+
+        return Ok(count)
+    }
+
+
+
+    fn value(&mut self) -> Result<Value, Sql_error>{
+        let negative = self.accept_symbol("-");
+
+        let value = match self.peek(){
+            Some(Token::Number(number))                                     => {
+                let text = if negative { format!("-{}", number) } else { number.clone() };
+
+                //a number with a point or an exponent is a float, any other one an integer.
+                let value = if text.contains(['.', 'e', 'E']){
+                    text.parse().ok().map(Value::Float)
+                }else{
+                    text.parse().ok().map(Value::Int)
+                };
+
+                match value{
+                    None    => return Err(Sql_error::Invalid_number(text)),
+                    Some(v) => v,
+                }
+            },
+
+            _ if negative                                                   => return Err(self.unexpected("a number")),
+            Some(Token::String(text))                                       => Value::String(text.clone()),
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("TRUE")    => Value::Bool(true),
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("FALSE")   => Value::Bool(false),
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("NULL")    => Value::Null,
+            _                                                               => return Err(self.unexpected("a value")),
+        };
+
+        self.position += 1;
+
+        return Ok(value)
+    }
+
+
+
+    //values separated by commas in brackets.
+    fn value_list(&mut self) -> Result<Vec<Value>, Sql_error>{
+        self.expect_symbol("(")?;
+
+        let mut values = vec![self.value()?];
+
+        while self.accept_symbol(","){
+            values.push(self.value()?);
+        }
+
+        self.expect_symbol(")")?;
+
+        return Ok(values)
+    }
+
+
+
+    fn statement(&mut self) -> Result<Statement, Sql_error>{
+        if self.accept_keyword("CREATE"){
+            return self.create_table()
+        }
+
+        if self.accept_keyword("DROP"){
+            self.expect_keyword("TABLE")?;
+            return Ok(Statement::Drop_table{table_name: self.name()?})
+        }
+
+        if self.accept_keyword("ALTER"){
+            return self.alter_table()
+        }
+
+        if self.accept_keyword("INSERT"){
+            return self.insert()
+        }
+
+        if self.accept_keyword("SELECT"){
+            return self.select()
+        }
+
+        if self.accept_keyword("UPDATE"){
+            return self.update()
+        }
+
+        if self.accept_keyword("DELETE"){
+            self.expect_keyword("FROM")?;
+            let table_name = self.name()?;
+            return Ok(Statement::Delete{table_name, predicate: self.where_clause()?})
+        }
+
+        return Err(self.unexpected("a statement"))
+    }
+
+
+
+    //the columns of a new table. A PRIMARY KEY (column, ...) entry makes several columns the
+    //primary key, which then spans them in the order of the table, see Table::add_column_definition.
+    fn create_table(&mut self) -> Result<Statement, Sql_error>{
+        self.expect_keyword("TABLE")?;
+        let table_name = self.name()?;
+
+        self.expect_symbol("(")?;
+
+        let mut columns: Vec<Column> = Vec::new();
+        let mut key_names = Vec::new();
+
+        loop{
+            if self.accept_keyword("PRIMARY"){
+                self.expect_keyword("KEY")?;
+                self.expect_symbol("(")?;
+                key_names.extend(self.names()?);
+                self.expect_symbol(")")?;
+            }else{
+                columns.push(self.column_definition()?);
+            }
+
+            if !self.accept_symbol(","){
+                break
+            }
+        }
+
+        self.expect_symbol(")")?;
+
+        for name in key_names{
+            match columns.iter_mut().find(|column| column.column_name == name){
+                None            => return Err(Table_error::Column_not_found(name).into()),
+                Some(column)    => column.primary_key = true,
+            }
+        }
+
+        return Ok(Statement::Create_table{table_name, columns})
+    }
+
+
+
+    fn alter_table(&mut self) -> Result<Statement, Sql_error>{
+        self.expect_keyword("TABLE")?;
+        let table_name = self.name()?;
+
+        if self.accept_keyword("ADD"){
+            self.accept_keyword("COLUMN");
+            return Ok(Statement::Add_column{table_name, column: self.column_definition()?})
+        }
+
+        if self.accept_keyword("DROP"){
+            self.accept_keyword("COLUMN");
+            return Ok(Statement::Drop_column{table_name, column_name: self.name()?})
+        }
+
+        if self.accept_keyword("RENAME"){
+            self.accept_keyword("COLUMN");
+            let old_name = self.name()?;
+            self.expect_keyword("TO")?;
+            return Ok(Statement::Rename_column{table_name, old_name, new_name: self.name()?})
+        }
+
+        return Err(self.unexpected("ADD, DROP or RENAME"))
+    }
+
+
+
+    //a column name, its type and its constraints in any order.
+    fn column_definition(&mut self) -> Result<Column, Sql_error>{
+        let column_name = self.name()?;
+
+        let type_name = match self.peek(){
+            Some(Token::Word(word)) => word.clone(),
+            _                       => return Err(self.unexpected("a type")),
+        };
+
+        let mut column = match data_type(&type_name){
+            None    => return Err(Sql_error::Unknown_type(type_name)),
+            Some(t) => Column::new(column_name, t),
+        };
+
+        self.position += 1;
+
+        if self.accept_symbol("("){
+            self.count()?;
+            self.expect_symbol(")")?;
+        }
+
+        loop{
+            if self.accept_keyword("NOT"){
+                self.expect_keyword("NULL")?;
+                column.not_null = true;
+            }else if self.accept_keyword("NULL"){
+                column.not_null = false;
+            }else if self.accept_keyword("UNIQUE"){
+                column.unique = true;
+            }else if self.accept_keyword("PRIMARY"){
+                self.expect_keyword("KEY")?;
+                column.primary_key = true;
+            }else if self.accept_keyword("DEFAULT"){
+                //the default is given as the column's type, so DEFAULT 1 works for a FLOAT.
+                column.default = match self.value()?.convert(&column.data_type){
+                    None    => return Err(Table_error::Invalid_default(column.column_name).into()),
+                    Some(v) => Some(v),
+                };
+            }else if self.accept_keyword("REFERENCES"){
+                column.references = Some(self.foreign_key()?);
+            }else{
+                return Ok(column)
+            }
+        }
+    }
+
+
+
+    fn foreign_key(&mut self) -> Result<Foreign_key, Sql_error>{
+        let table_name = self.name()?;
+        let mut on_delete = On_delete::Restrict;
+
+        if self.accept_keyword("ON"){
+            self.expect_keyword("DELETE")?;
+
+            on_delete = if self.accept_keyword("CASCADE"){
+                On_delete::Cascade
+            }else if self.accept_keyword("RESTRICT"){
+                On_delete::Restrict
+            }else if self.accept_keyword("SET"){
+                self.expect_keyword("NULL")?;
+                On_delete::Set_null
+            }else{
+                return Err(self.unexpected("CASCADE, RESTRICT or SET NULL"))
+            };
+        }
+
+        return Ok(Foreign_key{table_name, on_delete})
+    }
+
+
+
+    fn insert(&mut self) -> Result<Statement, Sql_error>{
+        self.expect_keyword("INTO")?;
+        let table_name = self.name()?;
+
+        let mut column_names = Vec::new();
+
+        if self.accept_symbol("("){
+            column_names = self.names()?;
+            self.expect_symbol(")")?;
+        }
+
+        self.expect_keyword("VALUES")?;
+
+        let mut rows = vec![self.value_list()?];
+
+        while self.accept_symbol(","){
+            rows.push(self.value_list()?);
+        }
+
+        return Ok(Statement::Insert{table_name, column_names, rows})
+    }
+
+
+
+    fn select(&mut self) -> Result<Statement, Sql_error>{
+        let column_names = if self.accept_symbol("*"){
+            Vec::new()
+        }else{
+            self.names()?
+        };
+
+        self.expect_keyword("FROM")?;
+        let table_name = self.name()?;
+
+        let predicate = self.where_clause()?;
+
+        let mut order_by = Vec::new();
+
+        if self.accept_keyword("ORDER"){
+            self.expect_keyword("BY")?;
+
+            loop{
+                let name = self.name()?;
+                let descending = self.accept_keyword("DESC");
+                if !descending{
+                    self.accept_keyword("ASC");
+                }
+                order_by.push((name, descending));
+
+                if !self.accept_symbol(","){
+                    break
+                }
+            }
+        }
+
+        let mut limit = None;
+        let mut offset = 0;
+
+        if self.accept_keyword("LIMIT"){
+            limit = Some(self.count()?);
+
+            if self.accept_keyword("OFFSET"){
+                offset = self.count()?;
+            }
+        }
+
+        return Ok(Statement::Select{table_name, column_names, predicate, order_by, limit, offset})
+    }
+
+
+
+    fn update(&mut self) -> Result<Statement, Sql_error>{
+        let table_name = self.name()?;
+        self.expect_keyword("SET")?;
+
+        let mut changes = Vec::new();
+
+        loop{
+            let name = self.name()?;
+            self.expect_symbol("=")?;
+            changes.push((name, self.value()?));
+
+            if !self.accept_symbol(","){
+                break
+            }
+        }
+
+        return Ok(Statement::Update{table_name, changes, predicate: self.where_clause()?})
+    }
+
+
+
+    fn where_clause(&mut self) -> Result<Option<Predicate>, Sql_error>{
+        if !self.accept_keyword("WHERE"){
+            return Ok(None)
+        }
+
+        return Ok(Some(self.or_condition()?))
+    }
+
+
+
+    //OR binds weaker than AND, which binds weaker than NOT.
+    fn or_condition(&mut self) -> Result<Predicate, Sql_error>{
+        let mut predicate = self.and_condition()?;
+
+        while self.accept_keyword("OR"){
+            predicate = predicate.or(self.and_condition()?);
+        }
+
+        return Ok(predicate)
+    }
+
+
+
+    fn and_condition(&mut self) -> Result<Predicate, Sql_error>{
+        let mut predicate = self.not_condition()?;
+
+        while self.accept_keyword("AND"){
+            predicate = predicate.and(self.not_condition()?);
+        }
+
+        return Ok(predicate)
+    }
+
+
+
+    fn not_condition(&mut self) -> Result<Predicate, Sql_error>{
+        if self.accept_keyword("NOT"){
+            return Ok(self.not_condition()?.negate())
+        }
+
+        if self.accept_symbol("("){
+            let predicate = self.or_condition()?;
+            self.expect_symbol(")")?;
+            return Ok(predicate)
+        }
+
+        return self.comparison()
+    }
+
+
+
+    //a column compared with values. <=, >= and <> are written with the comparisons Predicate has,
+    //so a null stays unknown for them as well.
+    fn comparison(&mut self) -> Result<Predicate, Sql_error>{
+        let name = self.name()?;
+
+        if self.accept_keyword("IS"){
+            let negated = self.accept_keyword("NOT");
+            self.expect_keyword("NULL")?;
+
+            let predicate = Predicate::Is_null(name);
+            return Ok(if negated { predicate.negate() } else { predicate })
+        }
+
+        if self.accept_symbol("="){
+            return Ok(Predicate::Equal(name, self.value()?))
+        }
+
+        if self.accept_symbol("<>") || self.accept_symbol("!="){
+            return Ok(Predicate::Equal(name, self.value()?).negate())
+        }
+
+        if self.accept_symbol("<"){
+            return Ok(Predicate::Less(name, self.value()?))
+        }
+
+        if self.accept_symbol(">"){
+            return Ok(Predicate::Greater(name, self.value()?))
+        }
+
+        if self.accept_symbol("<="){
+            let value = self.value()?;
+            return Ok(Predicate::Less(name.clone(), value.clone()).or(Predicate::Equal(name, value)))
+        }
+
+        if self.accept_symbol(">="){
+            let value = self.value()?;
+            return Ok(Predicate::Greater(name.clone(), value.clone()).or(Predicate::Equal(name, value)))
+        }
+
+        let negated = self.accept_keyword("NOT");
+
+        let predicate = if self.accept_keyword("BETWEEN"){
+            let low = self.value()?;
+            self.expect_keyword("AND")?;
+            Predicate::Between(name, low, self.value()?)
+        }else if self.accept_keyword("IN"){
+            Predicate::In(name, self.value_list()?)
+        }else if self.accept_keyword("LIKE"){
+            match self.value()?{
+                Value::String(pattern)  => Predicate::Like(name, pattern),
+                _                       => {
+                    self.position -= 1;
+                    return Err(self.unexpected("a pattern"))
+                },
+            }
+        }else{
+            return Err(self.unexpected("a comparison"))
+        };
+
+        return Ok(if negated { predicate.negate() } else { predicate })
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+
+    //the predicate of a WHERE clause on its own.
+    fn condition(text: &str) -> Predicate{
+        return match parse(&format!("SELECT * FROM t WHERE {}", text)).unwrap().pop(){
+            Some(Statement::Select{predicate: Some(predicate), ..}) => predicate,
+            other                                                   => panic!("{:?}", other),
+        }
+    }
+
+    fn equal(name: &str, n: i64) -> Predicate{
+        return Predicate::Equal(name.to_string(), Value::Int(n))
+    }
+
+    //the value of an UPDATE.
+    fn value(text: &str) -> Value{
+        return match parse(&format!("UPDATE t SET a = {}", text)).unwrap().pop(){
+            Some(Statement::Update{mut changes, ..})    => changes.pop().unwrap().1,
+            other                                       => panic!("{:?}", other),
+        }
+    }
+
+
+
+    #[test]
+    fn not_binds_tighter_than_and_which_binds_tighter_than_or(){
+        assert_eq!(condition("a = 1 OR b = 2 AND NOT c = 3"), equal("a", 1).or(equal("b", 2).and(equal("c", 3).negate())));
+        assert_eq!(condition("NOT a = 1 AND b = 2"), equal("a", 1).negate().and(equal("b", 2)));
+        assert_eq!(condition("a = 1 AND b = 2 OR c = 3"), equal("a", 1).and(equal("b", 2)).or(equal("c", 3)));
+        assert_eq!(condition("(a = 1 OR b = 2) AND c = 3"), equal("a", 1).or(equal("b", 2)).and(equal("c", 3)));
+        assert_eq!(condition("NOT (a = 1 OR b = 2)"), equal("a", 1).or(equal("b", 2)).negate());
+        assert_eq!(condition("NOT NOT a = 1"), equal("a", 1).negate().negate());
+        assert_eq!(condition("a = 1 OR b = 2 OR c = 3"), equal("a", 1).or(equal("b", 2)).or(equal("c", 3)));
+    }
+
+
+
+    #[test]
+    fn comparisons(){
+        let a = || "a".to_string();
+
+        assert_eq!(condition("a <= 5"), Predicate::Less(a(), Value::Int(5)).or(equal("a", 5)));
+        assert_eq!(condition("a >= 5"), Predicate::Greater(a(), Value::Int(5)).or(equal("a", 5)));
+        assert_eq!(condition("a <> 5"), equal("a", 5).negate());
+        assert_eq!(condition("a != 5"), equal("a", 5).negate());
+        assert_eq!(condition("a BETWEEN 1 AND 5"), Predicate::Between(a(), Value::Int(1), Value::Int(5)));
+        assert_eq!(condition("a NOT BETWEEN 1 AND 5"), Predicate::Between(a(), Value::Int(1), Value::Int(5)).negate());
+        assert_eq!(condition("a NOT IN (1, NULL)"), Predicate::In(a(), vec![Value::Int(1), Value::Null]).negate());
+        assert_eq!(condition("a not like 'x%'"), Predicate::Like(a(), "x%".to_string()).negate());
+        assert_eq!(condition("a IS NOT NULL"), Predicate::Is_null(a()).negate());
+
+        //the AND of a BETWEEN is not a condition of its own.
+        assert_eq!(condition("a BETWEEN 1 AND 5 AND b = 2"), Predicate::Between(a(), Value::Int(1), Value::Int(5)).and(equal("b", 2)));
+    }
+
+
+
+    #[test]
+    fn values(){
+        assert_eq!(value("-5"), Value::Int(-5));
+        assert_eq!(value("- 5"), Value::Int(-5));
+        assert_eq!(value("-1.5"), Value::Float(-1.5));
+        assert_eq!(value("2e3"), Value::Float(2000.0));
+        assert_eq!(value(&i64::MIN.to_string()), Value::Int(i64::MIN));
+        assert_eq!(value("'it''s'"), Value::String("it's".to_string()));
+        assert_eq!(value("true"), Value::Bool(true));
+        assert_eq!(value("FALSE"), Value::Bool(false));
+        assert_eq!(value("NULL"), Value::Null);
+    }
+
+
+
+    #[test]
+    fn quoted_identifiers_are_names(){
+        match parse("SELECT \"Select\", \"two words\" FROM \"from\" WHERE \"NULL\" IS NULL").unwrap().pop(){
+            Some(Statement::Select{table_name, column_names, predicate, ..})    => {
+                assert_eq!(table_name, "from");
+                assert_eq!(column_names, vec!["Select".to_string(), "two words".to_string()]);
+                assert_eq!(predicate, Some(Predicate::Is_null("NULL".to_string())));
+            },
+            other                                                               => panic!("{:?}", other),
+        }
+
+        //a keyword needs the quotes.
+        assert!(matches!(parse("SELECT * FROM from"), Err(Sql_error::Unexpected_token{..})));
+    }
+
+
+
+    #[test]
+    fn statements_are_separated_by_semicolons(){
+        let statements = parse(";DROP TABLE a;; drop table b;").unwrap();
+
+        assert_eq!(statements.len(), 2);
+        assert!(matches!(&statements[0], Statement::Drop_table{table_name} if table_name == "a"));
+        assert!(matches!(&statements[1], Statement::Drop_table{table_name} if table_name == "b"));
+
+        assert!(parse("").unwrap().is_empty());
+        assert!(matches!(parse("DROP TABLE a DROP TABLE b"), Err(Sql_error::Unexpected_token{expected, ..}) if expected == ";"));
+    }
+
+
+
+    #[test]
+    fn malformed_statements_are_errors(){
+        let unexpected_end = [
+            "SELECT * FROM",
+            "SELECT * FROM t WHERE",
+            "SELECT * FROM t WHERE a BETWEEN 1",
+            "SELECT * FROM t WHERE (a = 1",
+            "SELECT * FROM t WHERE a = -",
+            "INSERT INTO t VALUES (1",
+            "CREATE TABLE t (a INT",
+        ];
+
+        for sql in unexpected_end{
+            assert!(matches!(parse(sql), Err(Sql_error::Unexpected_end{..})), "{}", sql);
+        }
+
+        let unexpected_token = [
+            "SELEC * FROM t",
+            "SELECT * t",
+            "SELECT * FROM t WHERE a LIKE 5",
+            "SELECT * FROM t WHERE a = - 'x'",
+            "SELECT * FROM t WHERE a NOT = 1",
+            "SELECT * FROM t WHERE a IN ()",
+            "SELECT * FROM t LIMIT -1",
+            "ALTER TABLE t MODIFY a INT",
+            "CREATE TABLE t (a INT REFERENCES b ON DELETE NOTHING)",
+        ];
+
+        for sql in unexpected_token{
+            assert!(matches!(parse(sql), Err(Sql_error::Unexpected_token{..})), "{}", sql);
+        }
+
+        assert!(matches!(parse("CREATE TABLE t (a NUMBERS)"), Err(Sql_error::Unknown_type(name)) if name == "NUMBERS"));
+        assert!(matches!(parse("SELECT * FROM t LIMIT 1.5"), Err(Sql_error::Invalid_number(_))));
+        assert!(matches!(parse("UPDATE t SET a = 99999999999999999999"), Err(Sql_error::Invalid_number(_))));
+        assert!(matches!(parse("SELECT * FROM t WHERE a = 'x"), Err(Sql_error::Unterminated_string)));
+        assert!(matches!(parse("SELECT * FROM t WHERE a = 1 & b = 2"), Err(Sql_error::Unexpected_character('&'))));
+        assert!(matches!(parse("CREATE TABLE t (a INT, PRIMARY KEY (b))"), Err(Sql_error::Database(Database_error::Table(Table_error::Column_not_found(_))))));
+    }
+}
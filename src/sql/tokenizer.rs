@@ -0,0 +1,205 @@
+//Splits the text of SQL statements into tokens. Keywords are not told apart from names here, both
+//are Words and the parser compares them without case. A name in double quotes is a
+//Quoted_identifier, so it is never read as a keyword and keeps its case and spaces. Strings are in
+//single quotes, with '' for a quote inside them. Everything after -- on a line is a comment.
+
+use crate::sql::parser::Sql_error;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token{
+    Word(String),
+    Quoted_identifier(String),
+    Number(String),             //the digits as written, without a sign.
+    String(String),
+    Symbol(&'static str),
+}
+
+
+
+impl Token{
+
+    //the token as it would be written, for error messages.
+    pub fn describe(&self) -> String{
+        return match self{
+            Token::Word(word)               => word.clone(),
+            Token::Quoted_identifier(name)  => format!("\"{}\"", name),
+            Token::Number(number)           => number.clone(),
+            Token::String(text)             => format!("'{}'", text),
+            Token::Symbol(symbol)           => symbol.to_string(),
+        }
+    }
+}
+
+
+
+//symbols of two characters come first so <= is not read as < and =.
+const SYMBOLS: [&str; 13] = ["<=", ">=", "<>", "!=", "(", ")", ",", ";", "*", "=", "<", ">", "-"];
+
+
+
+pub fn tokenize(sql: &str) -> Result<Vec<Token>, Sql_error>{
+    let characters: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < characters.len(){
+        let c = characters[i];
+
+        if c.is_whitespace(){
+            i += 1;
+            continue
+        }
+
+        //a comment runs to the end of the line.
+        if c == '-' && characters.get(i + 1) == Some(&'-'){
+            while i < characters.len() && characters[i] != '\n'{
+                i += 1;
+            }
+            continue
+        }
+
+        if c.is_ascii_alphabetic() || c == '_'{
+            let start = i;
+            while i < characters.len() && (characters[i].is_ascii_alphanumeric() || characters[i] == '_'){
+                i += 1;
+            }
+            tokens.push(Token::Word(characters[start .. i].iter().collect()));
+            continue
+        }
+
+        if c.is_ascii_digit() || (c == '.' && characters.get(i + 1).is_some_and(|next| next.is_ascii_digit())){
+            let start = i;
+            while i < characters.len() && (characters[i].is_ascii_digit() || characters[i] == '.'){
+                i += 1;
+            }
+
+            //an exponent, as in 1.5e-3.
+            if i < characters.len() && matches!(characters[i], 'e' | 'E'){
+                let sign = matches!(characters.get(i + 1), Some('+' | '-')) as usize;
+                if characters.get(i + 1 + sign).is_some_and(|next| next.is_ascii_digit()){
+                    i += 1 + sign;
+                    while i < characters.len() && characters[i].is_ascii_digit(){
+                        i += 1;
+                    }
+                }
+            }
+
+            tokens.push(Token::Number(characters[start .. i].iter().collect()));
+            continue
+        }
+
+        if c == '\'' || c == '"'{
+            let (text, end) = quoted(&characters, i)?;
+            tokens.push(match c{
+                '\''    => Token::String(text),
+                _       => Token::Quoted_identifier(text),
+            });
+            i = end;
+            continue
+        }
+
+        match SYMBOLS.iter().find(|symbol| symbol.chars().enumerate().all(|(n, s)| characters.get(i + n) == Some(&s))){
+            None            => return Err(Sql_error::Unexpected_character(c)),
+            Some(symbol)    => {
+                tokens.push(Token::Symbol(symbol));
+                i += symbol.len();
+            },
+        }
+    }
+
+    return Ok(tokens)
+}
+
+
+
+//the text between the quote at start and the matching one, where two quotes stand for one, and
+//the position after the closing quote.
+fn quoted(characters: &[char], start: usize) -> Result<(String, usize), Sql_error>{
+    let quote = characters[start];
+    let mut text = String::new();
+    let mut i = start + 1;
+
+    loop{
+        match characters.get(i){
+            None                                                            => return Err(Sql_error::Unterminated_string),
+            Some(c) if *c == quote && characters.get(i + 1) == Some(&quote) => {
+                text.push(quote);
+                i += 2;
+            },
+            Some(c) if *c == quote                                          => return Ok((text, i + 1)),
+            Some(c)                                                         => {
+                text.push(*c);
+                i += 1;
+            },
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+
+    fn word(text: &str) -> Token{
+        return Token::Word(text.to_string())
+    }
+
+    fn number(text: &str) -> Token{
+        return Token::Number(text.to_string())
+    }
+
+
+
+    #[test]
+    fn tokens_of_a_statement(){
+        let sql = "select \"My Col\", x_1 FROM t -- a comment\nWHERE a <= -1.5e-3 AND b>=.5 AND c <> 'it''s' AND d != 2;";
+
+        assert_eq!(tokenize(sql).unwrap(), vec![
+            word("select"), Token::Quoted_identifier("My Col".to_string()), Token::Symbol(","), word("x_1"), word("FROM"), word("t"),
+            word("WHERE"), word("a"), Token::Symbol("<="), Token::Symbol("-"), number("1.5e-3"),
+            word("AND"), word("b"), Token::Symbol(">="), number(".5"),
+            word("AND"), word("c"), Token::Symbol("<>"), Token::String("it's".to_string()),
+            word("AND"), word("d"), Token::Symbol("!="), number("2"), Token::Symbol(";"),
+        ]);
+    }
+
+
+
+    #[test]
+    fn quoted_text(){
+        assert_eq!(tokenize("''").unwrap(), vec![Token::String(String::new())]);
+        assert_eq!(tokenize("''''").unwrap(), vec![Token::String("'".to_string())]);
+        assert_eq!(tokenize("'a \"b\" -- c'").unwrap(), vec![Token::String("a \"b\" -- c".to_string())]);
+        assert_eq!(tokenize("\"Select\"").unwrap(), vec![Token::Quoted_identifier("Select".to_string())]);
+        assert_eq!(tokenize("\"a \"\"b\"\"\"").unwrap(), vec![Token::Quoted_identifier("a \"b\"".to_string())]);
+    }
+
+
+
+    #[test]
+    fn numbers(){
+        assert_eq!(tokenize("12 3.25 1e10 2E+3").unwrap(), vec![number("12"), number("3.25"), number("1e10"), number("2E+3")]);
+
+        //an e that is not followed by digits is not part of the number.
+        assert_eq!(tokenize("1e").unwrap(), vec![number("1"), word("e")]);
+        assert_eq!(tokenize("1e-x").unwrap(), vec![number("1"), word("e"), Token::Symbol("-"), word("x")]);
+
+        //a sign is a symbol of its own.
+        assert_eq!(tokenize("-5").unwrap(), vec![Token::Symbol("-"), number("5")]);
+        assert_eq!(tokenize("--5").unwrap(), vec![]);
+    }
+
+
+
+    #[test]
+    fn malformed_text_is_an_error(){
+        assert!(matches!(tokenize("'abc"), Err(Sql_error::Unterminated_string)));
+        assert!(matches!(tokenize("\"abc"), Err(Sql_error::Unterminated_string)));
+        assert!(matches!(tokenize("'it''"), Err(Sql_error::Unterminated_string)));
+        assert!(matches!(tokenize("a @ b"), Err(Sql_error::Unexpected_character('@'))));
+        assert!(matches!(tokenize("a ! b"), Err(Sql_error::Unexpected_character('!'))));
+    }
+}
@@ -1,13 +1,957 @@
+//B+ tree stored in B_tree pages of a table file.
+//
+//Every node is one page. Interior nodes only hold separator keys and child page numbers, all the
+//values are stored in the leaves. Leaves are linked to their neighbours through the page's
+//previous/next page meta data so range scans can walk along the bottom of the tree.
+//
+//Node layout (starts straight after the 17 bytes of page meta data):
+//
+// 0 | 0 0 | (0 0 0 0) | entries ...
+// |    |       |
+// |    |   leftmost child (interior nodes only)
+// | entry count
+//node type
+//
+//leaf entry:       2 bytes key size | key | 2 bytes value size | value
+//interior entry:   2 bytes key size | key | 4 bytes child page num
+//
+//The keys of an interior entry are the smallest key of the child to its right, so a child at
+//position i holds keys >= keys[i-1] and < keys[i].
+//
+//Keys are made with Value::encode_key, which sorts byte by byte in the same order as the values,
+//so nodes compare keys without decoding them. A key can hold the values of several columns.
+//
+//A node which drops below half of a page after a delete either takes entries from a neighbour or
+//is merged into it. Pages emptied by a merge go back to the table's free page list.
+
+use std::cmp::Ordering;
+use std::io::Error;
+use std::io::ErrorKind;
+use std::ops::Bound;
+
+use crate::file_manager::block::Block_ID;
+use crate::file_manager::file_manager::File_manager;
+use crate::file_manager::page::Page_type;
+use crate::file_manager::page::PAGE_HEADER_SIZE;
+use crate::buffer_pool::page_table::Page_table;
+use crate::table::free_page_list::allocate_page;
+use crate::table::free_page_list::free_page;
+
+
+const NODE_START: usize = PAGE_HEADER_SIZE as usize;
+
+const LEAF_NODE: u8 = 1;
+const INTERIOR_NODE: u8 = 2;
+
+const LEAF_HEADER_SIZE: usize = 3;
+const INTERIOR_HEADER_SIZE: usize = 7;
+
+
+//a key and its value as stored in a leaf.
+pub type B_tree_entry = (Vec<u8>, Vec<u8>);
+
+
+#[derive(Debug)]
 pub struct B_tree{
-    
+    pub file_name:      String,
+    pub root_page_num:  u32,
+}
+
+
+#[derive(Debug)]
+struct B_tree_node{
+    page_num:       u32,
+    is_leaf:        bool,
+    keys:           Vec<Vec<u8>>,
+    values:         Vec<Vec<u8>>,   //leaf nodes only
+    children:       Vec<u32>,       //interior nodes only, always one more than the keys
+    previous_leaf:  Option<u32>,
+    next_leaf:      Option<u32>,
+}
+
+
+
+impl B_tree_node{
+
+    fn new_leaf(page_num: u32) -> B_tree_node{
+        return B_tree_node{
+            page_num,
+            is_leaf:        true,
+            keys:           Vec::new(),
+            values:         Vec::new(),
+            children:       Vec::new(),
+            previous_leaf:  None,
+            next_leaf:      None,
+        }
+    }
+
+    fn new_interior(page_num: u32) -> B_tree_node{
+        return B_tree_node{
+            page_num,
+            is_leaf:        false,
+            keys:           Vec::new(),
+            values:         Vec::new(),
+            children:       Vec::new(),
+            previous_leaf:  None,
+            next_leaf:      None,
+        }
+    }
+
+
+
+    fn entry_size(&self, index: usize) -> usize{
+        if self.is_leaf{
+            return 4 + self.keys[index].len() + self.values[index].len()
+        }else{
+            return 6 + self.keys[index].len()
+        }
+    }
+
+    fn header_size(&self) -> usize{
+        if self.is_leaf{
+            return LEAF_HEADER_SIZE
+        }else{
+            return INTERIOR_HEADER_SIZE
+        }
+    }
+
+    fn size(&self) -> usize{
+        let mut size = self.header_size();
+        for i in 0..self.keys.len(){
+            size += self.entry_size(i);
+        }
+        return size
+    }
+
+
+
+    //reads a node out of a page. A page that was never written to is an empty leaf.
+    fn load(page_num: u32, tree: &B_tree, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<B_tree_node, std::io::Error>{
+        let block = Block_ID{file_name: tree.file_name.clone(), number: page_num};
+
+        let page = match page_table.get_mut_page(block, file_manager){
+            None    => return Err(Error::other("B tree page could not be loaded.")),
+            Some(p) => p,
+        };
+
+        let bytes = &page.bytes;
+        let node_type = bytes[NODE_START];
+        let count = u16::from_be_bytes([bytes[NODE_START + 1], bytes[NODE_START + 2]]) as usize;
+
+        let mut node = if node_type == INTERIOR_NODE{
+            B_tree_node::new_interior(page_num)
+        }else{
+            B_tree_node::new_leaf(page_num)
+        };
+
+        let mut index = NODE_START + node.header_size();
+
+        if !node.is_leaf{
+            node.children.push(u32::from_be_bytes(bytes[NODE_START + 3 .. NODE_START + 7].try_into().unwrap()));
+        }else{
+            node.previous_leaf = page.previous_index;
+            node.next_leaf = page.next_index;
+        }
+
+        for _ in 0..count{
+            let key_size = u16::from_be_bytes([bytes[index], bytes[index + 1]]) as usize;
+            index += 2;
+            node.keys.push(bytes[index .. index + key_size].to_vec());
+            index += key_size;
+
+            if node.is_leaf{
+                let value_size = u16::from_be_bytes([bytes[index], bytes[index + 1]]) as usize;
+                index += 2;
+                node.values.push(bytes[index .. index + value_size].to_vec());
+                index += value_size;
+            }else{
+                node.children.push(u32::from_be_bytes(bytes[index .. index + 4].try_into().unwrap()));
+                index += 4;
+            }
+        }
+
+        return Ok(node)
+    }
+
+
+
+    fn save(&self, tree: &B_tree, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+        let block = Block_ID{file_name: tree.file_name.clone(), number: self.page_num};
+
+        let mut bytes: Vec<u8> = Vec::with_capacity(self.size());
+
+        if self.is_leaf{
+            bytes.push(LEAF_NODE);
+            bytes.extend_from_slice(&(self.keys.len() as u16).to_be_bytes());
+        }else{
+            bytes.push(INTERIOR_NODE);
+            bytes.extend_from_slice(&(self.keys.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(&self.children[0].to_be_bytes());
+        }
+
+        for i in 0..self.keys.len(){
+            bytes.extend_from_slice(&(self.keys[i].len() as u16).to_be_bytes());
+            bytes.extend_from_slice(&self.keys[i]);
+
+            if self.is_leaf{
+                bytes.extend_from_slice(&(self.values[i].len() as u16).to_be_bytes());
+                bytes.extend_from_slice(&self.values[i]);
+            }else{
+                bytes.extend_from_slice(&self.children[i + 1].to_be_bytes());
+            }
+        }
+
+        let page = match page_table.get_mut_page(block.clone(), file_manager){
+            None    => return Err(Error::other("B tree page could not be loaded.")),
+            Some(p) => p,
+        };
+
+        if NODE_START + bytes.len() > page.record_index_end_point as usize{
+            return Err(Error::other("B tree node is too large for its page."))
+        }
+
+        let end_point = (NODE_START + bytes.len()) as u16;
+        page.write(NODE_START as u16, bytes)?;
+        page.data_end_point = end_point;
+        page.page_type = Page_type::B_tree;
+
+        if self.is_leaf{
+            page.previous_index = self.previous_leaf;
+            page.next_index = self.next_leaf;
+        }else{
+            page.previous_index = None;
+            page.next_index = None;
+        }
+
+        page_table.set_dirty(&block);
+
+        return Ok(1)
+    }
+}
+
+
+
+impl B_tree{
+
+    pub fn new(file_name: String, root_page_num: u32) -> B_tree{
+        return B_tree{
+            file_name,
+            root_page_num,
+        }
+    }
+
+
+
+    //space in a page that a node can use.
+    fn node_capacity(&self, page_table: &Page_table) -> usize{
+        return page_table.page_size as usize - 2 - NODE_START
+    }
+
+    fn is_underfull(&self, node: &B_tree_node, page_table: &Page_table) -> bool{
+        return node.size() < self.node_capacity(page_table) / 2
+    }
+
+    //an entry can be at most a quarter of a node so that a split always leaves both halves with
+    //at least one entry.
+    fn max_entry_size(&self, page_table: &Page_table) -> usize{
+        return self.node_capacity(page_table) / 4
+    }
+
+
+
+    pub fn compare_keys(&self, a: &[u8], b: &[u8]) -> Ordering{
+        return a.cmp(b)
+    }
+
+
+
+    //position of the first key in the node which is >= key, and whether it is equal.
+    fn search_node(&self, node: &B_tree_node, key: &[u8]) -> (usize, bool){
+        let mut low = 0;
+        let mut high = node.keys.len();
+
+        while low < high{
+            let middle = (low + high) / 2;
+            match self.compare_keys(&node.keys[middle], key){
+                Ordering::Less      => low = middle + 1,
+                Ordering::Equal     => return (middle, true),
+                Ordering::Greater   => high = middle,
+            }
+        }
+
+        return (low, false)
+    }
+
+    //index of the child which could contain the key.
+    fn child_index(&self, node: &B_tree_node, key: &[u8]) -> usize{
+        let (index, found) = self.search_node(node, key);
+        if found{
+            return index + 1
+        }
+        return index
+    }
+
+
+
+    fn find_leaf(&self, key: Option<&[u8]>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<B_tree_node, std::io::Error>{
+        let mut node = B_tree_node::load(self.root_page_num, self, page_table, file_manager)?;
+
+        while !node.is_leaf{
+            let child = match key{
+                Some(key)   => node.children[self.child_index(&node, key)],
+                None        => node.children[0],
+            };
+            node = B_tree_node::load(child, self, page_table, file_manager)?;
+        }
+
+        return Ok(node)
+    }
+
+
+
+    pub fn search(&self, key: &[u8], page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Option<Vec<u8>>, std::io::Error>{
+        let leaf = self.find_leaf(Some(key), page_table, file_manager)?;
+
+        let (index, found) = self.search_node(&leaf, key);
+
+        if found{
+            return Ok(Some(leaf.values[index].clone()))
+        }
+        return Ok(None)
+    }
+
+
+
+    //returns every (key, value) pair in order with a key between the two bounds.
+    pub fn range(&self, from: Bound<&[u8]>, to: Bound<&[u8]>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<B_tree_entry>, std::io::Error>{
+        let start_key = match from{
+            Bound::Included(key) | Bound::Excluded(key) => Some(key),
+            Bound::Unbounded                            => None,
+        };
+
+        let mut leaf = self.find_leaf(start_key, page_table, file_manager)?;
+
+        let mut index = match from{
+            Bound::Included(key)    => self.search_node(&leaf, key).0,
+            Bound::Excluded(key)    => {
+                let (index, found) = self.search_node(&leaf, key);
+                if found { index + 1 } else { index }
+            },
+            Bound::Unbounded        => 0,
+        };
+
+        let mut result = Vec::new();
+
+        loop{
+            while index < leaf.keys.len(){
+                let key = &leaf.keys[index];
+
+                let in_range = match to{
+                    Bound::Included(end)    => self.compare_keys(key, end) != Ordering::Greater,
+                    Bound::Excluded(end)    => self.compare_keys(key, end) == Ordering::Less,
+                    Bound::Unbounded        => true,
+                };
+
+                if !in_range{
+                    return Ok(result)
+                }
+
+                result.push((key.clone(), leaf.values[index].clone()));
+                index += 1;
+            }
+
+            match leaf.next_leaf{
+                None            => return Ok(result),
+                Some(next)      => {
+                    leaf = B_tree_node::load(next, self, page_table, file_manager)?;
+                    index = 0;
+                },
+            }
+        }
+    }
+
+
+
+    //inserts a new key. Keys are unique so inserting an existing key is an error.
+    pub fn insert(&mut self, key: &[u8], value: &[u8], page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+
+        if key.len() + value.len() + 6 > self.max_entry_size(page_table){
+            return Err(Error::new(ErrorKind::InvalidInput, "Key is too large for the B tree."))
+        }
+
+        let split = self.insert_into(self.root_page_num, key, value, page_table, file_manager)?;
+
+        //the root was split so the tree grows by one level with a new root above the two halves.
+        if let Some((separator, right_page_num)) = split{
+            let new_root_page_num = allocate_page(&self.file_name, Page_type::B_tree, page_table, file_manager)?;
+
+            let mut new_root = B_tree_node::new_interior(new_root_page_num);
+            new_root.keys.push(separator);
+            new_root.children.push(self.root_page_num);
+            new_root.children.push(right_page_num);
+            new_root.save(self, page_table, file_manager)?;
+
+            self.root_page_num = new_root_page_num;
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //inserts into the subtree at page_num. If the node had to split, the separator key and the
+    //new right node are returned so the parent can add them.
+    fn insert_into(&mut self, page_num: u32, key: &[u8], value: &[u8], page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Option<(Vec<u8>, u32)>, std::io::Error>{
+        let mut node = B_tree_node::load(page_num, self, page_table, file_manager)?;
+
+        if node.is_leaf{
+            let (index, found) = self.search_node(&node, key);
+            if found{
+                return Err(Error::new(ErrorKind::AlreadyExists, "Key already exists in the B tree."))
+            }
+
+            node.keys.insert(index, key.to_vec());
+            node.values.insert(index, value.to_vec());
+
+        }else{
+            let index = self.child_index(&node, key);
+
+            let split = self.insert_into(node.children[index], key, value, page_table, file_manager)?;
+
+            match split{
+                None                            => return Ok(None),
+                Some((separator, right_page))   => {
+                    node.keys.insert(index, separator);
+                    node.children.insert(index + 1, right_page);
+                },
+            }
+        }
+
+        if node.size() <= self.node_capacity(page_table){
+            node.save(self, page_table, file_manager)?;
+            return Ok(None)
+        }
+
+        return self.split(node, page_table, file_manager).map(Some)
+    }
+
+
+
+    //index to split a node at so both halves hold about the same amount of bytes. An interior
+    //node gives its middle key to the parent so it has to leave at least one key on each side.
+    fn split_point(node: &B_tree_node) -> usize{
+        let half = node.size() / 2;
+        let mut size = node.header_size();
+        let last = if node.is_leaf { node.keys.len() - 1 } else { node.keys.len() - 2 };
+
+        for i in 0..node.keys.len(){
+            size += node.entry_size(i);
+            if size >= half{
+                return std::cmp::max(1, std::cmp::min(i + 1, last))
+            }
+        }
+
+        return node.keys.len() / 2
+    }
+
+
+
+    fn split(&mut self, mut node: B_tree_node, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<(Vec<u8>, u32), std::io::Error>{
+        let right_page_num = allocate_page(&self.file_name, Page_type::B_tree, page_table, file_manager)?;
+        let split_index = Self::split_point(&node);
+
+        let separator;
+        let mut right;
+
+        if node.is_leaf{
+            right = B_tree_node::new_leaf(right_page_num);
+            right.keys = node.keys.split_off(split_index);
+            right.values = node.values.split_off(split_index);
+            separator = right.keys[0].clone();
+
+            //linking the new leaf in between the node and its old neighbour.
+            right.previous_leaf = Some(node.page_num);
+            right.next_leaf = node.next_leaf;
+
+            if let Some(next_page_num) = node.next_leaf{
+                let mut next = B_tree_node::load(next_page_num, self, page_table, file_manager)?;
+                next.previous_leaf = Some(right_page_num);
+                next.save(self, page_table, file_manager)?;
+            }
+
+            node.next_leaf = Some(right_page_num);
+
+        }else{
+            //the middle key moves up to the parent, it is not kept in either half.
+            right = B_tree_node::new_interior(right_page_num);
+            right.keys = node.keys.split_off(split_index + 1);
+            right.children = node.children.split_off(split_index + 1);
+            separator = node.keys.pop().unwrap();
+        }
+
+        node.save(self, page_table, file_manager)?;
+        right.save(self, page_table, file_manager)?;
+
+        return Ok((separator, right_page_num))
+    }
+
 
 
 
+    //frees every page of the tree, including the root.
+    pub fn destroy(&self, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+        let mut page_nums = vec![self.root_page_num];
+
+        while let Some(page_num) = page_nums.pop(){
+            let node = B_tree_node::load(page_num, self, page_table, file_manager)?;
+            page_nums.extend_from_slice(&node.children);
+
+            free_page(&self.file_name, page_num, page_table, file_manager)?;
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //removes a key and returns its value, or None if the key was not in the tree.
+    pub fn delete(&mut self, key: &[u8], page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Option<Vec<u8>>, std::io::Error>{
+        let (removed, _) = self.delete_from(self.root_page_num, key, page_table, file_manager)?;
+
+        //an interior root left with a single child is removed so the tree shrinks by one level.
+        let root = B_tree_node::load(self.root_page_num, self, page_table, file_manager)?;
+
+        if !root.is_leaf && root.keys.is_empty(){
+            let old_root_page_num = self.root_page_num;
+            self.root_page_num = root.children[0];
+            free_page(&self.file_name, old_root_page_num, page_table, file_manager)?;
+        }
+
+        return Ok(removed)
+    }
+
+
+
+    //deletes from the subtree at page_num. Returns the removed value and whether the node is now
+    //less than half full.
+    fn delete_from(&mut self, page_num: u32, key: &[u8], page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<(Option<Vec<u8>>, bool), std::io::Error>{
+        let mut node = B_tree_node::load(page_num, self, page_table, file_manager)?;
+
+        if node.is_leaf{
+            let (index, found) = self.search_node(&node, key);
+            if !found{
+                return Ok((None, false))
+            }
+
+            node.keys.remove(index);
+            let removed = node.values.remove(index);
+            node.save(self, page_table, file_manager)?;
+
+            return Ok((Some(removed), self.is_underfull(&node, page_table)))
+        }
+
+        let index = self.child_index(&node, key);
+        let (removed, child_underfull) = self.delete_from(node.children[index], key, page_table, file_manager)?;
+
+        if !child_underfull{
+            return Ok((removed, false))
+        }
+
+        self.rebalance(&mut node, index, page_table, file_manager)?;
+        node.save(self, page_table, file_manager)?;
+
+        return Ok((removed, self.is_underfull(&node, page_table)))
+    }
+
+
+
+    //fixes an underfull child of parent by merging it with a neighbour, or if both together do
+    //not fit in one page by spreading their entries evenly between the two.
+    fn rebalance(&mut self, parent: &mut B_tree_node, child_index: usize, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+        if parent.children.len() < 2{
+            return Ok(0)
+        }
+
+        //the pair of neighbours is always taken left to right.
+        let left_index = if child_index > 0 { child_index - 1 } else { child_index };
+
+        let mut left = B_tree_node::load(parent.children[left_index], self, page_table, file_manager)?;
+        let mut right = B_tree_node::load(parent.children[left_index + 1], self, page_table, file_manager)?;
+
+        if left.is_leaf{
+            left.keys.append(&mut right.keys);
+            left.values.append(&mut right.values);
+        }else{
+            left.keys.push(parent.keys[left_index].clone());
+            left.keys.append(&mut right.keys);
+            left.children.append(&mut right.children);
+        }
+
+        if left.size() <= self.node_capacity(page_table){
+            //merging the right node into the left one.
+            if left.is_leaf{
+                left.next_leaf = right.next_leaf;
+
+                if let Some(next_page_num) = right.next_leaf{
+                    let mut next = B_tree_node::load(next_page_num, self, page_table, file_manager)?;
+                    next.previous_leaf = Some(left.page_num);
+                    next.save(self, page_table, file_manager)?;
+                }
+            }
+
+            parent.keys.remove(left_index);
+            parent.children.remove(left_index + 1);
+
+            left.save(self, page_table, file_manager)?;
+            free_page(&self.file_name, right.page_num, page_table, file_manager)?;
+
+            return Ok(1)
+        }
+
+        //redistributing the entries between both nodes.
+        let split_index = Self::split_point(&left);
+
+        if left.is_leaf{
+            right.keys = left.keys.split_off(split_index);
+            right.values = left.values.split_off(split_index);
+            parent.keys[left_index] = right.keys[0].clone();
+        }else{
+            right.keys = left.keys.split_off(split_index + 1);
+            right.children = left.children.split_off(split_index + 1);
+            parent.keys[left_index] = left.keys.pop().unwrap();
+        }
+
+        left.save(self, page_table, file_manager)?;
+        right.save(self, page_table, file_manager)?;
+
+        return Ok(1)
+    }
 }
 
 
-impl B_tre{
 
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::test_utils::open_test_table;
+
+
+    fn new_tree(name: &str) -> (File_manager, Page_table, B_tree){
+        let (file_manager, page_table, table) = open_test_table(name);
+        return (file_manager, page_table, B_tree::new(name.to_string(), table.b_tree_page_num))
+    }
+
+    //a key of size bytes that sorts by n.
+    fn key(n: u32, size: usize) -> Vec<u8>{
+        let mut key = n.to_be_bytes().to_vec();
+        key.resize(size, b'k');
+        return key
+    }
+
+    fn value(n: u32, size: usize) -> Vec<u8>{
+        let mut value = format!("value {}", n).into_bytes();
+        value.resize(size, b'v');
+        return value
+    }
+
+    //the number of levels, found by following the leftmost children.
+    fn depth(tree: &B_tree, page_table: &mut Page_table, file_manager: &mut File_manager) -> usize{
+        let mut node = B_tree_node::load(tree.root_page_num, tree, page_table, file_manager).unwrap();
+        let mut depth = 1;
+
+        while !node.is_leaf{
+            node = B_tree_node::load(node.children[0], tree, page_table, file_manager).unwrap();
+            depth += 1;
+        }
+
+        return depth
+    }
+
+    //checks that keys are ordered and within the separators above them, that all leaves are on the
+    //same level and linked in order both ways, and that no node other than the root is much less
+    //than half full. Returns the keys of the tree in order.
+    fn check_tree(tree: &B_tree, page_table: &mut Page_table, file_manager: &mut File_manager) -> Vec<Vec<u8>>{
+        let mut leaves = Vec::new();
+        let mut leaf_depths = Vec::new();
+        check_node(tree, tree.root_page_num, None, None, 1, &mut leaves, &mut leaf_depths, page_table, file_manager);
+
+        assert!(leaf_depths.iter().all(|depth| *depth == leaf_depths[0]));
+
+        let mut keys = Vec::new();
+
+        for (i, page_num) in leaves.iter().enumerate(){
+            let leaf = B_tree_node::load(*page_num, tree, page_table, file_manager).unwrap();
+            assert_eq!(leaf.previous_leaf.filter(|n| *n != 0), if i == 0 { None } else { Some(leaves[i - 1]) });
+            assert_eq!(leaf.next_leaf.filter(|n| *n != 0), leaves.get(i + 1).copied());
+            keys.extend(leaf.keys);
+        }
+
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+
+        return keys
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn check_node(tree: &B_tree, page_num: u32, low: Option<&[u8]>, high: Option<&[u8]>, depth: usize, leaves: &mut Vec<u32>, leaf_depths: &mut Vec<usize>, page_table: &mut Page_table, file_manager: &mut File_manager){
+        let node = B_tree_node::load(page_num, tree, page_table, file_manager).unwrap();
+
+        if page_num != tree.root_page_num{
+            assert!(!node.keys.is_empty());
+            assert!(node.size() + tree.max_entry_size(page_table) >= tree.node_capacity(page_table) / 2);
+        }
+
+        for key in node.keys.iter(){
+            assert!(low.is_none_or(|low| key.as_slice() >= low));
+            assert!(high.is_none_or(|high| key.as_slice() < high));
+        }
+
+        if node.is_leaf{
+            leaves.push(page_num);
+            leaf_depths.push(depth);
+            return
+        }
+
+        assert_eq!(node.children.len(), node.keys.len() + 1);
+
+        for (i, child) in node.children.iter().enumerate(){
+            let child_low = if i == 0 { low } else { Some(node.keys[i - 1].as_slice()) };
+            let child_high = if i == node.keys.len() { high } else { Some(node.keys[i].as_slice()) };
+            check_node(tree, *child, child_low, child_high, depth + 1, leaves, leaf_depths, page_table, file_manager);
+        }
+    }
+
+    //the keys 0..count inserted in a scrambled order.
+    fn fill(tree: &mut B_tree, count: u32, key_size: usize, value_size: usize, page_table: &mut Page_table, file_manager: &mut File_manager){
+        for i in 0..count{
+            let n = (i * 7919) % count;
+            tree.insert(&key(n, key_size), &value(n, value_size), page_table, file_manager).unwrap();
+        }
+    }
+
+
+
+    #[test]
+    fn insert_and_search(){
+        let (mut fm, mut pt, mut tree) = new_tree("b_tree_insert");
+
+        fill(&mut tree, 100, 8, 20, &mut pt, &mut fm);
+
+        assert_eq!(depth(&tree, &mut pt, &mut fm), 1);
+
+        for n in 0..100{
+            assert_eq!(tree.search(&key(n, 8), &mut pt, &mut fm).unwrap(), Some(value(n, 20)));
+        }
+
+        assert_eq!(tree.search(&key(100, 8), &mut pt, &mut fm).unwrap(), None);
+
+        let error = tree.insert(&key(5, 8), b"again", &mut pt, &mut fm).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::AlreadyExists);
+        assert_eq!(tree.search(&key(5, 8), &mut pt, &mut fm).unwrap(), Some(value(5, 20)));
+    }
+
+
+
+    #[test]
+    fn leaf_split_adds_an_interior_root(){
+        let (mut fm, mut pt, mut tree) = new_tree("b_tree_leaf_split");
+        let first_root = tree.root_page_num;
+
+        //a leaf holds 16 of these entries.
+        fill(&mut tree, 40, 8, 1000, &mut pt, &mut fm);
+
+        assert_ne!(tree.root_page_num, first_root);
+        assert_eq!(depth(&tree, &mut pt, &mut fm), 2);
+
+        let root = B_tree_node::load(tree.root_page_num, &tree, &mut pt, &mut fm).unwrap();
+        assert!(root.children.len() >= 3);
+
+        assert_eq!(check_tree(&tree, &mut pt, &mut fm), (0..40).map(|n| key(n, 8)).collect::<Vec<_>>());
+
+        for n in 0..40{
+            assert_eq!(tree.search(&key(n, 8), &mut pt, &mut fm).unwrap(), Some(value(n, 1000)));
+        }
+    }
+
+
+
+    #[test]
+    fn interior_split_grows_the_tree(){
+        let (mut fm, mut pt, mut tree) = new_tree("b_tree_interior_split");
+
+        //large keys keep interior nodes to a handful of entries, so the root splits again.
+        fill(&mut tree, 300, 2000, 10, &mut pt, &mut fm);
+
+        assert!(depth(&tree, &mut pt, &mut fm) >= 3);
+        assert_eq!(check_tree(&tree, &mut pt, &mut fm), (0..300).map(|n| key(n, 2000)).collect::<Vec<_>>());
+
+        for n in 0..300{
+            assert_eq!(tree.search(&key(n, 2000), &mut pt, &mut fm).unwrap(), Some(value(n, 10)));
+        }
+
+        //the tree is read back the same from disk.
+        pt.write_all(&mut fm).unwrap();
+        let mut fresh_pt = Page_table::new(pt.page_size as u32 * 16, pt.page_size, vec![tree.file_name.clone()], &mut fm);
+        assert_eq!(check_tree(&tree, &mut fresh_pt, &mut fm).len(), 300);
+    }
+
+
+
+    #[test]
+    fn range_scans(){
+        let (mut fm, mut pt, mut tree) = new_tree("b_tree_range");
+
+        fill(&mut tree, 500, 8, 300, &mut pt, &mut fm);
+        assert!(depth(&tree, &mut pt, &mut fm) >= 2);
+
+        let keys = |entries: Vec<B_tree_entry>| -> Vec<u32> {
+            return entries.iter().map(|(key, _)| u32::from_be_bytes(key[..4].try_into().unwrap())).collect()
+        };
+
+        let (low, high) = (key(100, 8), key(300, 8));
+
+        let all = tree.range(Bound::Unbounded, Bound::Unbounded, &mut pt, &mut fm).unwrap();
+        assert_eq!(keys(all), (0..500).collect::<Vec<_>>());
+
+        let included = tree.range(Bound::Included(&low), Bound::Included(&high), &mut pt, &mut fm).unwrap();
+        assert_eq!(keys(included), (100..=300).collect::<Vec<_>>());
+
+        let excluded = tree.range(Bound::Excluded(&low), Bound::Excluded(&high), &mut pt, &mut fm).unwrap();
+        assert_eq!(keys(excluded), (101..300).collect::<Vec<_>>());
+
+        let from = tree.range(Bound::Included(&high), Bound::Unbounded, &mut pt, &mut fm).unwrap();
+        assert_eq!(keys(from), (300..500).collect::<Vec<_>>());
+
+        let to = tree.range(Bound::Unbounded, Bound::Excluded(&low), &mut pt, &mut fm).unwrap();
+        assert_eq!(keys(to), (0..100).collect::<Vec<_>>());
+
+        //bounds between keys and past the ends.
+        let (between, past) = (vec![0, 0, 0, 7, 0xff], key(1000, 8));
+        let partial = tree.range(Bound::Excluded(&between), Bound::Included(&past), &mut pt, &mut fm).unwrap();
+        assert_eq!(keys(partial), (8..500).collect::<Vec<_>>());
+
+        assert!(tree.range(Bound::Included(&past), Bound::Unbounded, &mut pt, &mut fm).unwrap().is_empty());
+        assert!(tree.range(Bound::Included(&high), Bound::Excluded(&low), &mut pt, &mut fm).unwrap().is_empty());
+    }
+
+
+
+    #[test]
+    fn oversized_entries_are_rejected(){
+        let (mut fm, mut pt, mut tree) = new_tree("b_tree_oversized");
+        let max_entry_size = tree.max_entry_size(&pt);
+
+        //an entry is the key and value plus 6 bytes of sizes and slot.
+        tree.insert(&key(1, 100), &vec![b'v'; max_entry_size - 106], &mut pt, &mut fm).unwrap();
+
+        let error = tree.insert(&key(2, 100), &vec![b'v'; max_entry_size - 105], &mut pt, &mut fm).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+        let error = tree.insert(&key(3, max_entry_size), b"", &mut pt, &mut fm).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+        assert_eq!(tree.search(&key(2, 100), &mut pt, &mut fm).unwrap(), None);
+        assert_eq!(check_tree(&tree, &mut pt, &mut fm), vec![key(1, 100)]);
+    }
+
+
+
+    #[test]
+    fn delete_merges_and_shrinks_the_root(){
+        let (mut fm, mut pt, mut tree) = new_tree("b_tree_delete");
+
+        fill(&mut tree, 300, 2000, 10, &mut pt, &mut fm);
+        let first_depth = depth(&tree, &mut pt, &mut fm);
+        assert!(first_depth >= 3);
+
+        let mut remaining: Vec<u32> = (0..300).collect();
+
+        for i in 0..297{
+            let n = (i * 4099) % 300;
+            assert_eq!(tree.delete(&key(n, 2000), &mut pt, &mut fm).unwrap(), Some(value(n, 10)));
+            assert_eq!(tree.delete(&key(n, 2000), &mut pt, &mut fm).unwrap(), None);
+            remaining.retain(|r| *r != n);
+
+            if i % 20 == 0{
+                assert_eq!(check_tree(&tree, &mut pt, &mut fm), remaining.iter().map(|n| key(*n, 2000)).collect::<Vec<_>>());
+            }
+        }
+
+        assert_eq!(depth(&tree, &mut pt, &mut fm), 1);
+        assert_eq!(check_tree(&tree, &mut pt, &mut fm), remaining.iter().map(|n| key(*n, 2000)).collect::<Vec<_>>());
+
+        for n in 0..300{
+            let expected = if remaining.contains(&n) { Some(value(n, 10)) } else { None };
+            assert_eq!(tree.search(&key(n, 2000), &mut pt, &mut fm).unwrap(), expected);
+        }
+
+        let all = tree.range(Bound::Unbounded, Bound::Unbounded, &mut pt, &mut fm).unwrap();
+        assert_eq!(all, remaining.iter().map(|n| (key(*n, 2000), value(*n, 10))).collect::<Vec<_>>());
+    }
+
+
+
+    #[test]
+    fn delete_redistributes_between_leaves(){
+        let (mut fm, mut pt, mut tree) = new_tree("b_tree_redistribute");
+        let capacity = tree.node_capacity(&pt);
+
+        //ascending keys until the root leaf splits in two.
+        let mut count = 0;
+        while depth(&tree, &mut pt, &mut fm) == 1{
+            tree.insert(&key(count, 8), &value(count, 1000), &mut pt, &mut fm).unwrap();
+            count += 1;
+        }
+
+        //filling the right leaf so the two leaves no longer fit in one page.
+        let root = B_tree_node::load(tree.root_page_num, &tree, &mut pt, &mut fm).unwrap();
+        let (left_page_num, right_page_num) = (root.children[0], root.children[1]);
+
+        while B_tree_node::load(right_page_num, &tree, &mut pt, &mut fm).unwrap().size() + 1012 <= capacity{
+            tree.insert(&key(count, 8), &value(count, 1000), &mut pt, &mut fm).unwrap();
+            count += 1;
+        }
+
+        //deleting from the left leaf until it is underfull and takes entries from the right one.
+        let left_count = B_tree_node::load(left_page_num, &tree, &mut pt, &mut fm).unwrap().keys.len();
+        let mut n = 0;
+        loop{
+            tree.delete(&key(n, 8), &mut pt, &mut fm).unwrap();
+            n += 1;
+
+            if B_tree_node::load(left_page_num, &tree, &mut pt, &mut fm).unwrap().keys.len() > left_count - n as usize{
+                break
+            }
+        }
+
+        let root = B_tree_node::load(tree.root_page_num, &tree, &mut pt, &mut fm).unwrap();
+        assert_eq!(root.children, vec![left_page_num, right_page_num]);
+
+        let right = B_tree_node::load(right_page_num, &tree, &mut pt, &mut fm).unwrap();
+        assert_eq!(root.keys, vec![right.keys[0].clone()]);
+
+        assert_eq!(check_tree(&tree, &mut pt, &mut fm), (n..count).map(|n| key(n, 8)).collect::<Vec<_>>());
+    }
+
+
+
+    #[test]
+    fn freed_pages_are_reused(){
+        let (mut fm, mut pt, mut tree) = new_tree("b_tree_reuse");
+
+        fill(&mut tree, 200, 2000, 10, &mut pt, &mut fm);
+        let largest_page = pt.largest_page_map["b_tree_reuse"];
+
+        for n in 0..200{
+            tree.delete(&key(n, 2000), &mut pt, &mut fm).unwrap();
+        }
+
+        assert_eq!(depth(&tree, &mut pt, &mut fm), 1);
+        assert!(check_tree(&tree, &mut pt, &mut fm).is_empty());
 
+        //the pages freed by the merges are enough to build the tree again.
+        fill(&mut tree, 200, 2000, 10, &mut pt, &mut fm);
 
+        assert_eq!(pt.largest_page_map["b_tree_reuse"], largest_page);
+        assert_eq!(check_tree(&tree, &mut pt, &mut fm).len(), 200);
+    }
 }
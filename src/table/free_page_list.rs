@@ -0,0 +1,152 @@
+//Pages of a table file which are no longer used are kept in a linked list so they can be handed
+//out again before the file grows. The first free page is stored in the table header on page 0 and
+//every free page points to the next one through its next page meta data.
+
+use std::io::Error;
+
+use crate::file_manager::page::Page;
+use crate::file_manager::page::Page_type;
+use crate::file_manager::block::Block_ID;
+use crate::file_manager::file_manager::File_manager;
+use crate::buffer_pool::page_table::Page_table;
+use crate::table::table::FREE_PAGE_LIST_OFFSET;
+
+
+fn get_first_free_page(file_name: &str, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u32, std::io::Error>{
+    let page = match page_table.get_mut_page(Block_ID{file_name: file_name.to_string(), number: 0}, file_manager){
+        None    => return Err(Error::other("Table header page could not be loaded.")),
+        Some(p) => p,
+    };
+
+    let offset = FREE_PAGE_LIST_OFFSET as usize;
+    return Ok(u32::from_be_bytes(page.bytes[offset .. offset + 4].try_into().unwrap()))
+}
+
+
+
+fn set_first_free_page(file_name: &str, page_num: u32, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+    let block = Block_ID{file_name: file_name.to_string(), number: 0};
+
+    let page = match page_table.get_mut_page(block.clone(), file_manager){
+        None    => return Err(Error::other("Table header page could not be loaded.")),
+        Some(p) => p,
+    };
+
+    page.write(FREE_PAGE_LIST_OFFSET, page_num.to_be_bytes().to_vec())?;
+    page_table.set_dirty(&block);
+
+    return Ok(1)
+}
+
+
+
+//returns an empty page of the given type, reusing a free page if there is one.
+pub fn allocate_page(file_name: &str, page_type: Page_type, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u32, std::io::Error>{
+    let first_free_page = get_first_free_page(file_name, page_table, file_manager)?;
+
+    if first_free_page == 0{
+        return page_table.new_page(&file_name.to_string(), page_type, file_manager)
+    }
+
+    let block = Block_ID{file_name: file_name.to_string(), number: first_free_page};
+    let page_size = page_table.page_size;
+
+    let page = match page_table.get_mut_page(block.clone(), file_manager){
+        None    => return Err(Error::other("Free page could not be loaded.")),
+        Some(p) => p,
+    };
+
+    let next_free_page = page.next_index.unwrap_or(0);
+
+    *page = Page::new(page_size, first_free_page, page_type);
+    page_table.set_dirty(&block);
+
+    set_first_free_page(file_name, next_free_page, page_table, file_manager)?;
+
+    return Ok(first_free_page)
+}
+
+
+
+//clears a page and puts it at the front of the free page list.
+pub fn free_page(file_name: &str, page_num: u32, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+    if page_num == 0{
+        return Err(Error::other("The table header page can not be freed."))
+    }
+
+    let first_free_page = get_first_free_page(file_name, page_table, file_manager)?;
+
+    let block = Block_ID{file_name: file_name.to_string(), number: page_num};
+    let page_size = page_table.page_size;
+
+    let page = match page_table.get_mut_page(block.clone(), file_manager){
+        None    => return Err(Error::other("Page could not be loaded.")),
+        Some(p) => p,
+    };
+
+    *page = Page::new(page_size, page_num, Page_type::Free);
+
+    if first_free_page != 0{
+        page.set_next_page_num(first_free_page);
+    }
+
+    page_table.set_dirty(&block);
+
+    return set_first_free_page(file_name, page_num, page_table, file_manager)
+}
+
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::test_utils::open_test_table;
+
+
+    //the type of a page and the page after it.
+    fn page(file_name: &str, page_num: u32, page_table: &mut Page_table, file_manager: &mut File_manager) -> (Page_type, Option<u32>){
+        let page = page_table.get_mut_page(Block_ID{file_name: file_name.to_string(), number: page_num}, file_manager).unwrap();
+        return (page.page_type.clone(), page.next_index)
+    }
+
+
+
+    #[test]
+    fn freed_pages_are_allocated_again(){
+        let (mut fm, mut pt, _) = open_test_table("free_page_list");
+        let name = "free_page_list";
+
+        let first = allocate_page(name, Page_type::Data, &mut pt, &mut fm).unwrap();
+        let second = allocate_page(name, Page_type::Data, &mut pt, &mut fm).unwrap();
+        let third = allocate_page(name, Page_type::Data, &mut pt, &mut fm).unwrap();
+        assert!(first < second && second < third);
+
+        free_page(name, first, &mut pt, &mut fm).unwrap();
+        free_page(name, third, &mut pt, &mut fm).unwrap();
+
+        assert_eq!(get_first_free_page(name, &mut pt, &mut fm).unwrap(), third);
+        assert!(matches!(page(name, third, &mut pt, &mut fm).0, Page_type::Free));
+        assert_eq!(page(name, third, &mut pt, &mut fm).1, Some(first));
+
+        //the last freed page comes out first and has the requested type.
+        assert_eq!(allocate_page(name, Page_type::B_tree, &mut pt, &mut fm).unwrap(), third);
+        assert!(matches!(page(name, third, &mut pt, &mut fm).0, Page_type::B_tree));
+        assert_eq!(page(name, third, &mut pt, &mut fm).1, None);
+
+        assert_eq!(allocate_page(name, Page_type::Record, &mut pt, &mut fm).unwrap(), first);
+        assert_eq!(get_first_free_page(name, &mut pt, &mut fm).unwrap(), 0);
+
+        //with the list empty the file grows.
+        assert_eq!(allocate_page(name, Page_type::Data, &mut pt, &mut fm).unwrap(), third + 1);
+    }
+
+
+
+    #[test]
+    fn header_page_can_not_be_freed(){
+        let (mut fm, mut pt, _) = open_test_table("free_page_list_header");
+
+        assert!(free_page("free_page_list_header", 0, &mut pt, &mut fm).is_err());
+        assert_eq!(get_first_free_page("free_page_list_header", &mut pt, &mut fm).unwrap(), 0);
+    }
+}
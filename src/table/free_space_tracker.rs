@@ -0,0 +1,120 @@
+//Free space tracker pages list how many free bytes the pages of one kind have, e.g. all the record
+//pages of a table. Each entry is 6 bytes:
+//
+// 0 0 0 0 | 0 0
+// page num  free bytes
+//
+//When a tracker page is full another one is linked in through the next page meta data.
+
+use std::io::Error;
+
+use crate::file_manager::page::Page_type;
+use crate::file_manager::page::PAGE_HEADER_SIZE;
+use crate::file_manager::block::Block_ID;
+use crate::file_manager::file_manager::File_manager;
+use crate::buffer_pool::page_table::Page_table;
+use crate::table::free_page_list::allocate_page;
+
+
+const ENTRY_SIZE: u16 = 6;
+
+
+
+//returns every (page num, free bytes) entry in the tracker chain.
+pub fn read_entries(file_name: &str, tracker_page_num: u32, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<(u32, u16)>, std::io::Error>{
+    let mut entries = Vec::new();
+    let mut tracker_page_num = Some(tracker_page_num);
+
+    while let Some(number) = tracker_page_num{
+        let page = match page_table.get_mut_page(Block_ID{file_name: file_name.to_string(), number}, file_manager){
+            None    => return Err(Error::other("Free space tracker page could not be loaded.")),
+            Some(p) => p,
+        };
+
+        let mut index = PAGE_HEADER_SIZE;
+        while index + ENTRY_SIZE <= page.data_end_point{
+            let i = index as usize;
+            let page_num = u32::from_be_bytes(page.bytes[i .. i + 4].try_into().unwrap());
+            let free = u16::from_be_bytes(page.bytes[i + 4 .. i + 6].try_into().unwrap());
+            entries.push((page_num, free));
+            index += ENTRY_SIZE;
+        }
+
+        tracker_page_num = match page.next_index{
+            None | Some(0)  => None,
+            Some(n)         => Some(n),
+        };
+    }
+
+    return Ok(entries)
+}
+
+
+
+//updates the free bytes of a page, adding an entry for it if the page is not tracked yet.
+pub fn set_free_space(file_name: &str, tracker_page_num: u32, page_num: u32, free: u16, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+    let mut current = tracker_page_num;
+
+    loop{
+        let block = Block_ID{file_name: file_name.to_string(), number: current};
+
+        let page = match page_table.get_mut_page(block.clone(), file_manager){
+            None    => return Err(Error::other("Free space tracker page could not be loaded.")),
+            Some(p) => p,
+        };
+
+        let mut index = PAGE_HEADER_SIZE;
+        while index + ENTRY_SIZE <= page.data_end_point{
+            let i = index as usize;
+            if u32::from_be_bytes(page.bytes[i .. i + 4].try_into().unwrap()) == page_num{
+                page.write(index + 4, free.to_be_bytes().to_vec())?;
+                page_table.set_dirty(&block);
+                return Ok(1)
+            }
+            index += ENTRY_SIZE;
+        }
+
+        match page.next_index{
+            None | Some(0)  => break,
+            Some(n)         => current = n,
+        }
+    }
+
+    //the page is not tracked yet so the entry goes at the end of the last tracker page.
+    let mut entry = page_num.to_be_bytes().to_vec();
+    entry.extend_from_slice(&free.to_be_bytes());
+
+    let block = Block_ID{file_name: file_name.to_string(), number: current};
+
+    let page = match page_table.get_mut_page(block.clone(), file_manager){
+        None    => return Err(Error::other("Free space tracker page could not be loaded.")),
+        Some(p) => p,
+    };
+
+    if page.data_end_point + ENTRY_SIZE <= page.record_index_end_point{
+        page.write_at_end(entry);
+        page_table.set_dirty(&block);
+        return Ok(1)
+    }
+
+    let new_tracker_page_num = allocate_page(file_name, Page_type::Free_space_tracker, page_table, file_manager)?;
+
+    match page_table.get_mut_page(block.clone(), file_manager){
+        None    => return Err(Error::other("Free space tracker page could not be loaded.")),
+        Some(p) => p.set_next_page_num(new_tracker_page_num),
+    };
+    page_table.set_dirty(&block);
+
+    let new_block = Block_ID{file_name: file_name.to_string(), number: new_tracker_page_num};
+
+    let new_page = match page_table.get_mut_page(new_block.clone(), file_manager){
+        None    => return Err(Error::other("Free space tracker page could not be loaded.")),
+        Some(p) => p,
+    };
+
+    new_page.set_previous_page_num(current);
+    new_page.write_at_end(entry);
+    page_table.set_dirty(&new_block);
+
+    return Ok(1)
+}
@@ -1,2 +1,8 @@
+#[allow(clippy::module_inception)]
 pub mod table;
 pub mod variable_data_manager;
+pub mod B_tree_logic;
+pub mod free_page_list;
+pub mod free_space_tracker;
+pub mod table_scan;
+pub mod predicate;
@@ -0,0 +1,464 @@
+//Conditions on the values of a record, used by Table::select to filter records.
+//
+//A comparison with a null value is neither true nor false but unknown, like in SQL, so
+//Equal("a", x) and Not(Equal("a", x)) both leave out records where a is null. Only Is_null finds
+//them. Values are compared in the order of their B-tree keys, see Value::encode_key, so a
+//selection gives the same records whether it goes through an index or not. Floats are compared as
+//numbers, which is the order of their keys too, and only NaN is compared by its key.
+//
+//A whole number outside the range of an integer column, like 70000 for a U16 one, is not an
+//error. It compares as above or below every value of the column.
+
+use std::cmp::Ordering;
+
+use crate::table::table::Column;
+use crate::table::table::Data_type;
+use crate::table::table::Table_error;
+use crate::table::table::Value;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate{
+    Equal(String, Value),
+    Less(String, Value),
+    Greater(String, Value),
+    Between(String, Value, Value),      //both ends included.
+    In(String, Vec<Value>),
+    Is_null(String),
+    Like(String, String),               //% matches any text, _ any single character.
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+    Not(Box<Predicate>),
+}
+
+
+
+impl Predicate{
+
+    pub fn and(self, other: Predicate) -> Predicate{
+        return Predicate::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: Predicate) -> Predicate{
+        return Predicate::Or(Box::new(self), Box::new(other))
+    }
+
+    pub fn negate(self) -> Predicate{
+        return Predicate::Not(Box::new(self))
+    }
+
+
+
+    //checks that every column the predicate names is in the schema and converts the values it
+    //compares them with to the columns' types, see Value::convert.
+    pub fn prepare(&self, columns: &[Column]) -> Result<Predicate, Table_error>{
+        let data_type = |name: &String| -> Result<Data_type, Table_error> {
+            return match columns.iter().find(|column| column.column_name == *name){
+                None            => Err(Table_error::Column_not_found(name.clone())),
+                Some(column)    => Ok(column.data_type.clone()),
+            }
+        };
+
+        let convert = |name: &String, value: &Value| -> Result<Literal, Table_error> {
+            let data_type = data_type(name)?;
+
+            if let Some(v) = value.convert(&data_type){
+                return Ok(Literal::Value(v))
+            }
+
+            return match value.clamp(&data_type){
+                Some((Ordering::Less, min)) => Ok(Literal::Below(min)),
+                Some((_, max))              => Ok(Literal::Above(max)),
+                None                        => Err(Table_error::Type_mismatch{column_name: name.clone()}),
+            }
+        };
+
+        //comparisons that are false for every value of a column, or true, and unknown for null.
+        let never_below = |name: &String, min: Value| Predicate::Less(name.clone(), min);
+        let never_above = |name: &String, max: Value| Predicate::Greater(name.clone(), max);
+
+        return Ok(match self{
+            Predicate::Equal(name, value)           => match convert(name, value)?{
+                Literal::Value(v)   => Predicate::Equal(name.clone(), v),
+                Literal::Below(min) => never_below(name, min),
+                Literal::Above(max) => never_above(name, max),
+            },
+
+            Predicate::Less(name, value)            => match convert(name, value)?{
+                Literal::Value(v)   => Predicate::Less(name.clone(), v),
+                Literal::Below(min) => never_below(name, min),
+                Literal::Above(max) => never_above(name, max).negate(),
+            },
+
+            Predicate::Greater(name, value)         => match convert(name, value)?{
+                Literal::Value(v)   => Predicate::Greater(name.clone(), v),
+                Literal::Below(min) => never_below(name, min).negate(),
+                Literal::Above(max) => never_above(name, max),
+            },
+
+            //both ends are included, so an end outside the range can be moved to its edge.
+            Predicate::Between(name, low, high)     => match (convert(name, low)?, convert(name, high)?){
+                (Literal::Above(max), _)    => never_above(name, max),
+                (_, Literal::Below(min))    => never_below(name, min),
+                (low, high)                 => Predicate::Between(name.clone(), low.value(), high.value()),
+            },
+
+            //values outside the range can never be equal to the column's. Without any other values
+            //the list stays unknown for null, not false like an empty one.
+            Predicate::In(name, values)             => {
+                let mut converted = Vec::with_capacity(values.len());
+                let mut never = None;
+
+                for value in values{
+                    match convert(name, value)?{
+                        Literal::Value(v)   => converted.push(v),
+                        Literal::Below(min) => never = Some(never_below(name, min)),
+                        Literal::Above(max) => never = Some(never_above(name, max)),
+                    }
+                }
+
+                match never{
+                    Some(predicate) if converted.is_empty() => predicate,
+                    _                                       => Predicate::In(name.clone(), converted),
+                }
+            },
+
+            Predicate::Is_null(name)                => {
+                data_type(name)?;
+                Predicate::Is_null(name.clone())
+            },
+
+            Predicate::Like(name, pattern)          => {
+                if !matches!(data_type(name)?, Data_type::String | Data_type::Enum){
+                    return Err(Table_error::Type_mismatch{column_name: name.clone()})
+                }
+                Predicate::Like(name.clone(), pattern.clone())
+            },
+
+            Predicate::And(left, right)             => left.prepare(columns)?.and(right.prepare(columns)?),
+            Predicate::Or(left, right)              => left.prepare(columns)?.or(right.prepare(columns)?),
+            Predicate::Not(predicate)               => predicate.prepare(columns)?.negate(),
+        })
+    }
+
+
+
+    //whether a record with the columns of a schema matches, None if that is unknown because of a
+    //null value. The predicate has to be prepared for the schema.
+    pub fn evaluate(&self, columns: &[Column], record: &[Value]) -> Option<bool>{
+        let value = |name: &String| -> &Value {
+            return match columns.iter().position(|column| column.column_name == *name){
+                None    => &Value::Null,
+                Some(i) => &record[i],
+            }
+        };
+
+        //the ordering of a column's value against another value, None if either is null.
+        let compare = |name: &String, other: &Value| -> Option<Ordering> {
+            let value = value(name);
+
+            if *value == Value::Null || *other == Value::Null{
+                return None
+            }
+
+            return Some(match (value, other){
+                (Value::Float(a), Value::Float(b))  => match a.partial_cmp(b){
+                    None            => value.encode_key().cmp(&other.encode_key()),
+                    Some(ordering)  => ordering,
+                },
+                _                                   => value.encode_key().cmp(&other.encode_key()),
+            })
+        };
+
+        return match self{
+            Predicate::Equal(name, other)           => Some(compare(name, other)? == Ordering::Equal),
+            Predicate::Less(name, other)            => Some(compare(name, other)? == Ordering::Less),
+            Predicate::Greater(name, other)         => Some(compare(name, other)? == Ordering::Greater),
+            Predicate::Between(name, low, high)     => Some(compare(name, low)? != Ordering::Less && compare(name, high)? != Ordering::Greater),
+
+            //a value that is not in the list is unknown if the list holds a null.
+            Predicate::In(name, values)             => {
+                let mut result = Some(false);
+
+                for other in values{
+                    match compare(name, other){
+                        Some(Ordering::Equal)   => return Some(true),
+                        Some(_)                 => (),
+                        None                    => result = None,
+                    }
+                }
+
+                result
+            },
+
+            Predicate::Is_null(name)                => Some(*value(name) == Value::Null),
+
+            Predicate::Like(name, pattern)          => match value(name){
+                Value::String(text)
+                | Value::Enum(text)     => Some(like(text, pattern)),
+                _                       => None,
+            },
+
+            Predicate::And(left, right)             => match (left.evaluate(columns, record), right.evaluate(columns, record)){
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true))            => Some(true),
+                _                                   => None,
+            },
+
+            Predicate::Or(left, right)              => match (left.evaluate(columns, record), right.evaluate(columns, record)){
+                (Some(true), _) | (_, Some(true))   => Some(true),
+                (Some(false), Some(false))          => Some(false),
+                _                                   => None,
+            },
+
+            Predicate::Not(predicate)               => predicate.evaluate(columns, record).map(|result| !result),
+        }
+    }
+}
+
+
+
+//a value a predicate compares a column with, as the column's type.
+enum Literal{
+    Value(Value),
+    Below(Value),       //below the range of the type, with its smallest value.
+    Above(Value),       //above the range of the type, with its largest value.
+}
+
+
+
+impl Literal{
+
+    fn value(self) -> Value{
+        return match self{
+            Literal::Value(v)
+            | Literal::Below(v)
+            | Literal::Above(v) => v,
+        }
+    }
+}
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Like_token{
+    Character(char),
+    Any_character,      //_
+    Any_text,           //%
+}
+
+
+
+//the tokens of a LIKE pattern. A backslash makes the character after it match only itself, so \%
+//and \_ match a % and an _.
+fn like_tokens(pattern: &str) -> Vec<Like_token>{
+    let mut tokens = Vec::new();
+    let mut characters = pattern.chars();
+
+    while let Some(c) = characters.next(){
+        tokens.push(match c{
+            '%'     => Like_token::Any_text,
+            '_'     => Like_token::Any_character,
+            '\\'    => Like_token::Character(characters.next().unwrap_or('\\')),
+            _       => Like_token::Character(c),
+        });
+    }
+
+    return tokens
+}
+
+
+
+//whether text matches a LIKE pattern. A % that is followed by a mismatch goes back to take one
+//more character, which is enough as a later % can take over any text an earlier one would.
+fn like(text: &str, pattern: &str) -> bool{
+    let text: Vec<char> = text.chars().collect();
+    let pattern = like_tokens(pattern);
+
+    let mut t = 0;
+    let mut p = 0;
+    //the position after the last % and the text position it was tried at.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len(){
+        match pattern.get(p){
+            Some(Like_token::Any_character)                 => { t += 1; p += 1; },
+            Some(Like_token::Character(c)) if *c == text[t] => { t += 1; p += 1; },
+
+            Some(Like_token::Any_text)                      => {
+                p += 1;
+                backtrack = Some((p, t));
+            },
+
+            _                                               => match backtrack{
+                None                        => return false,
+                Some((after_percent, tried))=> {
+                    p = after_percent;
+                    t = tried + 1;
+                    backtrack = Some((after_percent, t));
+                },
+            },
+        }
+    }
+
+    return pattern[p ..].iter().all(|token| *token == Like_token::Any_text)
+}
+
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+
+    fn columns() -> Vec<Column>{
+        return vec![
+            Column::new("n".to_string(), Data_type::U16),
+            Column::new("f".to_string(), Data_type::Float),
+            Column::new("s".to_string(), Data_type::String),
+        ]
+    }
+
+    fn equal(name: &str, value: Value) -> Predicate{
+        return Predicate::Equal(name.to_string(), value)
+    }
+
+    //a predicate that is true, false or unknown for any record.
+    fn constant(result: Option<bool>) -> Predicate{
+        return match result{
+            Some(true)  => Predicate::Is_null("s".to_string()),
+            Some(false) => Predicate::Is_null("s".to_string()).negate(),
+            None        => equal("n", Value::Null),
+        }
+    }
+
+    //the result of a predicate for a record, prepared like Table::select does.
+    fn evaluate(predicate: Predicate, record: &[Value]) -> Option<bool>{
+        let columns = columns();
+        return predicate.prepare(&columns).unwrap().evaluate(&columns, record)
+    }
+
+
+
+    #[test]
+    fn like_patterns(){
+        let cases = [
+            ("abc", "abc", true),
+            ("abc", "ab", false),
+            ("abc", "a%", true),
+            ("abc", "%c", true),
+            ("abc", "%b%", true),
+            ("abc", "%d%", false),
+            ("abc", "a_c", true),
+            ("abc", "a_", false),
+            ("", "%", true),
+            ("", "_", false),
+            ("aXbXc", "a%b%c", true),
+            ("abcbd", "a%b_", true),
+            ("abab", "%ab", true),
+            ("50%", "50\\%", true),
+            ("500", "50\\%", false),
+            ("a_b", "a\\_b", true),
+            ("axb", "a\\_b", false),
+        ];
+
+        for (text, pattern, expected) in cases{
+            assert_eq!(like(text, pattern), expected, "{:?} LIKE {:?}", text, pattern);
+        }
+    }
+
+
+
+    #[test]
+    fn and_or_not_are_three_valued(){
+        let record = [Value::U16(1), Value::Float(0.0), Value::Null];
+        let results = [Some(true), Some(false), None];
+
+        for left in results{
+            for right in results{
+                let and = match (left, right){
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true))            => Some(true),
+                    _                                   => None,
+                };
+
+                let or = match (left, right){
+                    (Some(true), _) | (_, Some(true))   => Some(true),
+                    (Some(false), Some(false))          => Some(false),
+                    _                                   => None,
+                };
+
+                assert_eq!(evaluate(constant(left).and(constant(right)), &record), and, "{:?} AND {:?}", left, right);
+                assert_eq!(evaluate(constant(left).or(constant(right)), &record), or, "{:?} OR {:?}", left, right);
+            }
+
+            assert_eq!(evaluate(constant(left).negate(), &record), left.map(|result| !result), "NOT {:?}", left);
+        }
+    }
+
+
+
+    #[test]
+    fn in_with_null(){
+        let with_null = Predicate::In("n".to_string(), vec![Value::U16(1), Value::Null]);
+
+        assert_eq!(evaluate(with_null.clone(), &[Value::U16(1), Value::Null, Value::Null]), Some(true));
+        assert_eq!(evaluate(with_null.clone(), &[Value::U16(2), Value::Null, Value::Null]), None);
+        assert_eq!(evaluate(with_null.clone().negate(), &[Value::U16(2), Value::Null, Value::Null]), None);
+
+        let without_null = Predicate::In("n".to_string(), vec![Value::U16(1)]);
+
+        assert_eq!(evaluate(without_null.clone(), &[Value::U16(2), Value::Null, Value::Null]), Some(false));
+        assert_eq!(evaluate(without_null, &[Value::Null, Value::Null, Value::Null]), None);
+    }
+
+
+
+    #[test]
+    fn out_of_range_numbers_compare_with_every_value(){
+        let n = |predicate: fn(String, Value) -> Predicate, value: i64| predicate("n".to_string(), Value::Int(value));
+
+        for record in [0, 1, u16::MAX]{
+            let record = [Value::U16(record), Value::Null, Value::Null];
+
+            assert_eq!(evaluate(n(Predicate::Less, 70000), &record), Some(true));
+            assert_eq!(evaluate(n(Predicate::Greater, 70000), &record), Some(false));
+            assert_eq!(evaluate(n(Predicate::Equal, 70000), &record), Some(false));
+            assert_eq!(evaluate(n(Predicate::Less, -1), &record), Some(false));
+            assert_eq!(evaluate(n(Predicate::Greater, -1), &record), Some(true));
+            assert_eq!(evaluate(n(Predicate::Equal, -1), &record), Some(false));
+
+            let between = Predicate::Between("n".to_string(), Value::Int(-5), Value::Int(70000));
+            assert_eq!(evaluate(between, &record), Some(true));
+
+            let between = Predicate::Between("n".to_string(), Value::Int(70000), Value::Int(80000));
+            assert_eq!(evaluate(between, &record), Some(false));
+        }
+
+        //a comparison with null stays unknown.
+        let null = [Value::Null, Value::Null, Value::Null];
+        assert_eq!(evaluate(n(Predicate::Less, 70000), &null), None);
+        assert_eq!(evaluate(n(Predicate::Greater, -1), &null), None);
+        assert_eq!(evaluate(Predicate::In("n".to_string(), vec![Value::Int(70000)]), &null), None);
+
+        let record = [Value::U16(3), Value::Null, Value::Null];
+        assert_eq!(evaluate(Predicate::In("n".to_string(), vec![Value::Int(70000), Value::Int(3)]), &record), Some(true));
+        assert_eq!(evaluate(Predicate::In("n".to_string(), vec![Value::Int(-1), Value::Int(70000)]), &record), Some(false));
+
+        //a number that is not whole is still the wrong type.
+        assert!(matches!(Predicate::Less("n".to_string(), Value::Float(1.5)).prepare(&columns()), Err(Table_error::Type_mismatch{..})));
+    }
+
+
+
+    #[test]
+    fn floats_compare_as_numbers(){
+        let record = [Value::Null, Value::Float(-0.0), Value::Null];
+
+        assert_eq!(evaluate(equal("f", Value::Float(0.0)), &record), Some(true));
+        assert_eq!(evaluate(Predicate::Less("f".to_string(), Value::Float(0.0)), &record), Some(false));
+        assert_eq!(evaluate(Predicate::Between("f".to_string(), Value::Float(0.0), Value::Float(1.0)), &record), Some(true));
+        assert_eq!(evaluate(equal("f", Value::Int(0)), &[Value::Null, Value::Float(0.0), Value::Null]), Some(true));
+        assert_eq!(evaluate(Predicate::Greater("f".to_string(), Value::Float(f64::INFINITY)), &[Value::Null, Value::Float(f64::NAN), Value::Null]), Some(true));
+    }
+}
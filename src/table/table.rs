@@ -1,25 +1,424 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::ops::Bound;
+
 use crate::file_manager::page::Page;
 use crate::file_manager::page::Page_type;
+use crate::file_manager::page::PAGE_HEADER_SIZE;
 use crate::file_manager::block::Block_ID;
 use crate::file_manager::file_manager::File_manager;
 use crate::buffer_pool::page_table::Page_table;
+use crate::table::variable_data_manager::Variable_data_manager;
+use crate::table::variable_data_manager::Page_free;
+use crate::table::B_tree_logic::B_tree;
+use crate::table::free_page_list::{allocate_page, free_page};
+use crate::table::free_space_tracker::read_entries;
+use crate::table::free_space_tracker::set_free_space;
+use crate::table::table_scan::Table_scan;
+use crate::table::predicate::Predicate;
+
+
+//Table header, stored at the start of page 0's data section before the columns:
+//
+// 0 0 0 0 | 0 0 0 0 |
+//   first     first variable
+// free page   data index page
+//
+// 0 0 0 0 | 0 0 0 0 | 0 0 0 0 | 0 0 0 0 | 0 0 0 0 | 0 0 0 0
+//   first     B-tree    record     first     data     schema
+//  record      root      free      data      free     version
+//   page                space      page      space
+//                      tracker              tracker
+//
+// 0 0 0 0 0 0 0 0
+//  last generated
+//   primary key
+//
+pub const FREE_PAGE_LIST_OFFSET: u16 = PAGE_HEADER_SIZE;
+pub const VARIABLE_DATA_INDEX_OFFSET: u16 = 21;
+pub const FIRST_RECORD_PAGE_OFFSET: u16 = 25;
+pub const B_TREE_ROOT_OFFSET: u16 = 29;
+pub const RECORD_FREE_SPACE_TRACKER_OFFSET: u16 = 33;
+pub const FIRST_DATA_PAGE_OFFSET: u16 = 37;
+pub const DATA_FREE_SPACE_TRACKER_OFFSET: u16 = 41;
+pub const SCHEMA_VERSION_OFFSET: u16 = 45;
+pub const LAST_KEY_OFFSET: u16 = 49;
+pub const TABLE_HEADER_END: u16 = 57;
+
+
+//Record layout in a Record page:
+//
+// 0 | 0 0 | 0 0 0 0 | 0 ... | values ...
+// |    |       |         |
+// |    |       |      null bitmap, one bit per column
+// |    |  schema version
+// |  record size (including the header)
+//flags
+//
+//A record holds the columns that existed in the schema version it was written with, see
+//Column::exists_in. Bit i % 8 of bitmap byte i / 8 is set when the i-th of those columns is null.
+//Null values take up no bytes in the record and nothing in the data pages. The other values are
+//stored in column order. Fixed width values are stored big endian, strings, enums and blobs are
+//stored in the data pages and the record holds their 4 byte variable data ID.
+//Every record has an entry in the page's record index.
+//
+//A record that grew too large for its page is moved to another page. In its old place a forwarding
+//record is left which holds the 4 byte page num and 2 byte slot of the moved record, so the
+//record's Record_ID stays the same.
+pub const RECORD_HEADER_SIZE: u16 = 7;
+
+pub const RECORD_FORWARDED: u8 = 1;     //the record only points to where it was moved to
+pub const RECORD_MOVED: u8 = 2;         //the record is reached through a forwarding record
+
+//flags, size, page num and slot. Every record is at least as large as a forwarding record so it
+//can always be replaced by one.
+pub const FORWARDING_RECORD_SIZE: u16 = 9;
+
+//where the value of a column is inside the bytes of a record, None if the record has no value.
+type Field_range = Option<(usize, usize)>;
+
+
+//names a single record: the record page it is stored in and its entry in that page's record index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Record_ID{
+    pub page_num:   u32,
+    pub slot:       u16,
+}
+
+impl Record_ID{
+
+    //4 byte page num and 2 byte slot, as stored in the B-tree.
+    pub fn to_bytes(self) -> Vec<u8>{
+        let mut bytes = self.page_num.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&self.slot.to_be_bytes());
+        return bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Record_ID>{
+        return Some(Record_ID{
+            page_num:   u32::from_be_bytes(bytes.get(0 .. 4)?.try_into().ok()?),
+            slot:       u16::from_be_bytes(bytes.get(4 .. 6)?.try_into().ok()?),
+        })
+    }
+}
+
+
+//what add_record stored: the record and the values of its primary key, which were generated if
+//they were left null. The key is empty if the table has no primary key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Added_record{
+    pub record_id:  Record_ID,
+    pub key:        Vec<Value>,
+}
+
+
+//the larger data written for the new bytes of a record and the data its old bytes referred to.
+//Which of them is removed depends on whether the new bytes could be written, see
+//settle_data_changes.
+#[derive(Debug, Default)]
+struct Data_changes{
+    added:      Vec<u32>,
+    replaced:   Vec<u32>,
+}
+
+
+#[derive(Debug)]
+pub enum Table_error{
+    Io(std::io::Error),
+    Column_count_mismatch{expected: usize, found: usize},
+    Type_mismatch{column_name: String},
+    Record_too_large{size: usize},
+    Record_not_found(Record_ID),
+    Corrupt_record(Record_ID),
+    Column_not_found(String),
+    Invalid_table_file(String),
+    Column_exists(String),
+    Invalid_column_name(String),
+    Invalid_default(String),
+    Not_null_violation(String),
+    Unique_violation(String),
+    Incompatible_type_change{column_name: String},
+    Primary_key_exists(String),
+    Index_exists(String),
+    Index_not_found(String),
+    //the index is the only one a unique column can be looked up through.
+    Index_required(String),
+    Key_overflow(String),
+}
+
+impl From<std::io::Error> for Table_error{
+    fn from(error: std::io::Error) -> Self{
+        Table_error::Io(error)
+    }
+}
 
 
 #[derive(Debug)]
 pub struct Table{
     pub table_name:     String,
+    //the current columns.
     pub column_schema:  Vec<Column>,
+    //every schema entry, including the columns that were dropped but may still be in old records.
+    pub column_history: Vec<Column>,
+    pub schema_version: u32,
+    pub indexes:        Vec<Index>,
 
     pub first_record_page_num:  u32,
     pub b_tree_page_num:        u32,
+    pub last_key:               u64,
     pub record_free_space_tracker_page_num:     u32,
+    //the free bytes of every record page in the record free space tracker, largest first.
+    pub record_free_bytes:                      BinaryHeap<Page_free>,
+    pub first_data_page_num:                    u32,
+    pub data_free_space_tracker_page_num:       u32,
+
+    pub variable_data_manager:                  Variable_data_manager,
     
 }
 #[derive(Debug, Clone)]
 pub struct Column{
-    pub column_name:    String,
-    pub data_type:      Data_type,
+    pub column_name:        String,
+    pub data_type:          Data_type,
+    pub not_null:           bool,
+    pub default:            Option<Value>,
+    pub unique:             bool,
+    //a primary key column is also not null. Several columns together can be the primary key, which
+    //is unique as a whole. Records can be looked up by it through the table's B-tree.
+    pub primary_key:        bool,
+    //the table whose primary key the values of the column refer to. Foreign keys are enforced by
+    //the Database the tables are in.
+    pub references:         Option<Foreign_key>,
+    //the schema version the column was added in, and the one it was dropped in (0 while it is
+    //part of the table).
+    pub added_version:      u32,
+    pub dropped_version:    u32,
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Foreign_key{
+    pub table_name:     String,
+    pub on_delete:      On_delete,
+}
+
+
+//what happens to the records referring to a record that is removed.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum On_delete{
+    Restrict = 0,       //the record cannot be removed while it is referred to.
+    Cascade = 1,        //the referring records are removed as well.
+    Set_null = 2,       //the referring columns are set to null.
+}
+
+
+//a B-tree over the values of one or more columns. Its keys are the values of a record encoded
+//with Value::encode_key, one after another in column order, followed by the record's Record_ID.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Index{
+    pub column_names:   Vec<String>,
+    pub root_page_num:  u32,
+}
+
+
+//Indexes are stored as schema entries after the columns:
+//
+// 0 | 0 0 0 0 | 0 | 0 | column name ... | 0 | column name ...
+// |     |       |   |
+// |     |       | column name size
+// |     | column count
+// | root page
+//INDEX_ENTRY
+pub const INDEX_ENTRY: u8 = 255;
+
+//first byte of a value in a B-tree key, see Value::encode_key.
+pub const KEY_NULL: u8 = 0;
+pub const KEY_VALUE: u8 = 1;
+
+impl Index{
+
+    pub fn to_bytes(&self) -> Vec<u8>{
+        let mut bytes = vec![INDEX_ENTRY];
+        bytes.extend_from_slice(&self.root_page_num.to_be_bytes());
+        bytes.push(self.column_names.len() as u8);
+
+        for column_name in self.column_names.iter(){
+            bytes.push(column_name.len() as u8);
+            bytes.extend_from_slice(column_name.as_bytes());
+        }
+
+        return bytes
+    }
+
+
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Index>{
+        if *bytes.first()? != INDEX_ENTRY{
+            return None
+        }
+
+        let root_page_num = u32::from_be_bytes(bytes.get(1 .. 5)?.try_into().ok()?);
+        let column_count = *bytes.get(5)? as usize;
+
+        let mut column_names = Vec::with_capacity(column_count);
+        let mut position = 6;
+
+        for _ in 0..column_count{
+            let name_size = *bytes.get(position)? as usize;
+            let column_name = std::str::from_utf8(bytes.get(position + 1 .. position + 1 + name_size)?).ok()?;
+            column_names.push(column_name.to_string());
+            position += 1 + name_size;
+        }
+
+        return Some(Index{column_names, root_page_num})
+    }
+}
+
+
+//Columns are stored in the schema, a chain of Table_structure pages starting with page 0. Every
+//column is one entry in a page's record index, in column order:
+//
+// 0 | 0 | name ... | 0 | 0 0 0 0 | 0 0 0 0 | 0 0 | default ... | 0 | table name ... | 0
+// type  name size   flags   added     dropped  default size    table name size    on delete
+//                          version    version
+//
+//The default size and default are only there if the COLUMN_HAS_DEFAULT flag is set, and the
+//referenced table and what to do on delete only if the COLUMN_REFERENCES flag is set.
+pub const COLUMN_NOT_NULL: u8 = 1;
+pub const COLUMN_UNIQUE: u8 = 2;
+pub const COLUMN_HAS_DEFAULT: u8 = 4;
+pub const COLUMN_PRIMARY_KEY: u8 = 8;
+pub const COLUMN_REFERENCES: u8 = 16;
+
+//largest default value a schema entry can hold.
+pub const MAX_DEFAULT_SIZE: usize = 4096;
+
+impl Column{
+
+    //a column without constraints.
+    pub fn new(column_name: String, data_type: Data_type) -> Column{
+        return Column{
+            column_name,
+            data_type,
+            not_null:           false,
+            default:            None,
+            unique:             false,
+            primary_key:        false,
+            references:         None,
+            added_version:      0,
+            dropped_version:    0,
+        }
+    }
+
+
+
+    //an Int or U32 primary key is given the next number of the table's sequence when it is left
+    //null, as long as it is the only column of the key.
+    pub fn is_auto_increment(&self) -> bool{
+        return self.primary_key && matches!(self.data_type, Data_type::Int | Data_type::U32)
+    }
+
+
+
+    //whether records written with a schema version hold a value for the column.
+    pub fn exists_in(&self, version: u32) -> bool{
+        return self.added_version <= version && (self.dropped_version == 0 || version < self.dropped_version)
+    }
+
+
+
+    pub fn to_bytes(&self) -> Vec<u8>{
+        let mut bytes = vec![self.data_type.clone() as u8, self.column_name.len() as u8];
+        bytes.extend_from_slice(self.column_name.as_bytes());
+
+        let mut flags = 0;
+        if self.not_null        { flags |= COLUMN_NOT_NULL; }
+        if self.unique          { flags |= COLUMN_UNIQUE; }
+        if self.primary_key     { flags |= COLUMN_PRIMARY_KEY; }
+        if self.default.is_some(){ flags |= COLUMN_HAS_DEFAULT; }
+        if self.references.is_some(){ flags |= COLUMN_REFERENCES; }
+        bytes.push(flags);
+        bytes.extend_from_slice(&self.added_version.to_be_bytes());
+        bytes.extend_from_slice(&self.dropped_version.to_be_bytes());
+
+        if let Some(default) = &self.default{
+            let default_bytes = default.to_bytes();
+            bytes.extend_from_slice(&(default_bytes.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(&default_bytes);
+        }
+
+        if let Some(foreign_key) = &self.references{
+            bytes.push(foreign_key.table_name.len() as u8);
+            bytes.extend_from_slice(foreign_key.table_name.as_bytes());
+            bytes.push(foreign_key.on_delete as u8);
+        }
+
+        return bytes
+    }
+
+
+
+    //reads a column from the start of the bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Column>{
+        let data_type = Data_type::try_from(*bytes.first()?).ok()?;
+        let name_size = *bytes.get(1)? as usize;
+        let name = std::str::from_utf8(bytes.get(2 .. 2 + name_size)?).ok()?;
+        let flags = *bytes.get(2 + name_size)?;
+
+        let mut index = 3 + name_size;
+        let added_version = u32::from_be_bytes(bytes.get(index .. index + 4)?.try_into().ok()?);
+        let dropped_version = u32::from_be_bytes(bytes.get(index + 4 .. index + 8)?.try_into().ok()?);
+        index += 8;
+
+        let default = if flags & COLUMN_HAS_DEFAULT != 0{
+            let default_size = u16::from_be_bytes(bytes.get(index .. index + 2)?.try_into().ok()?) as usize;
+            index += 2;
+            let default = Value::from_bytes(&data_type, bytes.get(index .. index + default_size)?)?;
+            index += default_size;
+            Some(default)
+        }else{
+            None
+        };
+
+        let references = if flags & COLUMN_REFERENCES != 0{
+            let table_name_size = *bytes.get(index)? as usize;
+            let table_name = std::str::from_utf8(bytes.get(index + 1 .. index + 1 + table_name_size)?).ok()?;
+            let on_delete = match *bytes.get(index + 1 + table_name_size)?{
+                0   => On_delete::Restrict,
+                1   => On_delete::Cascade,
+                2   => On_delete::Set_null,
+                _   => return None,
+            };
+            Some(Foreign_key{table_name: table_name.to_string(), on_delete})
+        }else{
+            None
+        };
+
+        return Some(Column{
+            column_name:    name.to_string(),
+            data_type,
+            not_null:       flags & COLUMN_NOT_NULL != 0,
+            default,
+            unique:         flags & COLUMN_UNIQUE != 0,
+            primary_key:    flags & COLUMN_PRIMARY_KEY != 0,
+            references,
+            added_version,
+            dropped_version,
+        })
+    }
+}
+
+
+
+//column names have to fit in the single byte name size of a schema entry.
+fn check_column_name(name: &str) -> Result<(), Table_error>{
+    if name.is_empty() || name.len() > 255{
+        return Err(Table_error::Invalid_column_name(name.to_string()))
+    }
 
+    return Ok(())
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -35,510 +434,3463 @@ pub enum Value{
     Blob(Vec<u8>),
     U32(u32),
     U16(u16),
+    Null,
+}
+
+
+
+impl PartialEq<Data_type> for Value {
+    fn eq(&self, other: &Data_type) -> bool {
+        match self {
+            Value::Int(_)      => *other == Data_type::Int,
+            Value::Float(_)    => *other == Data_type::Float,
+            Value::String(_)   => *other == Data_type::String,
+            Value::Datetime(_) => *other == Data_type::Datetime,
+            Value::Date(_)     => *other == Data_type::Date,
+            Value::Time(_)     => *other == Data_type::Time,
+            Value::Bool(_)     => *other == Data_type::Bool,
+            Value::Enum(_)     => *other == Data_type::Enum,
+            Value::Blob(_)     => *other == Data_type::Blob,
+            Value::U32(_)      => *other == Data_type::U32,
+            Value::U16(_)      => *other == Data_type::U16,
+            Value::Null        => false,
+        }
+    }
+}
+
+impl Value{
+
+    //number of bytes the value takes up in a record.
+    fn size(&self) -> u8{
+
+        return match self{
+            Value::Int(_)
+            | Value::Float(_)
+            | Value::Datetime(_) => 8,
+
+            Value::String(_) 
+            | Value::Enum(_) 
+            | Value::Blob(_)
+            | Value::Date(_) 
+            | Value::Time(_)
+            | Value::U32(_)   => 4,
+
+            Value::U16(_)  => 2,
+
+            Value::Bool(_) => 1,
+
+            Value::Null    => 0,
+        }
+    }
+
+
+
+    //big endian bytes of a fixed width value, or the bytes of the string or blob itself.
+    pub fn to_bytes(&self) -> Vec<u8>{
+
+        return match self{
+            Value::Int(n)       => n.to_be_bytes().to_vec(),
+            Value::Float(n)     => n.to_be_bytes().to_vec(),
+            Value::Datetime(n)  => n.to_be_bytes().to_vec(),
+            Value::Date(n)
+            | Value::Time(n)
+            | Value::U32(n)     => n.to_be_bytes().to_vec(),
+            Value::U16(n)       => n.to_be_bytes().to_vec(),
+            Value::Bool(b)      => vec![*b as u8],
+
+            Value::String(s)
+            | Value::Enum(s)    => s.as_bytes().to_vec(),
+            Value::Blob(b)      => b.clone(),
+
+            Value::Null         => Vec::new(),
+        }
+    }
+
+
+
+    //bytes of the value in a B-tree key, which sort byte by byte in the same order as the values.
+    //A key over several columns is their encoded values one after another, so a key sorts by its
+    //first value, then its second, and so on. Every value starts with KEY_NULL or KEY_VALUE, so
+    //nulls sort first. Ints have their sign bit flipped, negative floats all their bits and other
+    //floats their sign bit, which sorts them like f64::total_cmp, except that -0.0 is written as
+    //0.0 so the two are found by the same lookup. Strings, enums and blobs end with 0 0 and have
+    //every 0 byte in them written as 0 255, so a shorter value sorts before a longer one it is the
+    //start of. Other types are big endian already.
+    pub fn encode_key(&self) -> Vec<u8>{
+        let mut bytes = vec![KEY_VALUE];
+
+        match self{
+            Value::Null         => return vec![KEY_NULL],
+
+            Value::Int(n)       => bytes.extend_from_slice(&((*n as u64) ^ (1 << 63)).to_be_bytes()),
+
+            Value::Float(f)     => {
+                //-0.0 and 0.0 are equal, so they have the same key.
+                let f = if *f == 0.0 { 0.0 } else { *f };
+                let bits = f.to_bits();
+                let bits = if bits >> 63 == 1 { !bits } else { bits ^ (1 << 63) };
+                bytes.extend_from_slice(&bits.to_be_bytes());
+            },
+
+            Value::String(_)
+            | Value::Enum(_)
+            | Value::Blob(_)    => {
+                for byte in self.to_bytes(){
+                    bytes.push(byte);
+                    if byte == 0{
+                        bytes.push(255);
+                    }
+                }
+                bytes.extend_from_slice(&[0, 0]);
+            },
+
+            _                   => bytes.extend_from_slice(&self.to_bytes()),
+        }
+
+        return bytes
+    }
+
+
+
+    //opposite of encode_key for a value of a type at the start of a key, along with the number of
+    //bytes it took up, so the next value of a key over several columns starts after them. None if
+    //the bytes do not start with a value of the type.
+    pub fn decode_key(data_type: &Data_type, bytes: &[u8]) -> Option<(Value, usize)>{
+        match *bytes.first()?{
+            KEY_NULL    => return Some((Value::Null, 1)),
+            KEY_VALUE   => {},
+            _           => return None,
+        }
+
+        let bytes = &bytes[1 ..];
+
+        return match data_type{
+            Data_type::Int      => {
+                let n = u64::from_be_bytes(bytes.get(.. 8)?.try_into().ok()?);
+                Some((Value::Int((n ^ (1 << 63)) as i64), 9))
+            },
+
+            Data_type::Float    => {
+                let bits = u64::from_be_bytes(bytes.get(.. 8)?.try_into().ok()?);
+                let bits = if bits >> 63 == 1 { bits ^ (1 << 63) } else { !bits };
+                Some((Value::Float(f64::from_bits(bits)), 9))
+            },
+
+            Data_type::String
+            | Data_type::Enum
+            | Data_type::Blob   => {
+                let mut value = Vec::new();
+                let mut i = 0;
+
+                loop{
+                    match (*bytes.get(i)?, bytes.get(i + 1)){
+                        (0, Some(0))    => break,
+                        (0, Some(255))  => { value.push(0); i += 2; },
+                        (0, _)          => return None,
+                        (byte, _)       => { value.push(byte); i += 1; },
+                    }
+                }
+
+                Some((Value::from_bytes(data_type, &value)?, i + 3))
+            },
+
+            _                   => {
+                let size = data_type.size() as usize;
+                Some((Value::from_bytes(data_type, bytes.get(.. size)?)?, size + 1))
+            },
+        }
+    }
+
+
+
+    //opposite of to_bytes, None if there are not enough bytes for the type.
+    pub fn from_bytes(data_type: &Data_type, bytes: &[u8]) -> Option<Value>{
+        let size = data_type.size() as usize;
+
+        if !data_type.is_variable_size() && bytes.len() < size{
+            return None
+        }
+
+        return match data_type{
+            Data_type::Int      => Some(Value::Int(i64::from_be_bytes(bytes[..8].try_into().ok()?))),
+            Data_type::Float    => Some(Value::Float(f64::from_be_bytes(bytes[..8].try_into().ok()?))),
+            Data_type::Datetime => Some(Value::Datetime(u64::from_be_bytes(bytes[..8].try_into().ok()?))),
+            Data_type::Date     => Some(Value::Date(u32::from_be_bytes(bytes[..4].try_into().ok()?))),
+            Data_type::Time     => Some(Value::Time(u32::from_be_bytes(bytes[..4].try_into().ok()?))),
+            Data_type::U32      => Some(Value::U32(u32::from_be_bytes(bytes[..4].try_into().ok()?))),
+            Data_type::U16      => Some(Value::U16(u16::from_be_bytes(bytes[..2].try_into().ok()?))),
+            Data_type::Bool     => Some(Value::Bool(bytes[0] != 0)),
+
+            Data_type::String   => Some(Value::String(String::from_utf8(bytes.to_vec()).ok()?)),
+            Data_type::Enum     => Some(Value::Enum(String::from_utf8(bytes.to_vec()).ok()?)),
+            Data_type::Blob     => Some(Value::Blob(bytes.to_vec())),
+        }
+    }
+
+
+
+    //the value as another type, None if it cannot be converted without losing information.
+    //Numbers convert between each other when they are in range, floats only when they are whole.
+    //Bools are the numbers 0 and 1. Numbers and bools convert to and from their text, and strings,
+    //enums and blobs convert between each other as long as the blob is valid UTF-8. Dates, times
+    //and datetimes only convert to and from the integer types.
+    pub fn convert(&self, data_type: &Data_type) -> Option<Value>{
+        if *self == Value::Null{
+            return Some(Value::Null)
+        }
+
+        if *self == *data_type{
+            return Some(self.clone())
+        }
+
+        let integer_source = matches!(self, Value::Int(_) | Value::U32(_) | Value::U16(_));
+        let integer_target = matches!(data_type, Data_type::Int | Data_type::U32 | Data_type::U16);
+
+        if (matches!(self, Value::Datetime(_) | Value::Date(_) | Value::Time(_)) && !integer_target)
+            || (matches!(data_type, Data_type::Datetime | Data_type::Date | Data_type::Time) && !integer_source){
+            return None
+        }
+
+        return match (self, data_type){
+            (Value::String(s) | Value::Enum(s), _)                      => Value::parse(s, data_type),
+            (Value::Blob(b), Data_type::String | Data_type::Enum)       => Value::parse(std::str::from_utf8(b).ok()?, data_type),
+            (Value::Blob(_), _) | (_, Data_type::Blob)                  => None,
+
+            (Value::Float(f), Data_type::String | Data_type::Enum)      => Value::parse(&f.to_string(), data_type),
+            (Value::Bool(b), Data_type::String | Data_type::Enum)       => Value::parse(&b.to_string(), data_type),
+            (_, Data_type::String | Data_type::Enum)                    => Value::parse(&self.integer()?.to_string(), data_type),
+
+            (_, Data_type::Float)                                       => {
+                let n = self.integer()?;
+                let f = n as f64;
+                if f as i128 == n { Some(Value::Float(f)) } else { None }
+            },
+
+            _                                                           => Value::from_integer(self.integer()?, data_type),
+        }
+    }
+
+
+
+    //a whole number value as an integer.
+    fn integer(&self) -> Option<i128>{
+        return match self{
+            Value::Int(n)       => Some(*n as i128),
+            Value::U32(n)
+            | Value::Date(n)
+            | Value::Time(n)    => Some(*n as i128),
+            Value::U16(n)       => Some(*n as i128),
+            Value::Datetime(n)  => Some(*n as i128),
+            Value::Bool(b)      => Some(*b as i128),
+            Value::Float(f)     => {
+                if f.is_finite() && f.fract() == 0.0 && f.abs() < 2f64.powi(100){
+                    Some(*f as i128)
+                }else{
+                    None
+                }
+            },
+            _                   => None,
+        }
+    }
+
+
+
+    //an integer as a value of a fixed width type, None if it is out of the type's range.
+    fn from_integer(n: i128, data_type: &Data_type) -> Option<Value>{
+        return match data_type{
+            Data_type::Int      => Some(Value::Int(i64::try_from(n).ok()?)),
+            Data_type::U32      => Some(Value::U32(u32::try_from(n).ok()?)),
+            Data_type::U16      => Some(Value::U16(u16::try_from(n).ok()?)),
+            Data_type::Date     => Some(Value::Date(u32::try_from(n).ok()?)),
+            Data_type::Time     => Some(Value::Time(u32::try_from(n).ok()?)),
+            Data_type::Datetime => Some(Value::Datetime(u64::try_from(n).ok()?)),
+            Data_type::Bool     => match n{
+                0   => Some(Value::Bool(false)),
+                1   => Some(Value::Bool(true)),
+                _   => None,
+            },
+            _                   => None,
+        }
+    }
+
+
+
+    //for a whole number outside the range of an integer type, whether it is below or above the
+    //range and the value of the type nearest to it. None if the number is in range or the type
+    //does not hold integers.
+    pub fn clamp(&self, data_type: &Data_type) -> Option<(Ordering, Value)>{
+        let n = self.integer()?;
+
+        let (min, max) = match data_type{
+            Data_type::Int      => (i64::MIN as i128, i64::MAX as i128),
+            Data_type::U32
+            | Data_type::Date
+            | Data_type::Time   => (0, u32::MAX as i128),
+            Data_type::U16      => (0, u16::MAX as i128),
+            Data_type::Datetime => (0, u64::MAX as i128),
+            Data_type::Bool     => (0, 1),
+            _                   => return None,
+        };
+
+        if n < min{
+            return Some((Ordering::Less, Value::from_integer(min, data_type)?))
+        }
+
+        if n > max{
+            return Some((Ordering::Greater, Value::from_integer(max, data_type)?))
+        }
+
+        return None
+    }
+
+
+
+    //reads text as a value of a type.
+    fn parse(text: &str, data_type: &Data_type) -> Option<Value>{
+        return match data_type{
+            Data_type::String   => Some(Value::String(text.to_string())),
+            Data_type::Enum     => Some(Value::Enum(text.to_string())),
+            Data_type::Blob     => Some(Value::Blob(text.as_bytes().to_vec())),
+            Data_type::Float    => Some(Value::Float(text.trim().parse().ok()?)),
+            Data_type::Bool     => match text.trim(){
+                "true"  => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _       => None,
+            },
+            Data_type::Int
+            | Data_type::U32
+            | Data_type::U16    => Value::from_integer(text.trim().parse().ok()?, data_type),
+            _                   => None,
+        }
+    }
+
+
+}
+
+
+
+#[repr(u8)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Data_type{
+    Int = 0,                    // 8 bytes.
+    Float = 1,                  // 8 bytes.
+    String = 3,                 // 4 bytes: variable data ID
+    Datetime = 4,               // 8 bytes.
+    Date = 5,                   // 4 bytes.
+    Time = 6,                   // 4 bytes.
+    Bool = 7,                   // 1 bytes.
+    Enum = 8,                   // 4 bytes: variable data ID
+    Blob = 9,                   // 4 bytes: variable data ID
+    U32  = 10,                  // 4 bytes.
+    U16  = 11,                  // 2 bytes
+
+}
+
+
+
+
+
+impl Data_type{
+
+    //number of bytes a value of this type takes up in a record.
+    pub fn size(&self) -> u8{
+
+        return match self{
+            Data_type::Int
+            | Data_type::Float
+            | Data_type::Datetime   => 8,
+
+            Data_type::Date
+            | Data_type::Time
+            | Data_type::U32
+            | Data_type::String
+            | Data_type::Enum
+            | Data_type::Blob       => 4,
+
+            Data_type::U16          => 2,
+
+            Data_type::Bool         => 1,
+        }
+    }
+
+
+
+    //strings, enums and blobs are kept in the data pages.
+    pub fn is_variable_size(&self) -> bool{
+        return matches!(self, Data_type::String | Data_type::Enum | Data_type::Blob)
+    }
+}
+
+
+
+impl PartialEq<Value> for Data_type {
+    fn eq(&self, other: &Value) -> bool {
+        other == self
+    }
+}
+
+
+
+impl std::convert::TryFrom<u8> for Data_type {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Data_type::Int),
+            1 => Ok(Data_type::Float),
+            3 => Ok(Data_type::String),
+            4 => Ok(Data_type::Datetime),
+            5 => Ok(Data_type::Date),
+            6 => Ok(Data_type::Time),
+            7 => Ok(Data_type::Bool),
+            8 => Ok(Data_type::Enum),
+            9 => Ok(Data_type::Blob),
+            10 => Ok(Data_type::U32),
+            11 => Ok(Data_type::U16),
+            _ => Err(()),
+        }
+    }
+}
+
+//the key of some columns of a record in a B-tree, see Value::encode_key.
+fn encode_columns(record: &[Value], columns: &[usize]) -> Vec<u8>{
+    return columns.iter().flat_map(|i| record[*i].encode_key()).collect()
+}
+
+
+
+//reads one of the 4 byte page nums of the table header.
+fn read_header_field(bytes: &[u8], offset: u16) -> u32{
+    let offset = offset as usize;
+    return u32::from_be_bytes(bytes[offset .. offset + 4].try_into().unwrap())
+}
+
+
+
+//builds the bytes of a record written with a schema version from the stored bytes of each of its
+//columns, None for null values. The flags are left for write_record_bytes/insert_record_bytes to
+//set.
+fn build_record_bytes(fields: &[Option<Vec<u8>>], schema_version: u32) -> Vec<u8>{
+    let mut bitmap = vec![0u8; fields.len().div_ceil(8)];
+    let mut values = Vec::new();
+
+    for (i, field) in fields.iter().enumerate(){
+        match field{
+            None        => bitmap[i / 8] |= 1 << (i % 8),
+            Some(bytes) => values.extend_from_slice(bytes),
+        }
+    }
+
+    let size = std::cmp::max(RECORD_HEADER_SIZE as usize + bitmap.len() + values.len(), FORWARDING_RECORD_SIZE as usize);
+
+    let mut bytes = vec![0u8];
+    bytes.extend_from_slice(&(size as u16).to_be_bytes());
+    bytes.extend_from_slice(&schema_version.to_be_bytes());
+    bytes.extend_from_slice(&bitmap);
+    bytes.extend_from_slice(&values);
+    bytes.resize(size, 0);
+
+    return bytes
+}
+
+
+
+//the number of bytes a record of values is stored in, see build_record_bytes.
+fn stored_record_size(record: &[Value]) -> usize{
+    let size = RECORD_HEADER_SIZE as usize + record.len().div_ceil(8) + record.iter().map(|value| value.size() as usize).sum::<usize>();
+
+    return std::cmp::max(size, FORWARDING_RECORD_SIZE as usize)
+}
+
+
+
+//bytes a record page has for records and their record index entries, the last 2 bytes of a page
+//are not used by the record index.
+fn record_page_capacity(page_table: &Page_table) -> usize{
+    return (page_table.page_size - PAGE_HEADER_SIZE - 2) as usize
+}
+
+
+
+pub fn open_table(name: String, file_manager: &mut File_manager, page_table: &mut Page_table) -> Option<Table>{
+    return Table::open(name, page_table, file_manager).ok()
+}
+
+
+//in future implement the method to ensure the file is locked and all pages of the file are also
+//locked while changes ar ebeing made.
+
+impl Table{
+
+    //reads the root pages of the table from its header, the schema and indexes are left to open.
+    fn new(name: String, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Table, Table_error>{
+        //the root pages of the table are stored in the table header.
+        let header = match page_table.get_mut_page(Block_ID{file_name: name.clone(), number: 0}, file_manager){
+            None    => return Err(Table_error::Invalid_table_file(format!("The header of {} could not be read.", name))),
+            Some(p) => p,
+        };
+
+        let first_record_page_num               = read_header_field(&header.bytes, FIRST_RECORD_PAGE_OFFSET);
+        let b_tree_page_num                     = read_header_field(&header.bytes, B_TREE_ROOT_OFFSET);
+        let record_free_space_tracker_page_num  = read_header_field(&header.bytes, RECORD_FREE_SPACE_TRACKER_OFFSET);
+        let first_data_page_num                 = read_header_field(&header.bytes, FIRST_DATA_PAGE_OFFSET);
+        let data_free_space_tracker_page_num    = read_header_field(&header.bytes, DATA_FREE_SPACE_TRACKER_OFFSET);
+        let schema_version                      = read_header_field(&header.bytes, SCHEMA_VERSION_OFFSET);
+        let last_key = u64::from_be_bytes(header.bytes[LAST_KEY_OFFSET as usize .. LAST_KEY_OFFSET as usize + 8].try_into().unwrap());
+
+        let variable_data_manager = Variable_data_manager::new(name.clone(), first_data_page_num, &data_free_space_tracker_page_num, page_table, file_manager)?;
+
+        let record_free_bytes = read_entries(&name, record_free_space_tracker_page_num, page_table, file_manager)?
+            .into_iter()
+            .map(|(page_num, free)| Page_free{free, page_num})
+            .collect();
+
+        let table = Table{
+            table_name: name,
+            column_schema: Vec::new(),
+            column_history: Vec::new(),
+            schema_version,
+            indexes: Vec::new(),
+            first_record_page_num,
+            b_tree_page_num,
+            last_key,
+            record_free_space_tracker_page_num,
+            record_free_bytes,
+            first_data_page_num,
+            data_free_space_tracker_page_num,
+            variable_data_manager,
+        };
+
+        return Ok(table)
+    }
+
+
+
+    //opens the table stored in a file, initialising the file first if it is empty.
+    pub fn open_or_create(name: String, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Table, Table_error>{
+        if file_manager.total_blocks(&name)? == 0{
+            Table::init_file(&name, file_manager)?;
+        }
+
+        return Table::open(name, page_table, file_manager)
+    }
+
+
+
+    //opens the table stored in an existing file after checking that its header points at pages of
+    //the right types, then loads its schema.
+    pub fn open(name: String, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Table, Table_error>{
+        let total_blocks = file_manager.total_blocks(&name)?;
+
+        if total_blocks == 0{
+            return Err(Table_error::Invalid_table_file(format!("{} is empty.", name)))
+        }
+
+        let header = match page_table.get_mut_page(Block_ID{file_name: name.clone(), number: 0}, file_manager){
+            None    => return Err(Table_error::Invalid_table_file(format!("The header of {} could not be read.", name))),
+            Some(p) => p,
+        };
+
+        if !matches!(header.page_type, Page_type::Table_structure) || header.data_end_point < TABLE_HEADER_END{
+            return Err(Table_error::Invalid_table_file(format!("{} has no table header.", name)))
+        }
+
+        let root_pages = [
+            (FIRST_RECORD_PAGE_OFFSET,          Page_type::Record),
+            (B_TREE_ROOT_OFFSET,                Page_type::B_tree),
+            (RECORD_FREE_SPACE_TRACKER_OFFSET,  Page_type::Free_space_tracker),
+            (FIRST_DATA_PAGE_OFFSET,            Page_type::Data),
+            (DATA_FREE_SPACE_TRACKER_OFFSET,    Page_type::Free_space_tracker),
+        ];
+
+        let root_page_nums: Vec<(u32, Page_type)> = root_pages.into_iter().map(|(offset, page_type)| (read_header_field(&header.bytes, offset), page_type)).collect();
+
+        for (page_num, page_type) in root_page_nums{
+            if page_num == 0 || page_num >= total_blocks{
+                return Err(Table_error::Invalid_table_file(format!("The header of {} points at page {} which does not exist.", name, page_num)))
+            }
+
+            let page = match page_table.get_mut_page(Block_ID{file_name: name.clone(), number: page_num}, file_manager){
+                None    => return Err(Table_error::Invalid_table_file(format!("Page {} of {} could not be read.", page_num, name))),
+                Some(p) => p,
+            };
+
+            if page.page_type.clone() as u8 != page_type as u8{
+                return Err(Table_error::Invalid_table_file(format!("Page {} of {} has the wrong page type.", page_num, name)))
+            }
+        }
+
+        let mut table = Table::new(name, page_table, file_manager)?;
+        table.column_history = table.parse_columns(page_table, file_manager)?;
+        table.column_schema = table.column_history.iter().filter(|column| column.dropped_version == 0).cloned().collect();
+        table.indexes = table.parse_indexes(page_table, file_manager)?;
+        table.index_unique_columns(page_table, file_manager)?;
+
+        return Ok(table)
+    }
+
+
+
+    //records the new root of the table's B-tree after it split or shrank.
+    pub fn set_b_tree_page_num(&mut self, page_num: u32, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+        if page_num == self.b_tree_page_num{
+            return Ok(0)
+        }
+
+        let block = Block_ID{file_name: self.table_name.clone(), number: 0};
+        let header = match page_table.get_mut_page(block.clone(), file_manager){
+            None    => return Err(std::io::Error::other("Table header page could not be loaded.")),
+            Some(p) => p,
+        };
+
+        header.write(B_TREE_ROOT_OFFSET, page_num.to_be_bytes().to_vec())?;
+        page_table.set_dirty(&block);
+        self.b_tree_page_num = page_num;
+
+        return Ok(1)
+    }
+
+
+
+    //writes the six pages every table starts with to an empty file.
+    pub fn init_file(table_name: &str, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+
+        //generating the initial pages
+        let mut init_pages: Vec<Page> = Vec::new();
+        let free_space: u16 = file_manager.block_size - PAGE_HEADER_SIZE - 2;
+
+        init_pages.push(Page::new(file_manager.block_size, 0, Page_type::Table_structure));
+        init_pages.last_mut().unwrap().data_end_point = TABLE_HEADER_END; // the free page list and variable data index start empty
+
+        let header_fields: [(u16, u32); 5] = [
+            (FIRST_RECORD_PAGE_OFFSET,          4),
+            (B_TREE_ROOT_OFFSET,                3),
+            (RECORD_FREE_SPACE_TRACKER_OFFSET,  1),
+            (FIRST_DATA_PAGE_OFFSET,            5),
+            (DATA_FREE_SPACE_TRACKER_OFFSET,    2),
+        ];
+
+        for (offset, page_num) in header_fields{
+            init_pages.last_mut().unwrap().write(offset, page_num.to_be_bytes().to_vec())?;
+        }
+        init_pages.push(Page::new(file_manager.block_size, 1, Page_type::Free_space_tracker));
+
+        let mut bytes: [u8;6]   = [0u8; 6];
+        let page_num: u32 = 4;
+        bytes[..4].copy_from_slice( &page_num.to_be_bytes()  );
+        bytes[4..].copy_from_slice( &free_space.to_be_bytes());
+        init_pages.last_mut().unwrap().write_at_end(bytes.to_vec());
+
+
+        init_pages.push(Page::new(file_manager.block_size, 2, Page_type::Free_space_tracker));
+
+        let page_num: u32 = 5;
+        bytes[..4].copy_from_slice( &page_num.to_be_bytes()  );
+        bytes[4..].copy_from_slice( &free_space.to_be_bytes());
+        init_pages.last_mut().unwrap().write_at_end(bytes.to_vec());
+
+
+        init_pages.push(Page::new(file_manager.block_size, 3, Page_type::B_tree));
+        init_pages.push(Page::new(file_manager.block_size, 4, Page_type::Record)); 
+        init_pages.push(Page::new(file_manager.block_size, 5, Page_type::Data));
+        
+        for page in init_pages{
+            file_manager.write(&Block_ID{file_name: table_name.to_string(), number: page.page_num}, &page)?;
+        }
+
+        return Ok(0)
+
+    }
+
+
+
+
+    //adds a column without constraints to the end of the schema.
+    pub fn add_column(&mut self, name: String, data_type: Data_type, page_table: &mut Page_table,file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        return self.add_column_definition(Column::new(name, data_type), page_table, file_manager)
+    }
+
+
+
+    //adds a column with its constraints to the end of the schema. Records that are already in the
+    //table read the column as its default, or null if it has none.
+    pub fn add_column_definition(&mut self, column: Column, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let mut columns = self.parse_columns(page_table, file_manager)?;
+        let mut column = column;
+
+        check_column_name(&column.column_name)?;
+
+        if columns.iter().any(|other| other.dropped_version == 0 && other.column_name == column.column_name){
+            return Err(Table_error::Column_exists(column.column_name))
+        }
+
+        if let Some(default) = &column.default
+            && (column.data_type != *default || default.to_bytes().len() > MAX_DEFAULT_SIZE){
+            return Err(Table_error::Invalid_default(column.column_name))
+        }
+
+        //only whether there are none, one or more records matters here.
+        let record_count = self.count_records(2, page_table, file_manager)?;
+
+        //while the table is empty more columns can join its primary key, which then spans all of
+        //them in schema order.
+        if column.primary_key{
+            if !self.primary_key_columns().is_empty() && record_count > 0{
+                return Err(Table_error::Primary_key_exists(self.primary_key_name()))
+            }
+
+            column.not_null = true;
+        }
+
+        if column.not_null && column.default.is_none() && record_count > 0{
+            return Err(Table_error::Not_null_violation(column.column_name))
+        }
+
+        //the existing records would all get the same value for the column.
+        if (column.unique || column.primary_key) && column.default.is_some() && record_count > 1{
+            return Err(Table_error::Unique_violation(column.column_name))
+        }
+
+        column.added_version = self.next_schema_version(page_table, file_manager)?;
+        column.dropped_version = 0;
+        let primary_key = column.primary_key;
+        columns.push(column);
+
+        self.write_schema(columns, page_table, file_manager)?;
+
+        if primary_key{
+            self.index_primary_keys(true, page_table, file_manager)?;
+        }
+
+        return self.index_unique_columns(page_table, file_manager)
+    }
+
+
+
+    //the page num and byte position of a column's entry in the schema.
+    pub fn find_column_index(&self, name: String, page_table: &mut Page_table, file_manager: &mut File_manager ) -> Option<(u32, u16)>{
+        let mut page_num = 0;
+
+        loop{
+            let page = page_table.get_mut_page(Block_ID{file_name: self.table_name.clone(), number: page_num}, file_manager)?;
+
+            for slot in 0..page.get_record_index_count(){
+                let index = page.get_record_index_entry(slot)? as usize;
+
+                if let Some(column) = Column::from_bytes(&page.bytes[index .. page.data_end_point as usize])
+                    && column.dropped_version == 0 && column.column_name == name{
+                    return Some((page_num, index as u16))
+                }
+            }
+
+            page_num = match page.next_index{
+                None | Some(0)  => return None,
+                Some(n)         => n,
+            };
+        }
+    }
+
+
+
+
+    //drops a column from the schema. Its entry stays until no record written before the drop is
+    //left, see rewrite_old_records.
+    pub fn remove_column(&mut self, name: String, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let mut columns = self.parse_columns(page_table, file_manager)?;
+
+        let position = match columns.iter().position(|column| column.dropped_version == 0 && column.column_name == name){
+            None    => return Err(Table_error::Column_not_found(name)),
+            Some(n) => n,
+        };
+
+        //the other columns of a primary key over several columns stay the primary key, as long as
+        //their values are still unique without the dropped column.
+        let primary_key = columns[position].primary_key;
+
+        if primary_key{
+            let rest: Vec<usize> = self.primary_key_columns().into_iter().filter(|i| self.column_schema[*i].column_name != name).collect();
+
+            if !rest.is_empty(){
+                let mut keys: HashSet<Vec<u8>> = HashSet::new();
+
+                for record_id in self.record_ids(page_table, file_manager)?{
+                    let record = self.get_record(record_id, page_table, file_manager)?;
+
+                    if !keys.insert(encode_columns(&record, &rest)){
+                        return Err(Table_error::Unique_violation(self.primary_key_name()))
+                    }
+                }
+            }
+
+            self.index_primary_keys(false, page_table, file_manager)?;
+        }
+
+        for column_names in self.indexes_with_column(&name){
+            self.remove_index(column_names, page_table, file_manager)?;
+        }
+
+        columns[position].dropped_version = self.next_schema_version(page_table, file_manager)?;
+
+        self.write_schema(columns, page_table, file_manager)?;
+
+        if primary_key{
+            self.index_primary_keys(true, page_table, file_manager)?;
+        }
+
+        //a unique column may have been looked up through an index that was dropped with the column.
+        return self.index_unique_columns(page_table, file_manager)
+    }
+
+
+
+
+    pub fn modify_column_name(&mut self, old_name: String, new_name: String, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let mut columns = self.parse_columns(page_table, file_manager)?;
+
+        check_column_name(&new_name)?;
+
+        if old_name != new_name && columns.iter().any(|column| column.dropped_version == 0 && column.column_name == new_name){
+            return Err(Table_error::Column_exists(new_name))
+        }
+
+        match columns.iter_mut().find(|column| column.dropped_version == 0 && column.column_name == old_name){
+            None            => return Err(Table_error::Column_not_found(old_name)),
+            Some(column)    => column.column_name = new_name.clone(),
+        };
+
+        for column_name in self.indexes.iter_mut().flat_map(|index| index.column_names.iter_mut()){
+            if *column_name == old_name{
+                *column_name = new_name.clone();
+            }
+        }
+
+        return self.write_schema(columns, page_table, file_manager)
+    }
+
+
+
+    //changes the type of a column and converts the value every record holds for it, see
+    //Value::convert. The column is dropped and added again with the new type in the same place and
+    //every record is written again with the new schema version. All values are converted before
+    //anything is written, so nothing changes if one of them cannot be converted, they would no
+    //longer be unique or a record would no longer fit in a page.
+    pub fn modify_column_type(&mut self, name: String, new_type: Data_type, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let mut columns = self.parse_columns(page_table, file_manager)?;
+
+        let position = match columns.iter().position(|column| column.dropped_version == 0 && column.column_name == name){
+            None    => return Err(Table_error::Column_not_found(name)),
+            Some(n) => n,
+        };
+
+        if columns[position].data_type == new_type{
+            return Ok(0)
+        }
+
+        let column_index = match self.column_schema.iter().position(|column| column.column_name == name){
+            None    => return Err(Table_error::Column_not_found(name)),
+            Some(n) => n,
+        };
+
+        let mut column = columns[position].clone();
+
+        //the default is converted along with the values.
+        if let Some(default) = &column.default{
+            column.default = match default.convert(&new_type){
+                None    => return Err(Table_error::Incompatible_type_change{column_name: name}),
+                Some(v) => Some(v),
+            };
+        }
+
+        let record_ids = self.record_ids(page_table, file_manager)?;
+        let key_columns = self.primary_key_columns();
+        let mut unique_values: HashSet<Vec<u8>> = HashSet::new();
+        let mut keys: HashSet<Vec<u8>> = HashSet::new();
+
+        for record_id in record_ids.iter(){
+            let mut record = self.get_record(*record_id, page_table, file_manager)?;
+
+            let value = match record[column_index].convert(&new_type){
+                None    => return Err(Table_error::Incompatible_type_change{column_name: name}),
+                Some(v) => v,
+            };
+
+            //different values can convert to the same one, e.g. 0.0 and -0.0 to 0.
+            if column.unique && value != Value::Null && !unique_values.insert(value.to_bytes()){
+                return Err(Table_error::Unique_violation(name))
+            }
+
+            record[column_index] = value;
+
+            if column.primary_key && !keys.insert(encode_columns(&record, &key_columns)){
+                return Err(Table_error::Unique_violation(self.primary_key_name()))
+            }
+
+            //the record is written again with the converted value, so it has to fit in a page.
+            let record_size = stored_record_size(&record);
+
+            if record_size + 2 > record_page_capacity(page_table){
+                return Err(Table_error::Record_too_large{size: record_size})
+            }
+        }
+
+        //the keys are compared differently after the change, so the B-trees are built again.
+        if column.primary_key{
+            self.index_primary_keys(false, page_table, file_manager)?;
+        }
+
+        let indexes = self.indexes_with_column(&name);
+
+        for column_names in indexes.iter(){
+            self.remove_index(column_names.clone(), page_table, file_manager)?;
+        }
+
+        let version = self.next_schema_version(page_table, file_manager)?;
+
+        column.data_type = new_type;
+        column.added_version = version;
+        columns[position].dropped_version = version;
+        columns.insert(position + 1, column);
+
+        self.write_schema(columns, page_table, file_manager)?;
+
+        for record_id in record_ids{
+            self.convert_record_column(record_id, position, column_index, page_table, file_manager)?;
+        }
+
+        if self.column_schema[column_index].primary_key{
+            self.index_primary_keys(true, page_table, file_manager)?;
+        }
+
+        for column_names in indexes{
+            self.create_index(column_names, page_table, file_manager)?;
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //writes a record again with the current schema version after its column at column_index
+    //changed type, converting the value it held in the old schema entry at history_index.
+    fn convert_record_column(&mut self, record_id: Record_ID, history_index: usize, column_index: usize, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let location = self.locate_record(record_id, page_table, file_manager)?;
+        let bytes = self.read_record_bytes(location, page_table, file_manager)?;
+
+        let (version, ranges) = match self.field_ranges(&bytes){
+            None    => return Err(Table_error::Corrupt_record(record_id)),
+            Some(r) => r,
+        };
+
+        let old_column = self.column_history[history_index].clone();
+
+        let value = if old_column.exists_in(version){
+            let field = ranges[history_index].map(|(start, end)| &bytes[start .. end]);
+            self.decode_field(record_id, &old_column.data_type, field, page_table, file_manager)?
+        }else{
+            old_column.default.unwrap_or(Value::Null)
+        };
+
+        let value = match value.convert(&self.column_schema[column_index].data_type){
+            None    => return Err(Table_error::Incompatible_type_change{column_name: old_column.column_name}),
+            Some(v) => v,
+        };
+
+        let mut changes = Data_changes::default();
+        let written = self.current_fields(&bytes, ranges, version, &mut changes, page_table, file_manager)
+            .and_then(|mut fields| {
+                self.set_field(&mut fields, column_index, &value, &mut changes, page_table, file_manager)?;
+                return self.write_fields(record_id, &fields, page_table, file_manager)
+            });
+
+        self.settle_data_changes(changes, written.is_ok(), page_table, file_manager)?;
+
+        return written
+    }
+
+
+
+    //moves the table to a new schema version and records it in the table header.
+    fn next_schema_version(&mut self, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u32, Table_error>{
+        let version = self.schema_version + 1;
+
+        self.write_header_field(SCHEMA_VERSION_OFFSET, version.to_be_bytes().to_vec(), page_table, file_manager)?;
+        self.schema_version = version;
+
+        return Ok(version)
+    }
+
+
+
+    fn write_header_field(&self, offset: u16, bytes: Vec<u8>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let block = Block_ID{file_name: self.table_name.clone(), number: 0};
+        let header = match page_table.get_mut_page(block.clone(), file_manager){
+            None    => return Err(Table_error::Io(std::io::Error::other("Table header page could not be loaded."))),
+            Some(p) => p,
+        };
+
+        header.write(offset, bytes)?;
+        page_table.set_dirty(&block);
+
+        return Ok(1)
+    }
+
+
+
+    //reads the columns from the schema entries of the Table_structure page chain, in order,
+    //including the dropped ones.
+    pub fn parse_columns(&self, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<Column>, Table_error>{
+        let mut column_vector = Vec::new();
+
+        for (page_num, entry) in self.schema_entries(page_table, file_manager)?{
+            if entry[0] == INDEX_ENTRY{
+                continue
+            }
+
+            match Column::from_bytes(&entry){
+                None    => return Err(Table_error::Invalid_table_file(format!("Schema page {} of {} has an invalid column.", page_num, self.table_name))),
+                Some(c) => column_vector.push(c),
+            }
+        }
+
+        return Ok(column_vector)
+    }
+
+
+
+    //reads the indexes from the schema entries that follow the columns.
+    pub fn parse_indexes(&self, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<Index>, Table_error>{
+        let mut indexes = Vec::new();
+
+        for (page_num, entry) in self.schema_entries(page_table, file_manager)?{
+            if entry[0] != INDEX_ENTRY{
+                continue
+            }
+
+            match Index::from_bytes(&entry){
+                None    => return Err(Table_error::Invalid_table_file(format!("Schema page {} of {} has an invalid index.", page_num, self.table_name))),
+                Some(i) => indexes.push(i),
+            }
+        }
+
+        return Ok(indexes)
+    }
+
+
+
+    //the bytes of every entry of the Table_structure page chain, in order, with the page they are in.
+    fn schema_entries(&self, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<(u32, Vec<u8>)>, Table_error>{
+        let mut entries = Vec::new();
+        let mut page_num = 0;
+
+        loop{
+            let page = match page_table.get_mut_page(Block_ID{file_name: self.table_name.clone(), number: page_num}, file_manager){
+                None    => return Err(Table_error::Invalid_table_file(format!("Schema page {} of {} could not be read.", page_num, self.table_name))),
+                Some(p) => p,
+            };
+
+            let count = page.get_record_index_count();
+
+            for slot in 0..count{
+                let start = page.get_record_index_entry(slot).unwrap_or(0) as usize;
+
+                //entries are written one after the other, so an entry ends where the next starts.
+                let end = if slot + 1 < count { page.get_record_index_entry(slot + 1).unwrap_or(0) as usize } else { page.data_end_point as usize };
+
+                if start < PAGE_HEADER_SIZE as usize || start >= end || end > page.data_end_point as usize{
+                    return Err(Table_error::Invalid_table_file(format!("Schema page {} of {} has an invalid entry.", page_num, self.table_name)))
+                }
+
+                entries.push((page_num, page.bytes[start .. end].to_vec()));
+            }
+
+            page_num = match page.next_index{
+                None | Some(0)  => break,
+                Some(n)         => n,
+            };
+        }
+
+        return Ok(entries)
+    }
+
+
+
+    //writes the columns and then the table's indexes over the schema entries, filling page 0 after
+    //the table header and then the following Table_structure pages. Pages are added to the chain as
+    //needed and the ones left over are freed.
+    fn write_schema(&mut self, columns: Vec<Column>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let mut entries: Vec<Vec<u8>> = columns.iter().map(|column| column.to_bytes()).collect();
+        entries.extend(self.indexes.iter().map(|index| index.to_bytes()));
+        let mut remaining = &entries[..];
+        let mut page_num = 0;
+
+        loop{
+            let block = Block_ID{file_name: self.table_name.clone(), number: page_num};
+            let page = match page_table.get_mut_page(block.clone(), file_manager){
+                None    => return Err(Table_error::Io(std::io::Error::other("Schema page could not be loaded."))),
+                Some(p) => p,
+            };
+
+            page.data_end_point = if page_num == 0 { TABLE_HEADER_END } else { PAGE_HEADER_SIZE };
+            page.record_index_end_point = page.bytes.len() as u16 - 2;
+
+            while let Some(entry) = remaining.first(){
+                if page.data_end_point as usize + entry.len() + 2 > page.record_index_end_point as usize{
+                    break
+                }
+
+                let offset = page.data_end_point;
+                page.write(offset, entry.clone())?;
+                page.data_end_point += entry.len() as u16;
+                page.add_record_index(offset);
+                remaining = &remaining[1..];
+            }
+
+            let next_page_num = page.next_index.unwrap_or(0);
+
+            if remaining.is_empty() && next_page_num != 0{
+                page.set_next_page_num(0);
+            }
+            page_table.set_dirty(&block);
+
+            if remaining.is_empty(){
+                //the pages after this one are no longer needed.
+                let mut free_page_num = next_page_num;
+                while free_page_num != 0{
+                    let next = match page_table.get_mut_page(Block_ID{file_name: self.table_name.clone(), number: free_page_num}, file_manager){
+                        None    => 0,
+                        Some(p) => p.next_index.unwrap_or(0),
+                    };
+
+                    free_page(&self.table_name, free_page_num, page_table, file_manager)?;
+                    free_page_num = next;
+                }
+
+                break
+            }
+
+            if next_page_num != 0{
+                page_num = next_page_num;
+                continue
+            }
+
+            let new_page_num = allocate_page(&self.table_name, Page_type::Table_structure, page_table, file_manager)?;
+
+            page_table.get_mut_page(block.clone(), file_manager).unwrap().set_next_page_num(new_page_num);
+            page_table.set_dirty(&block);
+
+            let new_block = Block_ID{file_name: self.table_name.clone(), number: new_page_num};
+            match page_table.get_mut_page(new_block.clone(), file_manager){
+                None    => return Err(Table_error::Io(std::io::Error::other("Schema page could not be loaded."))),
+                Some(p) => p.set_previous_page_num(page_num),
+            };
+            page_table.set_dirty(&new_block);
+
+            page_num = new_page_num;
+        }
+
+        self.column_schema = columns.iter().filter(|column| column.dropped_version == 0).cloned().collect();
+        self.column_history = columns;
+
+        return Ok(1)
+    }
+
+
+
+
+    pub fn print_columns_2(&self, page_table: &mut Page_table, file_manager: &mut File_manager){ 
+
+        let columns = match self.parse_columns(page_table, file_manager){
+            Err(_)  => return,
+            Ok(c)   => c,
+        };
+
+        for (count, column) in columns.iter().filter(|column| column.dropped_version == 0).enumerate(){
+            println!("Number: {} \t | \t Type: {:?}, \t | \t Name: {}", count + 1, column.data_type, column.column_name);
+        }
+    }
+
+    //adds a record with a value for every column. A null auto increment primary key is replaced
+    //by the next key of the table's sequence.
+    pub fn add_record(&mut self, record: Vec<Value>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Added_record, Table_error>{
+        let mut record = record;
+
+        //verify if [values] follows the table schema.
+        if record.len() != self.column_schema.len(){
+            return Err(Table_error::Column_count_mismatch{expected: self.column_schema.len(), found: record.len()})
+        }
+
+        let auto_increment = self.auto_increment_index();
+        let mut generated = None;
+
+        if let Some(i) = auto_increment && record[i] == Value::Null{
+            let (key, last_key) = self.generate_key(page_table, file_manager)?;
+            record[i] = key;
+            generated = Some(last_key);
+        }
+
+        for (column, value) in self.column_schema.iter().zip(record.iter()){
+            if *value == Value::Null{
+                if column.not_null{
+                    return Err(Table_error::Not_null_violation(column.column_name.clone()))
+                }
+            }else if column.data_type != *value{
+                return Err(Table_error::Type_mismatch{column_name: column.column_name.clone()})
+            }
+        }
+
+        if self.find_record(&self.key_values(&record), page_table, file_manager)?.is_some(){
+            return Err(Table_error::Unique_violation(self.primary_key_name()))
+        }
+
+        self.check_unique(&record, None, page_table, file_manager)?;
+
+        let record_size = stored_record_size(&record);
+
+        //a record has to fit in a single page next to its record index entry.
+        if record_size + 2 > record_page_capacity(page_table){
+            return Err(Table_error::Record_too_large{size: record_size})
+        }
+
+        let mut data_ids = Vec::new();
+        let stored = self.store_fields(&record, &mut data_ids, page_table, file_manager)
+            .and_then(|fields| self.insert_record_bytes(build_record_bytes(&fields, self.schema_version), page_table, file_manager));
+
+        //the data written for a record that could not be stored is not referred to by anything.
+        let record_id = match stored{
+            Err(error)  => {
+                for id in data_ids{
+                    self.variable_data_manager.remove_data(id, page_table, file_manager)?;
+                }
+                return Err(error)
+            },
+            Ok(id)      => id,
+        };
+
+        //a key the B-trees cannot hold takes the record back out.
+        let mut indexed = self.insert_key(&record, record_id, page_table, file_manager);
+
+        if indexed.is_ok(){
+            indexed = self.index_record(&record, record_id, true, page_table, file_manager);
+        }
+
+        if let Err(error) = indexed{
+            self.remove_record(record_id, page_table, file_manager)?;
+            return Err(error)
+        }
+
+        //the sequence only moves on once the record is stored, so a rejected record uses up no
+        //key. It also continues after keys that were given explicitly.
+        let used_key = match generated{
+            Some(last_key)  => Some(last_key as i128),
+            None            => auto_increment.and_then(|i| record[i].integer()),
+        };
+
+        if let Some(n) = used_key && n > self.last_key as i128{
+            self.set_last_key(n as u64, page_table, file_manager)?;
+        }
+
+        return Ok(Added_record{record_id, key: self.key_values(&record)})
+    }
+
+
+
+    //the bytes to store for every column of a record. Larger data is written to the data pages
+    //first and the record keeps a reference to it, the IDs of the data written are added to
+    //data_ids even if a later value fails. Null values take up no bytes at all.
+    fn store_fields(&mut self, record: &[Value], data_ids: &mut Vec<u32>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<Option<Vec<u8>>>, Table_error>{
+        let mut fields: Vec<Option<Vec<u8>>> = Vec::with_capacity(record.len());
+
+        for (column, value) in self.column_schema.iter().zip(record.iter()){
+            match value{
+                Value::Null         => fields.push(None),
+
+                Value::String(_)
+                | Value::Enum(_)
+                | Value::Blob(_)    => {
+                    let id = self.variable_data_manager.add_data(&column.data_type, &value.to_bytes(), page_table, file_manager)?;
+                    data_ids.push(id);
+                    fields.push(Some(id.to_be_bytes().to_vec()));
+                },
+
+                _                   => fields.push(Some(value.to_bytes())),
+            }
+        }
+
+        return Ok(fields)
+    }
+
+
+
+    //adds a record from values given by column name. Columns that are left out take their default
+    //value, or null if they have none.
+    pub fn add_named_record(&mut self, values: Vec<(String, Value)>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Added_record, Table_error>{
+        let record = self.named_record(values)?;
+
+        return self.add_record(record, page_table, file_manager)
+    }
+
+
+
+    //the value of every column for a record given by column name, filled in as add_named_record
+    //does.
+    pub fn named_record(&self, values: Vec<(String, Value)>) -> Result<Vec<Value>, Table_error>{
+        let mut values = values;
+        let mut record = Vec::with_capacity(self.column_schema.len());
+        let auto_increment = self.auto_increment_index();
+
+        for (i, column) in self.column_schema.iter().enumerate(){
+            match values.iter().position(|(name, _)| *name == column.column_name){
+                Some(position)  => record.push(values.swap_remove(position).1),
+                None            => {
+                    match &column.default{
+                        Some(default)           => record.push(default.clone()),
+                        None if column.not_null && Some(i) != auto_increment
+                                                => return Err(Table_error::Not_null_violation(column.column_name.clone())),
+                        None                    => record.push(Value::Null),
+                    }
+                },
+            }
+        }
+
+        if let Some((name, _)) = values.into_iter().next(){
+            return Err(Table_error::Column_not_found(name))
+        }
+
+        return Ok(record)
+    }
+
+
+
+    //checks that the values of the unique columns of a record are not in any other record. Every
+    //unique column can be looked up through an index, see index_unique_columns.
+    fn check_unique(&self, record: &[Value], record_id: Option<Record_ID>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        //a primary key of a single column is checked through the B-tree instead.
+        let key_index = self.primary_key_index();
+
+        for (i, column) in self.column_schema.iter().enumerate(){
+            //any number of records can leave a unique column null.
+            if !column.unique || Some(i) == key_index || record[i] == Value::Null{
+                continue
+            }
+
+            let others = self.find_records(&column.column_name, &record[i], page_table, file_manager)?;
+
+            if others.iter().any(|other| Some(*other) != record_id){
+                return Err(Table_error::Unique_violation(column.column_name.clone()))
+            }
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //how many records the table holds, counting no further than limit. Record pages are read
+    //only until that many records are found.
+    fn count_records(&self, limit: usize, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<usize, Table_error>{
+        let mut count = 0;
+        let mut page_num = self.first_record_page_num;
+
+        while page_num != 0 && count < limit{
+            let page = match page_table.get_mut_page(Block_ID{file_name: self.table_name.clone(), number: page_num}, file_manager){
+                None    => return Err(Table_error::Io(std::io::Error::other("Record page could not be loaded."))),
+                Some(p) => p,
+            };
+
+            for slot in 0..page.get_record_index_count(){
+                match page.get_record_index_entry(slot){
+                    None | Some(0)  => (),
+                    Some(offset)    => {
+                        if page.bytes[offset as usize] & RECORD_MOVED == 0{
+                            count += 1;
+                        }
+                    },
+                }
+            }
+
+            page_num = page.next_index.unwrap_or(0);
+        }
+
+        return Ok(std::cmp::min(count, limit))
+    }
+
+
+
+    //the IDs of every record in the table, in record page order. Moved records are listed by the
+    //ID of their forwarding record.
+    pub fn record_ids(&self, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<Record_ID>, Table_error>{
+        let mut record_ids = Vec::new();
+        let mut page_num = self.first_record_page_num;
+
+        while page_num != 0{
+            let page = match page_table.get_mut_page(Block_ID{file_name: self.table_name.clone(), number: page_num}, file_manager){
+                None    => return Err(Table_error::Io(std::io::Error::other("Record page could not be loaded."))),
+                Some(p) => p,
+            };
+
+            for slot in 0..page.get_record_index_count(){
+                match page.get_record_index_entry(slot){
+                    None | Some(0)  => (),
+                    Some(offset)    => {
+                        if page.bytes[offset as usize] & RECORD_MOVED == 0{
+                            record_ids.push(Record_ID{page_num, slot});
+                        }
+                    },
+                }
+            }
+
+            page_num = page.next_index.unwrap_or(0);
+        }
+
+        return Ok(record_ids)
+    }
+
+
+
+    //iterates over every record of the table with its Record_ID, see Table_scan.
+    pub fn scan<'a>(&'a self, page_table: &'a mut Page_table, file_manager: &'a mut File_manager) -> Table_scan<'a>{
+        return Table_scan::new(self, page_table, file_manager)
+    }
+
+
+
+    //writes the bytes of a record into a record page with enough space for them.
+    fn insert_record_bytes(&mut self, bytes: Vec<u8>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Record_ID, Table_error>{
+        let record_size = bytes.len() as u16;
+
+        //Find the page to write the record to.
+        let page_num = self.find_record_page(record_size + 2, page_table, file_manager)?;
+        let block = Block_ID{file_name: self.table_name.clone(), number: page_num};
+
+        let page = match page_table.get_mut_page(block.clone(), file_manager){
+            None    => return Err(Table_error::Io(std::io::Error::other("Record page could not be loaded."))),
+            Some(p) => p,
+        };
+
+        let offset = page.data_end_point;
+        page.write(offset, bytes)?;
+        page.data_end_point += record_size;
+
+        //entries of removed records are reused before the record index grows.
+        let slot = match page.find_empty_record_index_entry(){
+            Some(slot)  => {
+                page.set_record_index_entry(slot, offset);
+                slot
+            },
+            None        => {
+                page.add_record_index(offset);
+                page.get_record_index_count() - 1
+            },
+        };
+
+        let free = page.record_index_end_point - page.data_end_point;
+        page_table.set_dirty(&block);
+
+        self.set_record_free_space(page_num, free, page_table, file_manager)?;
+
+        return Ok(Record_ID{page_num, slot})
+    }
+
+
+
+    //copies the bytes stored at a record index entry out of its record page.
+    fn read_record_bytes(&self, record_id: Record_ID, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<u8>, Table_error>{
+        let block = Block_ID{file_name: self.table_name.clone(), number: record_id.page_num};
+
+        let page = match page_table.get_mut_page(block, file_manager){
+            None    => return Err(Table_error::Record_not_found(record_id)),
+            Some(p) => p,
+        };
+
+        let offset = match page.get_record_index_entry(record_id.slot){
+            None | Some(0)  => return Err(Table_error::Record_not_found(record_id)),
+            Some(n)         => n as usize,
+        };
+
+        let record_size = u16::from_be_bytes([page.bytes[offset + 1], page.bytes[offset + 2]]) as usize;
+
+        if record_size < RECORD_HEADER_SIZE as usize || offset + record_size > page.data_end_point as usize{
+            return Err(Table_error::Corrupt_record(record_id))
+        }
+
+        return Ok(page.bytes[offset .. offset + record_size].to_vec())
+    }
+
+
+
+    //where the bytes of a record are actually stored, following its forwarding record if it has
+    //been moved.
+    fn locate_record(&self, record_id: Record_ID, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Record_ID, Table_error>{
+        let bytes = self.read_record_bytes(record_id, page_table, file_manager)?;
+
+        if bytes[0] & RECORD_FORWARDED == 0{
+            return Ok(record_id)
+        }
+
+        let page_num = u32::from_be_bytes(bytes[3..7].try_into().unwrap());
+        let slot = u16::from_be_bytes(bytes[7..9].try_into().unwrap());
+
+        return Ok(Record_ID{page_num, slot})
+    }
+
+
+
+    //the schema version of a record and the byte range of the value of each schema entry inside
+    //it, in column_history order. The range is None for null values and for the columns the
+    //record's version does not have.
+    fn field_ranges(&self, bytes: &[u8]) -> Option<(u32, Vec<Field_range>)>{
+        let version = u32::from_be_bytes(bytes.get(3 .. 7)?.try_into().ok()?);
+
+        let columns: Vec<usize> = (0..self.column_history.len()).filter(|i| self.column_history[*i].exists_in(version)).collect();
+
+        let bitmap_start = RECORD_HEADER_SIZE as usize;
+        let bitmap = bytes.get(bitmap_start .. bitmap_start + columns.len().div_ceil(8))?;
+
+        let mut ranges = vec![None; self.column_history.len()];
+        let mut index = bitmap_start + bitmap.len();
+
+        for (bit, i) in columns.into_iter().enumerate(){
+            if bitmap[bit / 8] & (1 << (bit % 8)) != 0{
+                continue
+            }
+
+            let size = self.column_history[i].data_type.size() as usize;
+
+            if index + size > bytes.len(){
+                return None
+            }
+
+            ranges[i] = Some((index, index + size));
+            index += size;
+        }
+
+        return Some((version, ranges))
+    }
+
+
+
+    //fetches a single record and decodes it against the column schema. Columns added after the
+    //record was written read as their default.
+    pub fn get_record(&self, record_id: Record_ID, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<Value>, Table_error>{
+        let location = self.locate_record(record_id, page_table, file_manager)?;
+        let bytes = self.read_record_bytes(location, page_table, file_manager)?;
+
+        let (version, ranges) = match self.field_ranges(&bytes){
+            None    => return Err(Table_error::Corrupt_record(record_id)),
+            Some(r) => r,
+        };
+
+        let mut record = Vec::with_capacity(self.column_schema.len());
+
+        for (column, range) in self.column_history.iter().zip(ranges){
+            if column.dropped_version != 0{
+                continue
+            }
+
+            if !column.exists_in(version){
+                record.push(column.default.clone().unwrap_or(Value::Null));
+                continue
+            }
+
+            let field = range.map(|(start, end)| &bytes[start .. end]);
+            record.push(self.decode_field(record_id, &column.data_type, field, page_table, file_manager)?);
+        }
+
+        return Ok(record)
+    }
+
+
+
+    //the value of a column from its stored bytes, fetching larger data from the data pages.
+    fn decode_field(&self, record_id: Record_ID, data_type: &Data_type, field: Option<&[u8]>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Value, Table_error>{
+        let field = match field{
+            None    => return Ok(Value::Null),
+            Some(f) => f,
+        };
+
+        let value = if data_type.is_variable_size(){
+            let id = u32::from_be_bytes(field[..4].try_into().unwrap());
+            let data = self.variable_data_manager.get_data(id, page_table, file_manager)?;
+            Value::from_bytes(data_type, &data)
+        }else{
+            Value::from_bytes(data_type, field)
+        };
+
+        return match value{
+            None        => Err(Table_error::Corrupt_record(record_id)),
+            Some(v)     => Ok(v),
+        }
+    }
+
+
+
+    //the stored bytes of each current column of a record, for writing it again with the current
+    //schema version. Columns added after the record was written get their default, and the larger
+    //data of the dropped columns it still held is left in changes to be freed.
+    fn current_fields(&mut self, bytes: &[u8], ranges: Vec<Field_range>, version: u32, changes: &mut Data_changes, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<Option<Vec<u8>>>, Table_error>{
+        let mut fields = Vec::with_capacity(self.column_schema.len());
+        let columns = self.column_history.clone();
+
+        for (column, range) in columns.iter().zip(ranges){
+            let field = range.map(|(start, end)| bytes[start .. end].to_vec());
+
+            if column.dropped_version != 0{
+                if let (true, Some(field)) = (column.data_type.is_variable_size(), field){
+                    changes.replaced.push(u32::from_be_bytes(field[..4].try_into().unwrap()));
+                }
+                continue
+            }
+
+            if column.exists_in(version){
+                fields.push(field);
+                continue
+            }
+
+            match &column.default{
+                None            => fields.push(None),
+                Some(default)   => {
+                    if column.data_type.is_variable_size(){
+                        let id = self.variable_data_manager.add_data(&column.data_type, &default.to_bytes(), page_table, file_manager)?;
+                        changes.added.push(id);
+                        fields.push(Some(id.to_be_bytes().to_vec()));
+                    }else{
+                        fields.push(Some(default.to_bytes()));
+                    }
+                },
+            }
+        }
+
+        return Ok(fields)
+    }
+
+
+
+    //writes records that were written with an older schema version again with the current one,
+    //at most limit of them per call so it can be done a little at a time next to other work.
+    //Returns how many were rewritten, once none are left the entries of dropped columns are taken
+    //out of the schema.
+    pub fn rewrite_old_records(&mut self, limit: usize, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<usize, Table_error>{
+        let mut rewritten = 0;
+
+        for record_id in self.record_ids(page_table, file_manager)?{
+            let location = self.locate_record(record_id, page_table, file_manager)?;
+            let bytes = self.read_record_bytes(location, page_table, file_manager)?;
+
+            let (version, ranges) = match self.field_ranges(&bytes){
+                None    => return Err(Table_error::Corrupt_record(record_id)),
+                Some(r) => r,
+            };
+
+            if version == self.schema_version{
+                continue
+            }
+
+            if rewritten == limit{
+                return Ok(rewritten)
+            }
+
+            let mut changes = Data_changes::default();
+            let written = self.current_fields(&bytes, ranges, version, &mut changes, page_table, file_manager)
+                .and_then(|fields| self.write_fields(record_id, &fields, page_table, file_manager));
+
+            self.settle_data_changes(changes, written.is_ok(), page_table, file_manager)?;
+            written?;
+
+            rewritten += 1;
+        }
+
+        if self.column_history.len() != self.column_schema.len(){
+            let columns = self.column_schema.clone();
+            self.write_schema(columns, page_table, file_manager)?;
+        }
+
+        return Ok(rewritten)
+    }
+
+
+
+    //picks the record page with the most free bytes if it has enough, or adds a new record page
+    //after the first one.
+    fn find_record_page(&mut self, needed: u16, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u32, std::io::Error>{
+        if let Some(page) = self.record_free_bytes.peek() && page.free >= needed{
+            return Ok(page.page_num)
+        }
+
+        let page_num = allocate_page(&self.table_name, Page_type::Record, page_table, file_manager)?;
+        let first_block = Block_ID{file_name: self.table_name.clone(), number: self.first_record_page_num};
+
+        let next_page_num = match page_table.get_mut_page(first_block.clone(), file_manager){
+            None    => return Err(std::io::Error::other("Record page could not be loaded.")),
+            Some(p) => p.next_index.unwrap_or(0),
+        };
+
+        page_table.create_overflow_page(&first_block, Page_type::Record, next_page_num, &[], Some(page_num), file_manager)?;
+
+        return Ok(page_num)
+    }
+
+
+
+    //records the free bytes of a record page in the record free space tracker.
+    fn set_record_free_space(&mut self, page_num: u32, free: u16, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+        let mut entries = std::mem::take(&mut self.record_free_bytes).into_vec();
+        entries.retain(|entry| entry.page_num != page_num);
+        entries.push(Page_free{free, page_num});
+        self.record_free_bytes = BinaryHeap::from(entries);
+
+        return set_free_space(&self.table_name, self.record_free_space_tracker_page_num, page_num, free, page_table, file_manager)
+    }
+
+
+
+
+    //builds a B-tree over the values of one or more columns so records can be found by them
+    //without going through every record. An index over several columns also finds records by the
+    //values of its first columns, see index_range. The index is kept up to date as records are
+    //added, modified and removed. Values too large for a B-tree entry cannot be indexed.
+    pub fn create_index(&mut self, column_names: Vec<String>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        if column_names.is_empty(){
+            return Err(Table_error::Column_not_found(String::new()))
+        }
+
+        for (i, column_name) in column_names.iter().enumerate(){
+            if !self.column_schema.iter().any(|column| column.column_name == *column_name) || column_names[.. i].contains(column_name){
+                return Err(Table_error::Column_not_found(column_name.clone()))
+            }
+        }
+
+        if self.indexes.iter().any(|index| index.column_names == column_names){
+            return Err(Table_error::Index_exists(column_names.join(", ")))
+        }
+
+        let root_page_num = allocate_page(&self.table_name, Page_type::B_tree, page_table, file_manager)?;
+        self.indexes.push(Index{column_names, root_page_num});
+        let position = self.indexes.len() - 1;
+
+        for record_id in self.record_ids(page_table, file_manager)?{
+            let record = self.get_record(record_id, page_table, file_manager)?;
+
+            if let Err(error) = self.insert_index_key(position, &record, record_id, page_table, file_manager){
+                let index = self.indexes.pop().unwrap();
+                B_tree::new(self.table_name.clone(), index.root_page_num).destroy(page_table, file_manager)?;
+                return Err(error)
+            }
+        }
+
+        let columns = self.column_history.clone();
+
+        return self.write_schema(columns, page_table, file_manager)
+    }
+
+
+
+    //removes the index over some columns and frees its pages. An index that is the only way to look
+    //up the values of a unique column cannot be removed.
+    pub fn drop_index(&mut self, column_names: Vec<String>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let position = match self.indexes.iter().position(|index| index.column_names == column_names){
+            None    => return Err(Table_error::Index_not_found(column_names.join(", "))),
+            Some(n) => n,
+        };
+
+        let index = self.indexes.remove(position);
+        let required = self.column_schema.iter().find(|column| column.unique && !self.is_indexed(&column.column_name)).map(|column| column.column_name.clone());
+        self.indexes.insert(position, index);
+
+        if let Some(column_name) = required{
+            return Err(Table_error::Index_required(column_name))
+        }
+
+        return self.remove_index(column_names, page_table, file_manager)
+    }
+
+
+
+    //removes the index over some columns and frees its pages, without checking whether a unique
+    //column needs it.
+    fn remove_index(&mut self, column_names: Vec<String>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let position = match self.indexes.iter().position(|index| index.column_names == column_names){
+            None    => return Err(Table_error::Index_not_found(column_names.join(", "))),
+            Some(n) => n,
+        };
+
+        let index = self.indexes.remove(position);
+        B_tree::new(self.table_name.clone(), index.root_page_num).destroy(page_table, file_manager)?;
+
+        let columns = self.column_history.clone();
+
+        return self.write_schema(columns, page_table, file_manager)
+    }
+
+
+
+    //whether the records with a value in a column can be found through the primary key or an
+    //index, see index_range.
+    fn is_indexed(&self, column_name: &str) -> bool{
+        let key_columns = self.primary_key_columns();
+
+        if key_columns.first().is_some_and(|i| self.column_schema[*i].column_name == column_name){
+            return true
+        }
+
+        return self.indexes.iter().any(|index| index.column_names.first().is_some_and(|name| name == column_name))
+    }
+
+
+
+    //creates an index over every unique column that is not yet indexed, so check_unique can find
+    //the records with a value without going through all of them. Like in any index, values too
+    //large for a B-tree entry cannot be stored in such a column.
+    fn index_unique_columns(&mut self, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let column_names: Vec<String> = self.column_schema.iter()
+            .filter(|column| column.unique && !self.is_indexed(&column.column_name))
+            .map(|column| column.column_name.clone())
+            .collect();
+
+        for column_name in column_names{
+            self.create_index(vec![column_name], page_table, file_manager)?;
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //the column names of the indexes that contain a column.
+    fn indexes_with_column(&self, column_name: &str) -> Vec<Vec<String>>{
+        return self.indexes.iter()
+            .filter(|index| index.column_names.iter().any(|name| name == column_name))
+            .map(|index| index.column_names.clone())
+            .collect()
+    }
+
+
+
+    //the key of a record in an index: its encoded values followed by its Record_ID.
+    fn index_key(&self, position: usize, record: &[Value], record_id: Record_ID) -> Vec<u8>{
+        let columns: Vec<usize> = self.indexes[position].column_names.iter()
+            .filter_map(|name| self.column_schema.iter().position(|column| column.column_name == *name))
+            .collect();
+
+        let mut key = encode_columns(record, &columns);
+        key.extend_from_slice(&record_id.to_bytes());
+        return key
+    }
+
+
+
+    fn insert_index_key(&mut self, position: usize, record: &[Value], record_id: Record_ID, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let mut tree = B_tree::new(self.table_name.clone(), self.indexes[position].root_page_num);
+        let result = tree.insert(&self.index_key(position, record, record_id), &[], page_table, file_manager);
+        self.set_index_root(position, tree.root_page_num, page_table, file_manager)?;
+        result?;
+
+        return Ok(1)
+    }
+
+
+
+    fn remove_index_key(&mut self, position: usize, record: &[Value], record_id: Record_ID, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let mut tree = B_tree::new(self.table_name.clone(), self.indexes[position].root_page_num);
+        tree.delete(&self.index_key(position, record, record_id), page_table, file_manager)?;
+        self.set_index_root(position, tree.root_page_num, page_table, file_manager)?;
+
+        return Ok(1)
+    }
+
+
+
+    //records the new root of an index after its B-tree split or shrank.
+    fn set_index_root(&mut self, position: usize, root_page_num: u32, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        if self.indexes[position].root_page_num == root_page_num{
+            return Ok(0)
+        }
+
+        self.indexes[position].root_page_num = root_page_num;
+        let columns = self.column_history.clone();
+
+        return self.write_schema(columns, page_table, file_manager)
+    }
+
+
+
+    //adds a record to every index, or removes it when insert is false.
+    fn index_record(&mut self, record: &[Value], record_id: Record_ID, insert: bool, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        for position in 0..self.indexes.len(){
+            if insert{
+                self.insert_index_key(position, record, record_id, page_table, file_manager)?;
+            }else{
+                self.remove_index_key(position, record, record_id, page_table, file_manager)?;
+            }
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //the records whose values of some columns are between two bounds, in value order, found
+    //through the primary key or an index whose columns start with the given ones. The bounds are
+    //values of the first of the columns, so records are compared by as many columns as a bound has
+    //values: from [a] to [a] finds every record with a in the first column, from [a, b] to [a, c]
+    //those that also have b to c in the second. None if no index starts with the columns.
+    pub fn index_range(&self, column_names: &[&str], from: Bound<&[Value]>, to: Bound<&[Value]>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Option<Vec<Record_ID>>, Table_error>{
+        for bound in [from, to]{
+            if let Bound::Included(values) | Bound::Excluded(values) = bound && values.len() > column_names.len(){
+                return Err(Table_error::Column_count_mismatch{expected: column_names.len(), found: values.len()})
+            }
+        }
+
+        let starts_with = |names: &[&str]| !column_names.is_empty() && names.starts_with(column_names);
+
+        let key_names: Vec<&str> = self.primary_key_columns().into_iter().map(|i| self.column_schema[i].column_name.as_str()).collect();
+
+        //the primary key maps keys to Record_IDs, an index has them at the end of its keys.
+        let (tree, in_key) = if starts_with(&key_names){
+            (B_tree::new(self.table_name.clone(), self.b_tree_page_num), false)
+        }else{
+            let index = self.indexes.iter().find(|index| starts_with(&index.column_names.iter().map(|name| name.as_str()).collect::<Vec<&str>>()));
+
+            match index{
+                None        => return Ok(None),
+                Some(index) => (B_tree::new(self.table_name.clone(), index.root_page_num), true),
+            }
+        };
+
+        //a key starting with some values sorts after the values on their own and before the values
+        //followed by 255s, as every value starts with KEY_NULL or KEY_VALUE.
+        let lowest = |values: &[Value]| -> Vec<u8> { values.iter().flat_map(|value| value.encode_key()).collect() };
+        let highest = |values: &[Value]| -> Vec<u8> { let mut key = lowest(values); key.extend_from_slice(&[255; 7]); key };
+
+        let from = match from{
+            Bound::Included(values) => Bound::Included(lowest(values)),
+            Bound::Excluded(values) => Bound::Excluded(highest(values)),
+            Bound::Unbounded        => Bound::Unbounded,
+        };
+
+        let to = match to{
+            Bound::Included(values) => Bound::Included(highest(values)),
+            Bound::Excluded(values) => Bound::Excluded(lowest(values)),
+            Bound::Unbounded        => Bound::Unbounded,
+        };
+
+        let entries = tree.range(from.as_ref().map(|key| key.as_slice()), to.as_ref().map(|key| key.as_slice()), page_table, file_manager)?;
+
+        let mut record_ids = Vec::with_capacity(entries.len());
+
+        for (key, value) in entries{
+            let record_id = match in_key{
+                true    => key.len().checked_sub(6).and_then(|start| Record_ID::from_bytes(&key[start ..])),
+                false   => Record_ID::from_bytes(&value),
+            };
+
+            match record_id{
+                None            => return Err(Table_error::Invalid_table_file(format!("An index of {} has an invalid entry.", self.table_name))),
+                Some(record_id) => record_ids.push(record_id),
+            }
+        }
+
+        return Ok(Some(record_ids))
+    }
+
+
+
+    //the records with a value in a column, found through an index or the primary key if one starts
+    //with the column, otherwise by going through every record.
+    pub fn find_records(&self, column_name: &str, value: &Value, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<Record_ID>, Table_error>{
+        let column_index = match self.column_schema.iter().position(|column| column.column_name == column_name){
+            None    => return Err(Table_error::Column_not_found(column_name.to_string())),
+            Some(n) => n,
+        };
+
+        let values = std::slice::from_ref(value);
+
+        if let Some(record_ids) = self.index_range(&[column_name], Bound::Included(values), Bound::Included(values), page_table, file_manager)?{
+            return Ok(record_ids)
+        }
+
+        let mut record_ids = Vec::new();
+
+        for item in self.scan(page_table, file_manager){
+            let (record_id, record) = item?;
+
+            if record[column_index] == *value{
+                record_ids.push(record_id);
+            }
+        }
+
+        return Ok(record_ids)
+    }
+
+
+
+    //the values of some columns of every record that matches a predicate, with their Record_IDs.
+    //No columns selects all of them, and no predicate every record. Records are found through the
+    //primary key or an index when the predicate compares the first column of one with values, see
+    //candidates, and by going through every record otherwise.
+    pub fn select(&self, columns: &[&str], predicate: Option<&Predicate>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<(Record_ID, Vec<Value>)>, Table_error>{
+        let mut projection = Vec::with_capacity(columns.len());
+
+        for name in columns{
+            match self.column_schema.iter().position(|column| column.column_name == *name){
+                None    => return Err(Table_error::Column_not_found(name.to_string())),
+                Some(i) => projection.push(i),
+            }
+        }
+
+        if columns.is_empty(){
+            projection = (0..self.column_schema.len()).collect();
+        }
+
+        let predicate = match predicate{
+            None    => None,
+            Some(p) => Some(p.prepare(&self.column_schema)?),
+        };
+
+        let candidates = match &predicate{
+            None    => None,
+            Some(p) => self.candidates(p, page_table, file_manager)?,
+        };
+
+        let mut selected = Vec::new();
+        let mut keep = |record_id: Record_ID, record: Vec<Value>|{
+            if predicate.as_ref().is_none_or(|p| p.evaluate(&self.column_schema, &record) == Some(true)){
+                selected.push((record_id, projection.iter().map(|i| record[*i].clone()).collect()));
+            }
+        };
+
+        match candidates{
+            Some(record_ids)    => {
+                //IN and OR can find a record more than once.
+                let mut seen: HashSet<Record_ID> = HashSet::new();
+
+                for record_id in record_ids{
+                    if seen.insert(record_id){
+                        keep(record_id, self.get_record(record_id, page_table, file_manager)?);
+                    }
+                }
+            },
+
+            None                => {
+                for item in self.scan(page_table, file_manager){
+                    let (record_id, record) = item?;
+                    keep(record_id, record);
+                }
+            },
+        }
+
+        return Ok(selected)
+    }
+
+
+
+    //the records that can match a prepared predicate, found through the primary key or an index.
+    //They still have to be checked against the whole predicate. None if there is no index to use,
+    //in which case every record has to be checked.
+    fn candidates(&self, predicate: &Predicate, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Option<Vec<Record_ID>>, Table_error>{
+        //nulls sort first in an index, so a lower bound leaves them out.
+        let null = [Value::Null];
+
+        return match predicate{
+            Predicate::Equal(name, value)           => self.index_range(&[name], Bound::Included(std::slice::from_ref(value)), Bound::Included(std::slice::from_ref(value)), page_table, file_manager),
+            Predicate::Less(name, value)            => self.index_range(&[name], Bound::Excluded(&null), Bound::Excluded(std::slice::from_ref(value)), page_table, file_manager),
+            Predicate::Greater(name, value)         => self.index_range(&[name], Bound::Excluded(std::slice::from_ref(value)), Bound::Unbounded, page_table, file_manager),
+            Predicate::Between(name, low, high)     => self.index_range(&[name], Bound::Included(std::slice::from_ref(low)), Bound::Included(std::slice::from_ref(high)), page_table, file_manager),
+            Predicate::Is_null(name)                => self.index_range(&[name], Bound::Included(&null), Bound::Included(&null), page_table, file_manager),
+
+            Predicate::In(name, values)             => {
+                let mut record_ids = Vec::new();
+
+                for value in values{
+                    let value = std::slice::from_ref(value);
+
+                    match self.index_range(&[name], Bound::Included(value), Bound::Included(value), page_table, file_manager)?{
+                        None        => return Ok(None),
+                        Some(ids)   => record_ids.extend(ids),
+                    }
+                }
+
+                Ok(Some(record_ids))
+            },
+
+            //either side narrows down the records, but both have to for OR.
+            Predicate::And(left, right)             => match self.candidates(left, page_table, file_manager)?{
+                Some(record_ids)    => Ok(Some(record_ids)),
+                None                => self.candidates(right, page_table, file_manager),
+            },
+
+            Predicate::Or(left, right)              => {
+                let mut record_ids = match self.candidates(left, page_table, file_manager)?{
+                    None        => return Ok(None),
+                    Some(ids)   => ids,
+                };
+
+                match self.candidates(right, page_table, file_manager)?{
+                    None        => return Ok(None),
+                    Some(ids)   => record_ids.extend(ids),
+                }
+
+                Ok(Some(record_ids))
+            },
+
+            Predicate::Like(..)
+            | Predicate::Not(_)                     => Ok(None),
+        }
+    }
+
+
+
+    //finds a record by the values of its primary key using the table's B-tree.
+    pub fn find_record(&self, key: &[Value], page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Option<Record_ID>, Table_error>{
+        let tree = match self.primary_key_tree(){
+            None    => return Ok(None),
+            Some(t) => t,
+        };
+
+        let key_columns = self.primary_key_columns();
+
+        if key.len() != key_columns.len(){
+            return Err(Table_error::Column_count_mismatch{expected: key_columns.len(), found: key.len()})
+        }
+
+        if key.contains(&Value::Null){
+            return Ok(None)
+        }
+
+        let key: Vec<u8> = key.iter().flat_map(|value| value.encode_key()).collect();
+
+        return match tree.search(&key, page_table, file_manager)?{
+            None        => Ok(None),
+            Some(bytes) => match Record_ID::from_bytes(&bytes){
+                None        => Err(Table_error::Invalid_table_file(format!("The B-tree of {} has an invalid entry.", self.table_name))),
+                Some(r)     => Ok(Some(r)),
+            },
+        }
+    }
+
+
+
+    //positions of the primary key columns in the column schema.
+    pub fn primary_key_columns(&self) -> Vec<usize>{
+        return (0..self.column_schema.len()).filter(|i| self.column_schema[*i].primary_key).collect()
+    }
+
+
+
+    //position of the primary key in the column schema, None if there is none or it spans several
+    //columns.
+    pub fn primary_key_index(&self) -> Option<usize>{
+        return match self.primary_key_columns()[..]{
+            [i] => Some(i),
+            _   => None,
+        }
+    }
+
+
+
+    //the primary key column whose values are generated, only a key of a single Int or U32 column
+    //has one.
+    fn auto_increment_index(&self) -> Option<usize>{
+        return self.primary_key_index().filter(|i| self.column_schema[*i].is_auto_increment())
+    }
+
+
+
+    //the names of the primary key columns, for errors.
+    fn primary_key_name(&self) -> String{
+        let names: Vec<&str> = self.primary_key_columns().into_iter().map(|i| self.column_schema[i].column_name.as_str()).collect();
+        return names.join(", ")
+    }
+
+
+
+    //the values of the primary key columns of a record.
+    fn key_values(&self, record: &[Value]) -> Vec<Value>{
+        return self.primary_key_columns().into_iter().map(|i| record[i].clone()).collect()
+    }
+
+
+
+    //the table's B-tree, which maps the primary key of every record to its Record_ID.
+    fn primary_key_tree(&self) -> Option<B_tree>{
+        if self.primary_key_columns().is_empty(){
+            return None
+        }
+
+        return Some(B_tree::new(self.table_name.clone(), self.b_tree_page_num))
+    }
+
+
+
+    //puts the primary key of a record in the B-tree.
+    fn insert_key(&mut self, record: &[Value], record_id: Record_ID, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let mut tree = match self.primary_key_tree(){
+            None    => return Ok(0),
+            Some(t) => t,
+        };
+
+        let key = encode_columns(record, &self.primary_key_columns());
+        let result = tree.insert(&key, &record_id.to_bytes(), page_table, file_manager);
+        self.set_b_tree_page_num(tree.root_page_num, page_table, file_manager)?;
+
+        return match result{
+            Ok(_)                                               => Ok(1),
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => Err(Table_error::Unique_violation(self.primary_key_name())),
+            Err(error)                                          => Err(error.into()),
+        }
+    }
+
+
+
+    //takes the primary key of a record out of the B-tree.
+    fn remove_key(&mut self, record: &[Value], page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let mut tree = match self.primary_key_tree(){
+            None    => return Ok(0),
+            Some(t) => t,
+        };
+
+        tree.delete(&encode_columns(record, &self.primary_key_columns()), page_table, file_manager)?;
+        self.set_b_tree_page_num(tree.root_page_num, page_table, file_manager)?;
+
+        return Ok(1)
+    }
+
+
+
+    //puts the primary key of every record in the B-tree, or takes them out again when insert is
+    //false, for when a primary key column is added, dropped or changes type.
+    fn index_primary_keys(&mut self, insert: bool, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        if self.primary_key_columns().is_empty(){
+            return Ok(0)
+        }
+
+        for record_id in self.record_ids(page_table, file_manager)?{
+            let record = self.get_record(record_id, page_table, file_manager)?;
+
+            if insert{
+                self.insert_key(&record, record_id, page_table, file_manager)?;
+            }else{
+                self.remove_key(&record, page_table, file_manager)?;
+            }
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //the next number of the primary key sequence that is not already used as a key, as a value and
+    //as the number to move the sequence on to once a record with it is stored.
+    fn generate_key(&self, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<(Value, u64), Table_error>{
+        let column = self.column_schema[self.primary_key_index().unwrap()].clone();
+        let mut last_key = self.last_key;
+
+        loop{
+            let key = match last_key.checked_add(1).and_then(|n| Value::from_integer(n as i128, &column.data_type)){
+                None    => return Err(Table_error::Key_overflow(column.column_name)),
+                Some(k) => k,
+            };
+            last_key += 1;
+
+            if self.find_record(std::slice::from_ref(&key), page_table, file_manager)?.is_none(){
+                return Ok((key, last_key))
+            }
+        }
+    }
+
+
+
+    fn set_last_key(&mut self, last_key: u64, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        self.write_header_field(LAST_KEY_OFFSET, last_key.to_be_bytes().to_vec(), page_table, file_manager)?;
+        self.last_key = last_key;
+
+        return Ok(1)
+    }
+
+
+
+    //changes the given columns of a record. Larger data is changed in the data pages, only moving
+    //it if it no longer fits where it was. The record is written again with the current schema
+    //version.
+    pub fn modify_record(&mut self, record_id: Record_ID, changes: Vec<(String, Value)>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let mut new_values: Vec<(usize, Value)> = Vec::with_capacity(changes.len());
+
+        for (column_name, value) in changes{
+            let column_index = match self.column_schema.iter().position(|column| column.column_name == column_name){
+                None    => return Err(Table_error::Column_not_found(column_name)),
+                Some(n) => n,
+            };
+
+            let column = &self.column_schema[column_index];
+
+            if value == Value::Null{
+                if column.not_null{
+                    return Err(Table_error::Not_null_violation(column_name))
+                }
+            }else if column.data_type != value{
+                return Err(Table_error::Type_mismatch{column_name})
+            }
+
+            new_values.push((column_index, value));
+        }
+
+        //Find record location
+        let location = self.locate_record(record_id, page_table, file_manager)?;
+        let bytes = self.read_record_bytes(location, page_table, file_manager)?;
+
+        let (version, ranges) = match self.field_ranges(&bytes){
+            None    => return Err(Table_error::Corrupt_record(record_id)),
+            Some(r) => r,
+        };
+
+        //the record as it was and as it will be after the changes, for checking the primary key and
+        //unique columns and updating the indexes.
+        let key_columns = self.primary_key_columns();
+        let mut old_record = Vec::new();
+        let mut new_record = Vec::new();
+
+        if !key_columns.is_empty() || !self.indexes.is_empty() || self.column_schema.iter().any(|column| column.unique){
+            old_record = self.get_record(record_id, page_table, file_manager)?;
+            new_record = old_record.clone();
+
+            for (i, value) in new_values.iter(){
+                new_record[*i] = value.clone();
+            }
+
+            self.check_unique(&new_record, Some(record_id), page_table, file_manager)?;
+        }
+
+        //the new primary key has to be free, unless it is the record's own.
+        let key_changed = key_columns.iter().any(|i| old_record[*i] != new_record[*i]);
+
+        if key_changed && self.find_record(&self.key_values(&new_record), page_table, file_manager)?.is_some(){
+            return Err(Table_error::Unique_violation(self.primary_key_name()))
+        }
+
+        //the new data is written next to the old one, which is only removed once the record refers
+        //to the new data.
+        let mut changes = Data_changes::default();
+        let written = self.current_fields(&bytes, ranges, version, &mut changes, page_table, file_manager)
+            .and_then(|mut fields| {
+                for (column_index, value) in new_values.iter(){
+                    self.set_field(&mut fields, *column_index, value, &mut changes, page_table, file_manager)?;
+                }
+                return self.write_fields(record_id, &fields, page_table, file_manager)
+            });
+
+        self.settle_data_changes(changes, written.is_ok(), page_table, file_manager)?;
+        written?;
+
+        if key_changed{
+            self.remove_key(&old_record, page_table, file_manager)?;
+            self.insert_key(&new_record, record_id, page_table, file_manager)?;
+        }
+
+        for position in 0..self.indexes.len(){
+            if self.index_key(position, &old_record, record_id) != self.index_key(position, &new_record, record_id){
+                self.remove_index_key(position, &old_record, record_id, page_table, file_manager)?;
+                self.insert_index_key(position, &new_record, record_id, page_table, file_manager)?;
+            }
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //replaces the stored bytes of a column among the fields of a record. Larger data is written
+    //as new data, the data the column referred to before is left in changes to be freed.
+    fn set_field(&mut self, fields: &mut [Option<Vec<u8>>], column_index: usize, value: &Value, changes: &mut Data_changes, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let data_type = self.column_schema[column_index].data_type.clone();
+
+        if data_type.is_variable_size(){
+            if let Some(field) = &fields[column_index]{
+                changes.replaced.push(u32::from_be_bytes(field[..4].try_into().unwrap()));
+            }
+
+            if *value == Value::Null{
+                fields[column_index] = None;
+            }else{
+                let id = self.variable_data_manager.add_data(&data_type, &value.to_bytes(), page_table, file_manager)?;
+                changes.added.push(id);
+                fields[column_index] = Some(id.to_be_bytes().to_vec());
+            }
+        }else if *value == Value::Null{
+            fields[column_index] = None;
+        }else{
+            fields[column_index] = Some(value.to_bytes());
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //writes the fields of a record as its new bytes with the current schema version.
+    fn write_fields(&mut self, record_id: Record_ID, fields: &[Option<Vec<u8>>], page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let bytes = build_record_bytes(fields, self.schema_version);
+
+        //a record has to fit in a single page next to its record index entry.
+        if bytes.len() + 2 > record_page_capacity(page_table){
+            return Err(Table_error::Record_too_large{size: bytes.len()})
+        }
+
+        return self.write_record_bytes(record_id, bytes, page_table, file_manager)
+    }
+
+
+
+    //frees the data a record no longer refers to once its new bytes are written, or the data
+    //written for them if they could not be.
+    fn settle_data_changes(&mut self, changes: Data_changes, written: bool, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let unused = if written { changes.replaced } else { changes.added };
+
+        for id in unused{
+            self.variable_data_manager.remove_data(id, page_table, file_manager)?;
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //replaces the stored bytes of a record. If the new bytes do not fit in the record's page the
+    //record is moved to another page and a forwarding record is left behind.
+    fn write_record_bytes(&mut self, record_id: Record_ID, mut bytes: Vec<u8>, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let location = self.locate_record(record_id, page_table, file_manager)?;
+        let old_size = self.read_record_bytes(location, page_table, file_manager)?.len() as u16;
+
+        let new_size = std::cmp::max(bytes.len(), FORWARDING_RECORD_SIZE as usize) as u16;
+        bytes.resize(new_size as usize, 0);
+        bytes[0] = if location == record_id { 0 } else { RECORD_MOVED };
+        bytes[1..3].copy_from_slice(&new_size.to_be_bytes());
+
+        let block = Block_ID{file_name: self.table_name.clone(), number: location.page_num};
+        let page = match page_table.get_mut_page(block.clone(), file_manager){
+            None    => return Err(Table_error::Record_not_found(location)),
+            Some(p) => p,
+        };
+
+        let offset = page.get_record_index_entry(location.slot).unwrap_or(0);
+
+        //the same size, so the record is simply overwritten.
+        if new_size == old_size{
+            page.write(offset, bytes)?;
+            page_table.set_dirty(&block);
+            return Ok(1)
+        }
+
+        let free = page.record_index_end_point - page.data_end_point;
+
+        //still fits in its page, the record is taken out and written again at the end of the page's
+        //data under the same record index entry.
+        if new_size <= old_size + free{
+            page.remove_record_index_entry_data(location.slot, old_size);
+            let offset = page.data_end_point;
+            page.write(offset, bytes)?;
+            page.data_end_point += new_size;
+            page.set_record_index_entry(location.slot, offset);
+
+            let free = page.record_index_end_point - page.data_end_point;
+            page_table.set_dirty(&block);
+            self.set_record_free_space(location.page_num, free, page_table, file_manager)?;
+
+            return Ok(1)
+        }
+
+        //the record has to move to another page. The moved copy is written first, so the record
+        //is not lost if that fails.
+        bytes[0] = RECORD_MOVED;
+        let new_location = self.insert_record_bytes(bytes, page_table, file_manager)?;
+
+        let page = match page_table.get_mut_page(block.clone(), file_manager){
+            None    => return Err(Table_error::Record_not_found(location)),
+            Some(p) => p,
+        };
+
+        page.remove_record_index_entry_data(location.slot, old_size);
+        let free = page.record_index_end_point - page.data_end_point;
+        page_table.set_dirty(&block);
+        self.set_record_free_space(location.page_num, free, page_table, file_manager)?;
+
+        let mut forwarding_record: Vec<u8> = vec![RECORD_FORWARDED];
+        forwarding_record.extend_from_slice(&FORWARDING_RECORD_SIZE.to_be_bytes());
+        forwarding_record.extend_from_slice(&new_location.page_num.to_be_bytes());
+        forwarding_record.extend_from_slice(&new_location.slot.to_be_bytes());
+
+        let home_block = Block_ID{file_name: self.table_name.clone(), number: record_id.page_num};
+        let home_page = match page_table.get_mut_page(home_block.clone(), file_manager){
+            None    => return Err(Table_error::Record_not_found(record_id)),
+            Some(p) => p,
+        };
+
+        if location == record_id{
+            //the record was removed above and every record is at least as large as a forwarding
+            //record, so there is room for it.
+            let offset = home_page.data_end_point;
+            home_page.write(offset, forwarding_record)?;
+            home_page.data_end_point += FORWARDING_RECORD_SIZE;
+            home_page.set_record_index_entry(record_id.slot, offset);
+
+            let free = home_page.record_index_end_point - home_page.data_end_point;
+            page_table.set_dirty(&home_block);
+            self.set_record_free_space(record_id.page_num, free, page_table, file_manager)?;
+        }else{
+            //the record had been moved before, only its forwarding record needs to change.
+            let offset = home_page.get_record_index_entry(record_id.slot).unwrap_or(0);
+            home_page.write(offset, forwarding_record)?;
+            page_table.set_dirty(&home_block);
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //removes a record and the larger data it refers to. Its entry in the record index is left as
+    //a tombstone (0) so the Record_IDs of the other records in the page stay valid.
+    pub fn remove_record(&mut self, record_id: Record_ID, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let location = self.locate_record(record_id, page_table, file_manager)?;
+        let bytes = self.read_record_bytes(location, page_table, file_manager)?;
+
+        //the values the primary key and the indexes hold for the record.
+        let record = match self.primary_key_columns().is_empty() && self.indexes.is_empty(){
+            true    => Vec::new(),
+            false   => self.get_record(record_id, page_table, file_manager)?,
+        };
+
+        //First get a list of all larger data IDs
+        let mut data_ids: Vec<u32> = Vec::new();
+
+        let (_, ranges) = match self.field_ranges(&bytes){
+            None    => return Err(Table_error::Corrupt_record(record_id)),
+            Some(r) => r,
+        };
+
+        for (column, range) in self.column_history.iter().zip(ranges){
+            if let (true, Some((start, _))) = (column.data_type.is_variable_size(), range){
+                data_ids.push(u32::from_be_bytes(bytes[start .. start + 4].try_into().unwrap()));
+            }
+        }
+
+        //Then take the record out of the primary key and the indexes, so they never point at a
+        //record that is gone.
+        self.remove_key(&record, page_table, file_manager)?;
+        self.index_record(&record, record_id, false, page_table, file_manager)?;
+
+        //Then remove the record, and its forwarding record if it was moved.
+        self.remove_record_bytes(location, bytes.len() as u16, page_table, file_manager)?;
+
+        if location != record_id{
+            self.remove_record_bytes(record_id, FORWARDING_RECORD_SIZE, page_table, file_manager)?;
+        }
+
+        //Finally, go to data page and de-allocate space so that new data can go there.
+        for id in data_ids{
+            self.variable_data_manager.remove_data(id, page_table, file_manager)?;
+        }
+
+        return Ok(1)
+    }
+
+
+
+    //takes the bytes at a record index entry out of the page and shifts the data after them left
+    //to fill the place.
+    fn remove_record_bytes(&mut self, record_id: Record_ID, size: u16, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, Table_error>{
+        let block = Block_ID{file_name: self.table_name.clone(), number: record_id.page_num};
+        let page = match page_table.get_mut_page(block.clone(), file_manager){
+            None    => return Err(Table_error::Record_not_found(record_id)),
+            Some(p) => p,
+        };
+
+        page.remove_record_index_entry_data(record_id.slot, size);
+        let free = page.record_index_end_point - page.data_end_point;
+        page_table.set_dirty(&block);
+
+        self.set_record_free_space(record_id.page_num, free, page_table, file_manager)?;
+
+        return Ok(1)
+    }
+
+
+
+    //writes every page in memory to disk. The free space trackers are written as the free space
+    //changes, so the pages hold everything the table needs to be opened again.
+    pub fn checkpoint(&mut self, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+        return page_table.write_all(file_manager)
+    }
+
+
+
+
+
 }
 
 
 
-impl PartialEq<Data_type> for Value {
-    fn eq(&self, other: &Data_type) -> bool {
-        match self {
-            Value::Int(_)      => *other == Data_type::Int,
-            Value::Float(_)    => *other == Data_type::Float,
-            Value::String(_)   => *other == Data_type::String,
-            Value::Datetime(_) => *other == Data_type::Datetime,
-            Value::Date(_)     => *other == Data_type::Date,
-            Value::Time(_)     => *other == Data_type::Time,
-            Value::Bool(_)     => *other == Data_type::Bool,
-            Value::Enum(_)     => *other == Data_type::Enum,
-            Value::Blob(_)     => *other == Data_type::Blob,
-            Value::U32(_)      => *other == Data_type::U32,
-            Value::U16(_)      => *other == Data_Type::U16,
-        }
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::table::free_space_tracker::read_entries;
+    use crate::file_manager::file_manager::build_file_manager;
+    use crate::test_utils::{open_test_table, reopen_test_table, temp_directory, TEST_PAGE_SIZE};
+
+
+    //values of every type in ascending order.
+    fn sorted_values() -> Vec<(Data_type, Vec<Value>)>{
+        return vec![
+            (Data_type::Int, vec![i64::MIN, i64::MIN + 1, -256, -1, 0, 1, 255, 256, i64::MAX].into_iter().map(Value::Int).collect()),
+            (Data_type::Float, vec![f64::NEG_INFINITY, f64::MIN, -1.5, -f64::MIN_POSITIVE, 0.0, f64::MIN_POSITIVE, 1.0, 1.5, f64::MAX, f64::INFINITY].into_iter().map(Value::Float).collect()),
+            (Data_type::String, vec!["", "\0", "\0\0", "\0a", "a", "a\0", "a\0b", "aa", "b", "é"].into_iter().map(|s| Value::String(s.to_string())).collect()),
+            (Data_type::Enum, vec!["", "large", "medium", "small"].into_iter().map(|s| Value::Enum(s.to_string())).collect()),
+            (Data_type::Blob, vec![vec![], vec![0], vec![0, 0], vec![0, 255], vec![1], vec![255], vec![255, 0], vec![255, 255]].into_iter().map(Value::Blob).collect()),
+            (Data_type::Datetime, vec![0, 1, 1_700_000_000_000, u64::MAX].into_iter().map(Value::Datetime).collect()),
+            (Data_type::Date, vec![0, 1, 20240229, u32::MAX].into_iter().map(Value::Date).collect()),
+            (Data_type::Time, vec![0, 1, 235959, u32::MAX].into_iter().map(Value::Time).collect()),
+            (Data_type::Bool, vec![Value::Bool(false), Value::Bool(true)]),
+            (Data_type::U32, vec![0, 1, 256, u32::MAX].into_iter().map(Value::U32).collect()),
+            (Data_type::U16, vec![0, 1, 256, u16::MAX].into_iter().map(Value::U16).collect()),
+        ]
     }
-}
 
-impl Value{
 
-    fn size(&self) -> u8{
 
-        return match self{
-            Value::Int(_)
-            | Value::Float(_)
-            | Value::Datetime(_) => 8,
+    #[test]
+    fn key_round_trip(){
+        for (data_type, values) in sorted_values(){
+            for value in values.into_iter().chain([Value::Null]){
+                let key = value.encode_key();
+                assert_eq!(Value::decode_key(&data_type, &key), Some((value.clone(), key.len())), "{:?}", value);
+            }
+        }
+    }
 
-            Value::String(_) 
-            | Value::Enum(_) 
-            | Value::Blob(_)  => 6,
 
-            Value::Date(_) 
-            | Value::Time(_)
-            | Value::U32(_)   => 4,
 
-            Value::U16(_)  => 2,
+    #[test]
+    fn key_round_trip_nan(){
+        let key = Value::Float(f64::NAN).encode_key();
 
-            Value::Bool(_) => 1,
+        match Value::decode_key(&Data_type::Float, &key){
+            Some((Value::Float(f), 9))  => assert_eq!(f.to_bits(), f64::NAN.to_bits()),
+            other                       => panic!("{:?}", other),
         }
     }
 
 
-}
+
+    #[test]
+    fn negative_zero_has_the_key_of_zero(){
+        assert_eq!(Value::Float(-0.0).encode_key(), Value::Float(0.0).encode_key());
+    }
 
 
 
-#[repr(u8)]
-#[derive(Debug, Clone, PartialEq)]
-pub enum Data_type{
-    Int = 0,                    // 8 bytes.
-    Float = 1,                  // 8 bytes.
-    String = 3,                 // 6 bytes: 4 bytes page num 2 bytes index
-    Datetime = 4,               // 8 bytes.
-    Date = 5,                   // 4 bytes.
-    Time = 6,                   // 4 bytes.
-    Bool = 7,                   // 1 bytes.
-    Enum = 8,                   // 6 bytes: 4 bytes page num 2 bytes index
-    Blob = 9,                   // 6 bytes: 4 bytes page num 2 bytes index
-    U32  = 10,                  // 4 bytes.
-    U16  = 11,                  // 2 bytes
+    #[test]
+    fn key_order(){
+        for (data_type, values) in sorted_values(){
+            //null sorts before every value.
+            let keys: Vec<Vec<u8>> = [Value::Null].iter().chain(values.iter()).map(|value| value.encode_key()).collect();
 
-}
+            for pair in keys.windows(2){
+                assert!(pair[0] < pair[1], "{:?}: {:?} >= {:?}", data_type, pair[0], pair[1]);
+            }
+        }
+    }
 
 
 
+    #[test]
+    fn float_key_order_matches_total_cmp(){
+        let floats = [f64::NAN, -f64::NAN, f64::INFINITY, -0.0, 0.0, -1e-300, 1e-300, -2.5, 2.5, f64::MIN, f64::MAX];
 
+        //apart from -0.0, which has the key of 0.0.
+        let zero = |f: f64| if f == 0.0 { 0.0 } else { f };
 
-impl PartialEq<Value> for Data_type {
-    fn eq(&self, other: &Value) -> bool {
-        other == self
+        for a in floats{
+            for b in floats{
+                assert_eq!(Value::Float(a).encode_key().cmp(&Value::Float(b).encode_key()), zero(a).total_cmp(&zero(b)), "{} {}", a, b);
+            }
+        }
     }
-}
 
 
 
-impl std::convert::TryFrom<u8> for Data_type {
-    type Error = ();
+    #[test]
+    fn composite_key_order(){
+        //keys over a string and an int column sort by the string first, even when one string is
+        //the start of the other.
+        let records = [
+            (Value::Null, Value::Int(5)),
+            (Value::String("".to_string()), Value::Null),
+            (Value::String("".to_string()), Value::Int(i64::MAX)),
+            (Value::String("a".to_string()), Value::Int(-1)),
+            (Value::String("a".to_string()), Value::Int(0)),
+            (Value::String("a\0".to_string()), Value::Int(i64::MIN)),
+            (Value::String("ab".to_string()), Value::Null),
+        ];
 
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
-        match value {
-            0 => Ok(Data_type::Int),
-            1 => Ok(Data_type::Float),
-            3 => Ok(Data_type::String),
-            4 => Ok(Data_type::Datetime),
-            5 => Ok(Data_type::Date),
-            6 => Ok(Data_type::Time),
-            7 => Ok(Data_type::Bool),
-            8 => Ok(Data_type::Enum),
-            9 => Ok(Data_type::Blob),
-            10 => Ok(Data_type::U32),
-            11 => Ok(Data_type::U16),
-            _ => Err(()),
+        let keys: Vec<Vec<u8>> = records.iter().map(|(a, b)| [a.encode_key(), b.encode_key()].concat()).collect();
+
+        for pair in keys.windows(2){
+            assert!(pair[0] < pair[1]);
         }
-    }
-}
 
-pub fn open_table(name: String, file_manager: &mut File_manager, page_table: &mut Page_table) -> Option<Table>{
-    let mut table = Table::new(name, file_manager);
-    table.column_schema = table.parse_columns(page_table, file_manager);
+        for (key, (a, b)) in keys.iter().zip(records.iter()){
+            let (first, size) = Value::decode_key(&Data_type::String, key).unwrap();
+            let (second, rest) = Value::decode_key(&Data_type::Int, &key[size ..]).unwrap();
 
-    //load the correct starting page nums aswell (not important)
-    return Some(table);
-}
+            assert_eq!((&first, &second), (a, b));
+            assert_eq!(size + rest, key.len());
+        }
+    }
 
 
-//in future implement the method to ensure the file is locked and all pages of the file are also
-//locked while changes ar ebeing made.
 
-impl Table{
+    #[test]
+    fn decode_key_rejects_invalid_bytes(){
+        assert_eq!(Value::decode_key(&Data_type::Int, &[]), None);
+        assert_eq!(Value::decode_key(&Data_type::Int, &[2, 0, 0, 0, 0, 0, 0, 0, 0]), None);
+        assert_eq!(Value::decode_key(&Data_type::Int, &[KEY_VALUE, 0, 0]), None);
+        assert_eq!(Value::decode_key(&Data_type::U16, &[KEY_VALUE, 1]), None);
+        //an unterminated string, a 0 that is neither escaped nor the end, and invalid UTF-8.
+        assert_eq!(Value::decode_key(&Data_type::String, &[KEY_VALUE, b'a']), None);
+        assert_eq!(Value::decode_key(&Data_type::String, &[KEY_VALUE, b'a', 0, 1]), None);
+        assert_eq!(Value::decode_key(&Data_type::String, &[KEY_VALUE, 0xc3, 0, 0]), None);
+        assert_eq!(Value::decode_key(&Data_type::Blob, &[KEY_VALUE, 0xc3, 0, 0]), Some((Value::Blob(vec![0xc3]), 4)));
+    }
 
-    pub fn new(name: String, file_manager: &mut File_manager) -> Table{
-        let table = Table{
-            table_name: name,
-            column_schema: Vec::new(),
-            first_record_page_num:              3, //TEMP
-            b_tree_page_num:                    1, //TEMP
-            record_free_space_tracker_page_num: 2, //TEMP
-        };
-        //TODO CHECK IF FILE ALREADY EXISTS AND JUST LOAD THE DATA....
-       // table.init_file(file_manager);
 
-        return table
-    }
 
+    #[test]
+    fn records_fill_the_record_pages_in_turn(){
+        let (mut fm, mut pt, mut table) = open_test_table("record_pages");
+        table.add_column("n".to_string(), Data_type::Int, &mut pt, &mut fm).unwrap();
+        table.add_column("s".to_string(), Data_type::String, &mut pt, &mut fm).unwrap();
 
+        assert!(matches!(table.add_record(vec![Value::Int(0)], &mut pt, &mut fm), Err(Table_error::Column_count_mismatch{expected: 2, found: 1})));
+        assert!(matches!(table.add_record(vec![Value::Int(0), Value::Int(0)], &mut pt, &mut fm), Err(Table_error::Type_mismatch{..})));
 
-    pub fn init_file(&self, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+        //records of 17 bytes, a few pages of them.
+        for i in 0..2000{
+            table.add_record(vec![Value::Int(i), Value::String(format!("record {}", i))], &mut pt, &mut fm).unwrap();
+        }
 
-        //generating the initial pages
-        let mut init_pages: Vec<Page> = Vec::new();
-        let free_space: u16 = (1024 * 16) - 18;
+        //every record page is in the chain from the first one, and the free bytes tracked for it
+        //are the bytes between its records and its record index.
+        let tracked = read_entries(&table.table_name, table.record_free_space_tracker_page_num, &mut pt, &mut fm).unwrap();
+        let mut page_num = table.first_record_page_num;
+        let mut record_count = 0;
+        let mut page_count = 0;
 
-        init_pages.push(Page::new(file_manager.block_size, 0, Page_type::Table_structure));
-        init_pages.push(Page::new(file_manager.block_size, 1, Page_type::Free_space_tracker));
+        while page_num != 0{
+            let page = pt.get_mut_page(Block_ID{file_name: table.table_name.clone(), number: page_num}, &mut fm).unwrap();
+            let free = page.record_index_end_point - page.data_end_point;
 
-        let mut bytes: [u8;6]   = [0u8; 6];
-        let page_num: u32 = 4;
-        bytes[..4].copy_from_slice( &page_num.to_be_bytes()  );
-        bytes[4..].copy_from_slice( &free_space.to_be_bytes());
-        println!("1: {:?}", &bytes);
-        init_pages.last_mut().unwrap().write_at_end(bytes.to_vec());
+            record_count += page.get_record_index().len();
+            page_count += 1;
+            page_num = page.next_index.unwrap_or(0);
 
+            assert!(tracked.contains(&(page.page_num, free)));
+        }
 
-        init_pages.push(Page::new(file_manager.block_size, 2, Page_type::Free_space_tracker));
+        assert_eq!(record_count, 2000);
+        assert!(page_count >= 3);
+        assert_eq!(tracked.len(), page_count);
+    }
 
-        let page_num: u32 = 5;
-        bytes[..4].copy_from_slice( &page_num.to_be_bytes()  );
-        bytes[4..].copy_from_slice( &free_space.to_be_bytes());
-        println!("2: {:?}", &bytes);
-        init_pages.last_mut().unwrap().write_at_end(bytes.to_vec());
 
 
-        init_pages.push(Page::new(file_manager.block_size, 3, Page_type::B_tree));
-        init_pages.push(Page::new(file_manager.block_size, 4, Page_type::Record)); 
-        init_pages.push(Page::new(file_manager.block_size, 5, Page_type::Data));
-        
-        let file_name = self.table_name.clone();
+    #[test]
+    fn records_with_nulls_read_back_the_same(){
+        let (mut fm, mut pt, mut table) = open_test_table("null_bitmap");
 
-        for page in init_pages{
-            let res = file_manager.write(&Block_ID{file_name: file_name.clone(), number: page.page_num}, &page);
+        //more than 8 columns so the null bitmap takes two bytes.
+        let columns = [Data_type::Int, Data_type::String, Data_type::Float, Data_type::Blob, Data_type::Bool, Data_type::Date, Data_type::String, Data_type::U16, Data_type::Datetime, Data_type::Blob];
+        for (i, data_type) in columns.into_iter().enumerate(){
+            table.add_column(format!("c{}", i), data_type, &mut pt, &mut fm).unwrap();
+        }
 
-            if !res.is_ok(){
-                return Ok(1) // ERROR CHECKING & Cancel all changes
-            }
+        let records = [
+            vec![Value::Int(1), Value::Null, Value::Float(1.5), Value::Blob(vec![1, 2]), Value::Null, Value::Date(20240229), Value::String("x".to_string()), Value::Null, Value::Datetime(5), Value::Null],
+            vec![Value::Null, Value::String("text".to_string()), Value::Null, Value::Null, Value::Bool(true), Value::Null, Value::Null, Value::U16(7), Value::Null, Value::Blob(vec![])],
+            vec![Value::Null; 10],
+            vec![Value::Int(-1), Value::String(String::new()), Value::Float(0.0), Value::Blob(vec![0; 100]), Value::Bool(false), Value::Date(0), Value::String("y".repeat(300)), Value::U16(0), Value::Datetime(0), Value::Blob(vec![255])],
+        ];
+
+        let mut record_ids = Vec::new();
+        for record in records.iter(){
+            let record_id = table.add_record(record.clone(), &mut pt, &mut fm).unwrap().record_id;
+            assert_eq!(&table.get_record(record_id, &mut pt, &mut fm).unwrap(), record);
+            record_ids.push(record_id);
         }
 
-        return Ok(0)
+        let (mut fm, mut pt, table) = reopen_test_table("null_bitmap", fm, pt, table);
+
+        for (record_id, record) in record_ids.into_iter().zip(records.iter()){
+            assert_eq!(&table.get_record(record_id, &mut pt, &mut fm).unwrap(), record);
+        }
 
+        let missing = Record_ID{page_num: table.first_record_page_num, slot: 4};
+        assert!(matches!(table.get_record(missing, &mut pt, &mut fm), Err(Table_error::Record_not_found(_))));
     }
 
 
 
+    //the ID of the larger data a column of a record refers to.
+    fn data_id(table: &Table, record_id: Record_ID, column_index: usize, page_table: &mut Page_table, file_manager: &mut File_manager) -> u32{
+        let location = table.locate_record(record_id, page_table, file_manager).unwrap();
+        let bytes = table.read_record_bytes(location, page_table, file_manager).unwrap();
+        let (start, _) = table.field_ranges(&bytes).unwrap().1[column_index].unwrap();
 
-    pub fn add_column(&self, name: String, data_type: Data_type, page_table: &mut Page_table,file_manager: &mut File_manager){
-        
-        let block = Block_ID{file_name: self.table_name.clone(), number: 0};
+        return u32::from_be_bytes(bytes[start .. start + 4].try_into().unwrap())
+    }
 
 
-        let page = page_table.get_mut_page(block, file_manager).unwrap();
 
+    #[test]
+    fn removed_records_are_gone_and_their_slot_is_reused(){
+        let (mut fm, mut pt, mut table) = open_test_table("remove_record");
+        let mut id = Column::new("id".to_string(), Data_type::Int);
+        id.primary_key = true;
+        table.add_column_definition(id, &mut pt, &mut fm).unwrap();
+        table.add_column("name".to_string(), Data_type::String, &mut pt, &mut fm).unwrap();
 
-        //writing the column to the data section
+        let record = |i: i64| vec![Value::Int(i), Value::String(format!("name {}", i))];
 
+        let record_ids: Vec<Record_ID> = (0..3).map(|i| table.add_record(record(i), &mut pt, &mut fm).unwrap().record_id).collect();
+        let removed_data_id = data_id(&table, record_ids[1], 1, &mut pt, &mut fm);
 
-        let column_name_byte_num = name.len();
+        table.remove_record(record_ids[1], &mut pt, &mut fm).unwrap();
 
-        if column_name_byte_num > 255{todo!()};        //too long name error.
+        match table.get_record(record_ids[1], &mut pt, &mut fm){
+            Err(Table_error::Record_not_found(record_id))   => assert_eq!(record_id, record_ids[1]),
+            other                                           => panic!("{:?}", other),
+        }
+        assert_eq!(table.find_record(&[Value::Int(1)], &mut pt, &mut fm).unwrap(), None);
+        assert!(table.variable_data_manager.get_data(removed_data_id, &mut pt, &mut fm).is_err());
 
-        let column_name_bytes = name.into_bytes();
+        //the next record takes the empty slot, and the records around it are untouched.
+        assert_eq!(table.add_record(record(3), &mut pt, &mut fm).unwrap().record_id, record_ids[1]);
 
-        // 4 bytes for next page pointer                // 2 bytes for col type and col name size
-        
-        if ((page.record_index_end_point + 4) - page.data_end_point) < (column_name_byte_num + 2) as u16 {
+        for (record_id, i) in [(record_ids[0], 0), (record_ids[1], 3), (record_ids[2], 2)]{
+            assert_eq!(table.get_record(record_id, &mut pt, &mut fm).unwrap(), record(i));
+        }
+    }
 
-            //create new page
 
 
+    #[test]
+    fn modify_record_replaces_variable_data(){
+        let (mut fm, mut pt, mut table) = open_test_table("modify_variable_data");
+        table.add_column("n".to_string(), Data_type::Int, &mut pt, &mut fm).unwrap();
+        table.add_column("name".to_string(), Data_type::String, &mut pt, &mut fm).unwrap();
 
-        } 
-                                                                                                      
-        page.bytes[(page.data_end_point + 1) as usize]  =   data_type as u8;
-        page.bytes[(page.data_end_point + 2) as usize]  = column_name_byte_num  as u8;
+        let record_id = table.add_record(vec![Value::Int(1), Value::String("first".to_string())], &mut pt, &mut fm).unwrap().record_id;
+        let old_id = data_id(&table, record_id, 1, &mut pt, &mut fm);
 
+        table.modify_record(record_id, vec![("name".to_string(), Value::String("second".to_string())), ("n".to_string(), Value::Int(2))], &mut pt, &mut fm).unwrap();
 
-        //let dst = &mut page.bytes[(page.data_end_point + 3) as usize .. (page.data_end_point + 3) as usize + column_name_byte_num];
-        //let src = &column_name_bytes;
+        assert_eq!(table.get_record(record_id, &mut pt, &mut fm).unwrap(), vec![Value::Int(2), Value::String("second".to_string())]);
+        assert!(table.variable_data_manager.get_data(old_id, &mut pt, &mut fm).is_err());
 
-        //dst.copy_from_slice(src);
+        //a change that cannot be made leaves the record as it was.
+        let new_id = data_id(&table, record_id, 1, &mut pt, &mut fm);
+        assert!(matches!(table.modify_record(record_id, vec![("name".to_string(), Value::String("third".to_string())), ("n".to_string(), Value::Bool(true))], &mut pt, &mut fm), Err(Table_error::Type_mismatch{..})));
+        assert!(matches!(table.modify_record(record_id, vec![("missing".to_string(), Value::Int(0))], &mut pt, &mut fm), Err(Table_error::Column_not_found(_))));
 
+        assert_eq!(table.get_record(record_id, &mut pt, &mut fm).unwrap(), vec![Value::Int(2), Value::String("second".to_string())]);
+        assert_eq!(data_id(&table, record_id, 1, &mut pt, &mut fm), new_id);
+    }
+
+
+
+    #[test]
+    fn record_pages_with_space_are_reused(){
+        let (mut fm, mut pt, mut table) = open_test_table("record_page_reuse");
+        table.add_column("n".to_string(), Data_type::Int, &mut pt, &mut fm).unwrap();
 
-        let _ = page.write(page.data_end_point + 3, column_name_bytes);
+        let mut record_ids = Vec::new();
+        for i in 0..3000{
+            record_ids.push(table.add_record(vec![Value::Int(i)], &mut pt, &mut fm).unwrap().record_id);
+        }
+        let largest_page = pt.largest_page_map["record_page_reuse"];
 
+        for record_id in record_ids{
+            table.remove_record(record_id, &mut pt, &mut fm).unwrap();
+        }
 
+        //the free bytes are read back from the tracker when the table is opened again.
+        let mut table = open_table("record_page_reuse".to_string(), &mut fm, &mut pt).unwrap();
 
-        //updating page meta data
-        page.add_record_index(page.data_end_point + 1);
+        let mut record_ids = Vec::new();
+        for i in 0..3000{
+            record_ids.push(table.add_record(vec![Value::Int(i)], &mut pt, &mut fm).unwrap().record_id);
+        }
 
-        page.data_end_point = page.data_end_point + 2 + column_name_byte_num as u16;
+        assert_eq!(pt.largest_page_map["record_page_reuse"], largest_page);
 
+        for (i, record_id) in record_ids.into_iter().enumerate(){
+            assert_eq!(table.get_record(record_id, &mut pt, &mut fm).unwrap(), vec![Value::Int(i as i64)]);
+        }
     }
 
 
 
-//TODO test and fix going to other pages
-    pub fn find_column_index(&self, name: String, page_table: &mut Page_table, file_manager: &mut File_manager ) -> Option<(u32, u16)>{
+    #[test]
+    fn root_page_nums_survive_a_reopen(){
+        let (mut fm, mut pt, mut table) = open_test_table("root_pages");
+        table.add_column("id".to_string(), Data_type::Int, &mut pt, &mut fm).unwrap();
+        table.add_column("text".to_string(), Data_type::String, &mut pt, &mut fm).unwrap();
 
-        //get byte length of the name then linearly search through the columns and find one which
-        //matches the byte length number. Then check if the names are the same.
-        let column_name_bytes_num = name.len() as u8;
-    
-        let block = Block_ID{file_name: self.table_name.clone() ,number: 0};
+        //enough records to fill several record pages.
+        let record_ids: Vec<Record_ID> = (0..5000).map(|i| table.add_record(vec![Value::Int(i), Value::String(format!("record {}", i))], &mut pt, &mut fm).unwrap().record_id).collect();
 
-        let mut page = match page_table.get_mut_page(block, file_manager){
-                        None    => return None,
-                        Some(p) => p,
-                    };
-        //println!("{:?}", column_name_bytes_num);
-        //println!("{:?}", page.get_record_index());
+        //a new B-tree root, as after the root splits.
+        let root = allocate_page("root_pages", Page_type::B_tree, &mut pt, &mut fm).unwrap();
+        table.set_b_tree_page_num(root, &mut pt, &mut fm).unwrap();
 
-        
+        let root_pages = (table.first_record_page_num, table.b_tree_page_num, table.record_free_space_tracker_page_num, table.first_data_page_num, table.data_free_space_tracker_page_num);
 
-        let indexes = page.get_record_index();
+        let (mut fm, mut pt, table) = reopen_test_table("root_pages", fm, pt, table);
 
-        let page_num = 0;
+        assert_eq!((table.first_record_page_num, table.b_tree_page_num, table.record_free_space_tracker_page_num, table.first_data_page_num, table.data_free_space_tracker_page_num), root_pages);
 
-        for index in indexes{
-            //println!("index: {:?}", index);
-            if index > page.record_index_end_point - 2 {
-                let next_page_bytes = &page.bytes[ (page.record_index_end_point-4) as usize .. (page.record_index_end_point) as usize ];
+        for i in [0, 2500, 4999]{
+            assert_eq!(table.get_record(record_ids[i], &mut pt, &mut fm).unwrap(), vec![Value::Int(i as i64), Value::String(format!("record {}", i))]);
+        }
+    }
 
-                let page_num = (( next_page_bytes[0] as u32) << 24)
-                             | (( next_page_bytes[1] as u32) << 16)
-                             | (( next_page_bytes[2] as u32) << 8 )
-                             |  ( next_page_bytes[3] as u32);
 
-                if page_num == 0{
-                    return None
-                }
 
+    #[test]
+    fn open_or_create_rejects_a_file_that_is_not_a_table(){
+        let directory = temp_directory("not_a_table");
+        let text = "this is not a table\n".repeat(TEST_PAGE_SIZE as usize);
+        std::fs::write(format!("{}/not_a_table", directory), &text.as_bytes()[.. TEST_PAGE_SIZE as usize * 2]).unwrap();
 
-                let block = Block_ID{file_name: self.table_name.clone(), number: page_num};
+        let mut fm = build_file_manager(TEST_PAGE_SIZE, directory);
+        let mut pt = Page_table::new(TEST_PAGE_SIZE as u32 * 16, TEST_PAGE_SIZE, vec!["not_a_table".to_string()], &mut fm);
 
-                page = match page_table.get_mut_page(block, file_manager){
-                                None    => return None,
-                                Some(p) => p
-                            };
+        match Table::open_or_create("not_a_table".to_string(), &mut pt, &mut fm){
+            Err(Table_error::Invalid_table_file(_)) => (),
+            other                                   => panic!("{:?}", other.map(|table| table.table_name)),
+        }
+    }
 
 
-            }
-           // println!("size: {:?} \n", &page.bytes[index as usize +1]);
 
-            if page.bytes[index as usize + 1] == column_name_bytes_num {
+    #[test]
+    fn schemas_spread_over_several_pages_can_be_changed(){
+        let (mut fm, mut pt, mut table) = open_test_table("long_schema");
+        let name = |i: usize| format!("{:0>200}", i);
 
-                //println!("AA");
-                let name_bytes = &page.bytes[(index+2) as usize .. (index+2+column_name_bytes_num as u16) as usize ];
+        for i in 0..320{
+            table.add_column(name(i), Data_type::Int, &mut pt, &mut fm).unwrap();
+        }
 
-                let s = String::from_utf8(name_bytes.to_vec()).unwrap();
+        //the first column on each schema page after page 0.
+        let pages: Vec<u32> = (0..320).map(|i| table.find_column_index(name(i), &mut pt, &mut fm).unwrap().0).collect();
+        let page_starts: Vec<usize> = (1..pages.len()).filter(|i| pages[*i] != pages[i - 1]).collect();
+        assert!(page_starts.len() >= 3);
 
-                //println!("{:?}", s);
-                //println!("{:?}", name);
+        let mut expected: Vec<String> = (0..320).map(name).collect();
 
-                if s == name{
-                    return Some( (page_num, index) ) // index -1 because the start of the column
-                                                       // is 1 before the stringname size.
-                }
+        //the columns on both sides of a page boundary are renamed, so their entries change size,
+        //and the next column is dropped.
+        for start in page_starts.iter().rev(){
+            for i in [start - 1, *start]{
+                let new_name = format!("renamed {}", "x".repeat(240 - i % 50));
+                table.modify_column_name(expected[i].clone(), new_name.clone(), &mut pt, &mut fm).unwrap();
+                expected[i] = new_name;
             }
 
-        };
+            table.remove_column(expected[start + 1].clone(), &mut pt, &mut fm).unwrap();
+            expected.remove(start + 1);
+        }
 
-        return None
+        let (_, _, table) = reopen_test_table("long_schema", fm, pt, table);
+
+        assert_eq!(table.column_schema.iter().map(|column| column.column_name.clone()).collect::<Vec<_>>(), expected);
     }
 
 
 
+    #[test]
+    fn constraints_are_checked_before_a_record_is_written(){
+        let (mut fm, mut pt, mut table) = open_test_table("constraints");
 
-    pub fn remove_column(&self, name: String, page_table: &mut Page_table, file_manager: &mut File_manager){
-        let location = self.find_column_index(name, page_table, file_manager).unwrap();
+        let mut email = Column::new("email".to_string(), Data_type::String);
+        email.unique = true;
+        email.not_null = true;
+        let mut score = Column::new("score".to_string(), Data_type::Int);
+        score.default = Some(Value::Int(7));
+        table.add_column_definition(email, &mut pt, &mut fm).unwrap();
+        table.add_column_definition(score, &mut pt, &mut fm).unwrap();
 
-        let block = Block_ID{file_name: self.table_name.clone(), number: location.0};
+        let mut bad_default = Column::new("bad".to_string(), Data_type::Int);
+        bad_default.default = Some(Value::String("seven".to_string()));
+        match table.add_column_definition(bad_default, &mut pt, &mut fm){
+            Err(Table_error::Invalid_default(column_name))  => assert_eq!(column_name, "bad"),
+            other                                           => panic!("{:?}", other),
+        }
 
-        let page = match page_table.get_mut_page(block, file_manager){
-                        None    => return (),
-                        Some(p) => p,
-        };
+        let (mut fm, mut pt, mut table) = reopen_test_table("constraints", fm, pt, table);
+        let email = |text: &str| Value::String(text.to_string());
+
+        let first = table.add_named_record(vec![("email".to_string(), email("a@example.com"))], &mut pt, &mut fm).unwrap().record_id;
+        assert_eq!(table.get_record(first, &mut pt, &mut fm).unwrap(), vec![email("a@example.com"), Value::Int(7)]);
 
+        match table.add_named_record(vec![("score".to_string(), Value::Int(1))], &mut pt, &mut fm){
+            Err(Table_error::Not_null_violation(column_name))   => assert_eq!(column_name, "email"),
+            other                                               => panic!("{:?}", other),
+        }
 
-        let start_index = location.1;
-        let string_byte_num = page.bytes[ (start_index + 1) as usize];
-        let end_index = start_index + 2 + string_byte_num as u16;
+        let second = table.add_record(vec![email("b@example.com"), Value::Int(2)], &mut pt, &mut fm).unwrap().record_id;
 
-        
-        if end_index > page.data_end_point{
-            page.remove_data_range(start_index, page.data_end_point - 1);
-        }else{
-            page.remove_data_range(start_index, end_index);
+        match table.add_record(vec![email("a@example.com"), Value::Int(3)], &mut pt, &mut fm){
+            Err(Table_error::Unique_violation(column_name)) => assert_eq!(column_name, "email"),
+            other                                           => panic!("{:?}", other),
         }
-        
-        page.update_records_after(start_index, end_index - start_index, false);
-        page.remove_record_index(start_index);
 
+        match table.modify_record(second, vec![("email".to_string(), email("a@example.com"))], &mut pt, &mut fm){
+            Err(Table_error::Unique_violation(column_name)) => assert_eq!(column_name, "email"),
+            other                                           => panic!("{:?}", other),
+        }
 
+        //a record keeps its own value.
+        table.modify_record(first, vec![("email".to_string(), email("a@example.com")), ("score".to_string(), Value::Int(8))], &mut pt, &mut fm).unwrap();
+        assert_eq!(table.record_ids(&mut pt, &mut fm).unwrap(), vec![first, second]);
+        assert_eq!(table.get_record(second, &mut pt, &mut fm).unwrap(), vec![email("b@example.com"), Value::Int(2)]);
     }
 
 
 
+    #[test]
+    fn modify_record_rejects_records_too_large(){
+        let (mut fm, mut pt, mut table) = open_test_table("modify_too_large");
 
-    pub fn modify_column_name(&self, old_name: String, new_name: String, page_table: &mut Page_table, file_manager: &mut File_manager){
-        
-        let location = match self.find_column_index(old_name.clone(), page_table, file_manager){
-                        None    => {
-                            return ()}, // update
-                        Some(n) => n,
-        };
+        //null values take no space, so the record is only too large once they are set.
+        let mut columns = vec![Column::new("name".to_string(), Data_type::String)];
+        for i in 0..2100{
+            columns.push(Column::new(format!("c{}", i), Data_type::Int));
+        }
+        table.write_schema(columns, &mut pt, &mut fm).unwrap();
 
-        let block = Block_ID{file_name: self.table_name.clone(), number: 0};
-        let page = page_table.get_mut_page(block, file_manager).unwrap();
+        let mut record = vec![Value::String("first".to_string())];
+        record.resize(2101, Value::Null);
+        let record_id = table.add_record(record.clone(), &mut pt, &mut fm).unwrap().record_id;
+        let old_id = data_id(&table, record_id, 0, &mut pt, &mut fm);
 
-        //if the string size is > to original then shift everything after the end of the string
-        //enough places and then overwrite
-        //but if the bytes requireed exceeds the data heap then if there is no next page, create
-        //new one 
-        //otherwise find the existing page and try to add.
-        
-        let start_index = location.1;
-        let string_bytes_num = page.bytes[ (start_index + 1) as usize];
-        let end_index = start_index + 2 + string_bytes_num as u16;
+        let mut changes = vec![("name".to_string(), Value::String("second".to_string()))];
+        changes.extend((0..2100).map(|i| (format!("c{}", i), Value::Int(i))));
+
+        match table.modify_record(record_id, changes, &mut pt, &mut fm){
+            Err(Table_error::Record_too_large{..})  => (),
+            other                                   => panic!("{:?}", other),
+        }
 
-        if new_name.len() <= old_name.len(){
-            //let dst* = &mut page.bytes[2 + start_index as usize .. 2 +start_index as usize + new_name.len()];
-            //let src = new_name.as_bytes();
+        //the record still refers to its old data, and the data written for the change is gone.
+        assert_eq!(table.get_record(record_id, &mut pt, &mut fm).unwrap(), record);
+        assert_eq!(data_id(&table, record_id, 0, &mut pt, &mut fm), old_id);
+        assert_eq!(table.variable_data_manager.add_data(&Data_type::String, b"third", &mut pt, &mut fm).unwrap(), old_id + 1);
+    }
 
-            //dst.copy_from_slice(src);
 
-            let _ = page.write( 2 + start_index, new_name.as_bytes().to_vec());
 
-            page.remove_data_range(start_index + 2 + new_name.len() as u16, end_index);
+    #[test]
+    fn records_that_grow_out_of_their_page_are_moved(){
+        let (mut fm, mut pt, mut table) = open_test_table("move_record");
+        for i in 0..100{
+            table.add_column(format!("c{}", i), Data_type::Int, &mut pt, &mut fm).unwrap();
+        }
 
-            page.update_records_after(start_index, (old_name.len() - new_name.len()) as u16 , false);
+        //records of null values only, enough to fill the first record page.
+        let record_ids: Vec<Record_ID> = (0..1000).map(|_| table.add_record(vec![Value::Null; 100], &mut pt, &mut fm).unwrap().record_id).collect();
+        let record_id = record_ids[0];
 
-        }else{
+        let changes = |n: i64| (0..100).map(|i| (format!("c{}", i), Value::Int(n + i))).collect::<Vec<_>>();
+        let values = |n: i64| (0..100).map(|i| Value::Int(n + i)).collect::<Vec<_>>();
 
-            let bytes_to_add_number = new_name.len() - old_name.len();
-            if page.data_end_point + bytes_to_add_number as u16 >= page.record_index_end_point{
-                //TODO make a new page and insert remaining stuff in there like a linked list.
-            }
+        table.modify_record(record_id, changes(0), &mut pt, &mut fm).unwrap();
+
+        assert_ne!(table.locate_record(record_id, &mut pt, &mut fm).unwrap(), record_id);
+        assert_eq!(table.get_record(record_id, &mut pt, &mut fm).unwrap(), values(0));
+
+        //a moved record is changed in its new place and still found through its Record_ID.
+        table.modify_record(record_id, changes(1000), &mut pt, &mut fm).unwrap();
+        assert_eq!(table.get_record(record_id, &mut pt, &mut fm).unwrap(), values(1000));
+        assert_eq!(table.get_record(record_ids[1], &mut pt, &mut fm).unwrap(), vec![Value::Null; 100]);
+
+        let (mut fm, mut pt, mut table) = reopen_test_table("move_record", fm, pt, table);
+        assert_eq!(table.get_record(record_id, &mut pt, &mut fm).unwrap(), values(1000));
+
+        table.remove_record(record_id, &mut pt, &mut fm).unwrap();
+        assert_eq!(table.record_ids(&mut pt, &mut fm).unwrap().len(), 999);
+    }
+
+
+
+    //the name, type and versions of every schema entry.
+    fn history(table: &Table) -> Vec<(String, Data_type, u32, u32)>{
+        return table.column_history.iter().map(|column| (column.column_name.clone(), column.data_type.clone(), column.added_version, column.dropped_version)).collect()
+    }
+
+
+
+    #[test]
+    fn modify_column_type_keeps_the_schema_history(){
+        let (mut fm, mut pt, mut table) = open_test_table("modify_type_history");
+        table.add_column("a".to_string(), Data_type::Int, &mut pt, &mut fm).unwrap();
+        table.add_column("b".to_string(), Data_type::String, &mut pt, &mut fm).unwrap();
 
-            page.bytes.copy_within(end_index as usize .. page.data_end_point as usize + 1, end_index as usize + bytes_to_add_number );
+        let record_id = table.add_record(vec![Value::Int(3), Value::String("x".to_string())], &mut pt, &mut fm).unwrap().record_id;
+        table.remove_column("b".to_string(), &mut pt, &mut fm).unwrap();
+        let version = table.schema_version;
 
-            //let dst* = &mut page.bytes[start_index as usize + 2 .. start_index as usize + 2 + new_name.len()];
-            //let src = new_name.as_bytes();
-            //dst.copy_from_slice(src);
+        table.modify_column_type("a".to_string(), Data_type::Float, &mut pt, &mut fm).unwrap();
 
-            let _ = page.write(start_index + 2, new_name.as_bytes().to_vec()); //ERROR HANDLING
+        let expected = vec![
+            ("a".to_string(), Data_type::Int, 1, version + 1),
+            ("a".to_string(), Data_type::Float, version + 1, 0),
+            ("b".to_string(), Data_type::String, 2, version),
+        ];
+        assert_eq!(history(&table), expected);
+        assert_eq!(table.get_record(record_id, &mut pt, &mut fm).unwrap(), vec![Value::Float(3.0)]);
 
+        let mut table = Table::open("modify_type_history".to_string(), &mut pt, &mut fm).unwrap();
+        assert_eq!(history(&table), expected);
+        assert_eq!(table.get_record(record_id, &mut pt, &mut fm).unwrap(), vec![Value::Float(3.0)]);
 
-            page.update_records_after(start_index, bytes_to_add_number as u16, true);
-            page.data_end_point += bytes_to_add_number as u16;
+        //once every record is written with the current version the dropped entries go.
+        assert_eq!(table.rewrite_old_records(10, &mut pt, &mut fm).unwrap(), 0);
+        assert_eq!(history(&table), vec![("a".to_string(), Data_type::Float, version + 1, 0)]);
+    }
+
+
+
+    #[test]
+    fn modify_column_type_changes_nothing_when_a_value_cannot_be_converted(){
+        let (mut fm, mut pt, mut table) = open_test_table("modify_type_unconverted");
+        table.add_column("id".to_string(), Data_type::Int, &mut pt, &mut fm).unwrap();
+        table.add_column("name".to_string(), Data_type::String, &mut pt, &mut fm).unwrap();
+
+        let records = vec![
+            vec![Value::Int(1), Value::String("12".to_string())],
+            vec![Value::Int(2), Value::String("twelve".to_string())],
+        ];
+        let record_ids: Vec<Record_ID> = records.iter().map(|record| table.add_record(record.clone(), &mut pt, &mut fm).unwrap().record_id).collect();
+
+        let (version, schema) = (table.schema_version, history(&table));
+
+        match table.modify_column_type("name".to_string(), Data_type::Int, &mut pt, &mut fm){
+            Err(Table_error::Incompatible_type_change{column_name}) => assert_eq!(column_name, "name"),
+            other                                                   => panic!("{:?}", other),
         }
 
-        page.bytes[start_index as usize + 1] = new_name.len() as u8;
-        
+        let table = Table::open("modify_type_unconverted".to_string(), &mut pt, &mut fm).unwrap();
+        assert_eq!((table.schema_version, history(&table)), (version, schema));
+
+        for (record_id, record) in record_ids.into_iter().zip(records){
+            assert_eq!(table.get_record(record_id, &mut pt, &mut fm).unwrap(), record);
+        }
     }
 
 
 
-    pub fn modify_column_type(&self, name: String, new_type: Data_type, page_table: &mut Page_table, file_manager: &mut File_manager){
-        let location = self.find_column_index(name, page_table, file_manager).unwrap();
-        let block    = Block_ID{file_name: self.table_name.clone(), number: location.0};
-        let page = page_table.get_mut_page(block, file_manager).unwrap();
-        
-        page.bytes[location.1 as usize] = new_type as u8;
-        
+    #[test]
+    fn modify_column_type_changes_nothing_when_a_record_would_be_too_large(){
+        let (mut fm, mut pt, mut table) = open_test_table("modify_type_too_large");
+
+        //a record of all the columns only just fits in a page, 6 bytes more and it does not.
+        let mut columns = vec![Column::new("n".to_string(), Data_type::U16)];
+        columns.extend((0..2012).map(|i| Column::new(format!("c{}", i), Data_type::Int)));
+        columns.extend((0..3).map(|i| Column::new(format!("b{}", i), Data_type::Bool)));
+        table.write_schema(columns, &mut pt, &mut fm).unwrap();
+
+        let mut small = vec![Value::U16(1)];
+        small.resize(2016, Value::Null);
+        let mut large = vec![Value::U16(2)];
+        large.extend((0..2012).map(Value::Int));
+        large.resize(2016, Value::Bool(true));
+
+        //the small record comes first so it could be converted before the large one fails.
+        let small_id = table.add_record(small.clone(), &mut pt, &mut fm).unwrap().record_id;
+        let large_id = table.add_record(large.clone(), &mut pt, &mut fm).unwrap().record_id;
+
+        let (version, schema) = (table.schema_version, history(&table));
+
+        match table.modify_column_type("n".to_string(), Data_type::Int, &mut pt, &mut fm){
+            Err(Table_error::Record_too_large{..})  => (),
+            other                                   => panic!("{:?}", other),
+        }
+
+        let table = Table::open("modify_type_too_large".to_string(), &mut pt, &mut fm).unwrap();
+        assert_eq!((table.schema_version, history(&table)), (version, schema));
+        assert_eq!(table.get_record(small_id, &mut pt, &mut fm).unwrap(), small);
+        assert_eq!(table.get_record(large_id, &mut pt, &mut fm).unwrap(), large);
     }
 
-    pub fn parse_columns(&self, page_table: &mut Page_table, file_manager: &mut File_manager) -> Vec<Column>{
-        let page = page_table.get_mut_page(Block_ID{file_name: self.table_name.clone(), number: 0}, file_manager).unwrap();
-        let indexes = page.get_record_index();
-        let mut count = 0;
-        let mut column_vector = Vec::new();
-        for index in indexes.iter().rev(){
-            count += 1;
-            let t = page.bytes[*index as usize];
-            let string_size_bytes = page.bytes[*index as usize + 1];
-            let string = std::str::from_utf8(&page.bytes[(*index + 2) as usize .. (*index as usize + 2 + string_size_bytes as usize)]).unwrap();
-            column_vector.push(Column{column_name: string.to_string().clone(), data_type: Data_type::try_from(t.clone()).unwrap()})
+
+
+    #[test]
+    fn select_through_an_index_matches_a_scan(){
+        let (mut fm, mut pt, mut table) = open_test_table("select_index_scan");
+        table.add_column("f".to_string(), Data_type::Float, &mut pt, &mut fm).unwrap();
+        table.add_column("n".to_string(), Data_type::U16, &mut pt, &mut fm).unwrap();
+
+        for (f, n) in [(-0.0, 0), (0.0, 1), (1.0, u16::MAX)]{
+            table.add_record(vec![Value::Float(f), Value::U16(n)], &mut pt, &mut fm).unwrap();
         }
 
-        return column_vector
+        let predicates = [
+            Predicate::Equal("f".to_string(), Value::Float(0.0)),
+            Predicate::Equal("f".to_string(), Value::Float(-0.0)),
+            Predicate::Less("n".to_string(), Value::Int(70000)),
+            Predicate::Greater("n".to_string(), Value::Int(-1)),
+            Predicate::Between("n".to_string(), Value::Int(1), Value::Int(70000)),
+            Predicate::In("n".to_string(), vec![Value::Int(70000), Value::Int(0)]),
+        ];
+        let counts = [2, 2, 3, 3, 2, 1];
+
+        let select = |table: &Table, pt: &mut Page_table, fm: &mut File_manager| -> Vec<usize> {
+            return predicates.iter().map(|predicate| table.select(&[], Some(predicate), pt, fm).unwrap().len()).collect()
+        };
 
-        //now check if we need to go to other page.
+        assert_eq!(select(&table, &mut pt, &mut fm), counts);
 
+        table.create_index(vec!["f".to_string()], &mut pt, &mut fm).unwrap();
+        table.create_index(vec!["n".to_string()], &mut pt, &mut fm).unwrap();
+        assert_eq!(select(&table, &mut pt, &mut fm), counts);
     }
 
 
 
+    #[test]
+    fn rejected_records_use_up_no_generated_key(){
+        let (mut fm, mut pt, mut table) = open_test_table("generated_keys");
+        let mut id = Column::new("id".to_string(), Data_type::Int);
+        id.primary_key = true;
+        let mut name = Column::new("name".to_string(), Data_type::String);
+        name.not_null = true;
+        name.unique = true;
+        table.add_column_definition(id, &mut pt, &mut fm).unwrap();
+        table.add_column_definition(name, &mut pt, &mut fm).unwrap();
 
-    pub fn print_columns_2(&self, page_table: &mut Page_table, file_manager: &mut File_manager){ 
+        let name = |text: &str| Value::String(text.to_string());
 
-        let page = page_table.get_mut_page(Block_ID{file_name: self.table_name.clone(), number: 0}, file_manager).unwrap();
+        assert_eq!(table.add_record(vec![Value::Null, name("a")], &mut pt, &mut fm).unwrap().key, vec![Value::Int(1)]);
+        assert!(table.add_record(vec![Value::Null, Value::Null], &mut pt, &mut fm).is_err());
+        assert!(table.add_record(vec![Value::Null, name("a")], &mut pt, &mut fm).is_err());
+        assert_eq!(table.add_record(vec![Value::Null, name("b")], &mut pt, &mut fm).unwrap().key, vec![Value::Int(2)]);
 
-        let indexes = page.get_record_index();
+        //the sequence continues after a larger key given explicitly, and is kept in the table file.
+        table.add_record(vec![Value::Int(10), name("c")], &mut pt, &mut fm).unwrap();
+        let mut table = Table::open("generated_keys".to_string(), &mut pt, &mut fm).unwrap();
+        assert_eq!(table.add_record(vec![Value::Null, name("d")], &mut pt, &mut fm).unwrap().key, vec![Value::Int(11)]);
+    }
 
-        let mut count = 0;
 
-        for index in indexes.iter().rev(){
-            count += 1;
-            let t = page.bytes[*index as usize];
-            let string_size_bytes = page.bytes[*index as usize + 1];
-            let string = std::str::from_utf8(&page.bytes[(*index + 2) as usize .. (*index as usize + 2 + string_size_bytes as usize)]).unwrap();
-            println!("Number: {} \t | \t Index: {}, \t | \t Type: {}, \t | \t Name: {}", count,index, t, string);
 
+    #[test]
+    fn unique_columns_are_checked_through_an_index(){
+        let (mut fm, mut pt, mut table) = open_test_table("unique_index");
+
+        let mut email = Column::new("email".to_string(), Data_type::String);
+        email.unique = true;
+        table.add_column("id".to_string(), Data_type::Int, &mut pt, &mut fm).unwrap();
+        table.add_column_definition(email, &mut pt, &mut fm).unwrap();
+
+        assert_eq!(table.indexes.iter().map(|index| index.column_names.clone()).collect::<Vec<_>>(), vec![vec!["email".to_string()]]);
+
+        let email = |text: &str| Value::String(text.to_string());
+
+        let first = table.add_record(vec![Value::Int(1), email("a@example.com")], &mut pt, &mut fm).unwrap().record_id;
+        let second = table.add_record(vec![Value::Int(2), email("b@example.com")], &mut pt, &mut fm).unwrap().record_id;
+
+        //nulls do not collide with each other.
+        table.add_record(vec![Value::Int(3), Value::Null], &mut pt, &mut fm).unwrap();
+        table.add_record(vec![Value::Int(4), Value::Null], &mut pt, &mut fm).unwrap();
+
+        match table.add_record(vec![Value::Int(5), email("a@example.com")], &mut pt, &mut fm){
+            Err(Table_error::Unique_violation(column_name)) => assert_eq!(column_name, "email"),
+            other                                           => panic!("{:?}", other),
         }
-    }
 
-    pub fn add_record(&mut self, record: Vec<Value>, page_table: &mut Page_table, file_manager: &mut File_manager){
-        //verify if [values] follows the table schema.
-        //
-        //Find the page to write the record to.
-        //
-        //Find suitable data pages to write larger data to.
-        //
-        //
-        //Write the record to the record page and then write all the data to the data pages.
-        //
-        //
-        //Overflow if necessary.
-        //
-        let mut record_size = 0;
-        
-        for i in 0..self.column_schema.len(){
-            if self.column_schema[i].data_type != record[i]{
-                //ERROR entry type doesnt't match the column schema.
-                return ()
-            }
+        match table.modify_record(second, vec![("email".to_string(), email("a@example.com"))], &mut pt, &mut fm){
+            Err(Table_error::Unique_violation(column_name)) => assert_eq!(column_name, "email"),
+            other                                           => panic!("{:?}", other),
+        }
 
-            record_size += record[i].size();
+        //a record keeps its own value.
+        table.modify_record(first, vec![("id".to_string(), Value::Int(10)), ("email".to_string(), email("a@example.com"))], &mut pt, &mut fm).unwrap();
+
+        //the freed value can be used again.
+        table.modify_record(first, vec![("email".to_string(), email("c@example.com"))], &mut pt, &mut fm).unwrap();
+        table.modify_record(second, vec![("email".to_string(), email("a@example.com"))], &mut pt, &mut fm).unwrap();
+
+        match table.drop_index(vec!["email".to_string()], &mut pt, &mut fm){
+            Err(Table_error::Index_required(column_name))   => assert_eq!(column_name, "email"),
+            other                                           => panic!("{:?}", other),
         }
 
+        //another index starting with the column is enough.
+        table.create_index(vec!["email".to_string(), "id".to_string()], &mut pt, &mut fm).unwrap();
+        table.drop_index(vec!["email".to_string()], &mut pt, &mut fm).unwrap();
+        assert!(table.add_record(vec![Value::Int(6), email("c@example.com")], &mut pt, &mut fm).is_err());
 
+        //the index is created again once the other one is gone.
+        table.remove_column("id".to_string(), &mut pt, &mut fm).unwrap();
+        assert_eq!(table.indexes.iter().map(|index| index.column_names.clone()).collect::<Vec<_>>(), vec![vec!["email".to_string()]]);
+        assert!(table.add_record(vec![email("c@example.com")], &mut pt, &mut fm).is_err());
+        table.add_record(vec![email("d@example.com")], &mut pt, &mut fm).unwrap();
+    }
 
 
 
-        // generate the bytes that shall be stored with empty spaces for the strings, enums, blobs
-        // for now...
+    #[test]
+    fn indexes_follow_added_modified_and_removed_records(){
+        let (mut fm, mut pt, mut table) = open_test_table("index_records");
+        table.add_column("n".to_string(), Data_type::Int, &mut pt, &mut fm).unwrap();
+        table.add_column("s".to_string(), Data_type::String, &mut pt, &mut fm).unwrap();
 
+        let mut record_ids: Vec<Record_ID> = (0..50).map(|i| table.add_record(vec![Value::Int(i % 10), Value::String(format!("record {}", i))], &mut pt, &mut fm).unwrap().record_id).collect();
 
-        
-        
+        //records added before and after the index is created are both found through it.
+        table.create_index(vec!["n".to_string()], &mut pt, &mut fm).unwrap();
+        record_ids.push(table.add_record(vec![Value::Int(3), Value::String("late".to_string())], &mut pt, &mut fm).unwrap().record_id);
 
+        table.modify_record(record_ids[3], vec![("n".to_string(), Value::Int(20))], &mut pt, &mut fm).unwrap();
+        table.modify_record(record_ids[13], vec![("n".to_string(), Value::Null)], &mut pt, &mut fm).unwrap();
+        table.remove_record(record_ids[23], &mut pt, &mut fm).unwrap();
 
-    }
+        let mut table = Table::open("index_records".to_string(), &mut pt, &mut fm).unwrap();
 
+        let sorted = |mut ids: Vec<Record_ID>| { ids.sort_by_key(|id| (id.page_num, id.slot)); ids };
+        let threes = sorted(vec![record_ids[33], record_ids[43], record_ids[50]]);
 
+        assert_eq!(sorted(table.find_records("n", &Value::Int(3), &mut pt, &mut fm).unwrap()), threes);
+        assert_eq!(table.find_records("n", &Value::Int(20), &mut pt, &mut fm).unwrap(), vec![record_ids[3]]);
 
+        let range = table.index_range(&["n"], Bound::Excluded(&[Value::Int(2)]), Bound::Unbounded, &mut pt, &mut fm).unwrap().unwrap();
+        assert_eq!(range.len(), 34);
+        assert_eq!(range.last(), Some(&record_ids[3]));
 
-    pub fn find_record(){
-        //using B-Tree we can find the record by comparing fields with the search term.
+        //without the index the same records are found by going through all of them.
+        table.drop_index(vec!["n".to_string()], &mut pt, &mut fm).unwrap();
+        assert!(table.index_range(&["n"], Bound::Unbounded, Bound::Unbounded, &mut pt, &mut fm).unwrap().is_none());
+        assert_eq!(sorted(table.find_records("n", &Value::Int(3), &mut pt, &mut fm).unwrap()), threes);
     }
-    pub fn modify_record(){
-        //Find record location
-        //
-        //
-        //If simple data then modify that and continue (no need for resizing)
-        //
-        //
-        // If larger data is modified, go to the correct data page and modify the data there. There
-        // will be a need to resize it.
-    }
-    pub fn remove_record(){
-        //First get a list of all larger data locations
-        //Then remove the record and shift all the data left to fill the place, also locate the overflow
-        //bytes and shift them aswell. 
-        //
-        //Finally, go to data page and de-allocate space so that new data can go there.
 
+
+
+    #[test]
+    fn composite_index_ranges_match_a_scan(){
+        let (mut fm, mut pt, mut table) = open_test_table("composite_range");
+        table.add_column("a".to_string(), Data_type::Int, &mut pt, &mut fm).unwrap();
+        table.add_column("b".to_string(), Data_type::Blob, &mut pt, &mut fm).unwrap();
+
+        //blobs of 255s end up right next to the 255s the upper bounds are padded with.
+        let blobs = [vec![], vec![0], vec![0, 255], vec![255], vec![255, 0], vec![255; 2], vec![255; 8]];
+
+        for a in 0..3{
+            for b in blobs.iter(){
+                table.add_record(vec![Value::Int(a), Value::Blob(b.clone())], &mut pt, &mut fm).unwrap();
+            }
+            table.add_record(vec![Value::Int(a), Value::Null], &mut pt, &mut fm).unwrap();
+        }
+
+        table.create_index(vec!["a".to_string(), "b".to_string()], &mut pt, &mut fm).unwrap();
+        assert!(table.index_range(&["b"], Bound::Unbounded, Bound::Unbounded, &mut pt, &mut fm).unwrap().is_none());
+
+        let records: Vec<(Record_ID, Vec<Value>)> = table.scan(&mut pt, &mut fm).map(|item| item.unwrap()).collect();
+        let key = |values: &[Value]| -> Vec<u8> { values.iter().flat_map(|value| value.encode_key()).collect() };
+
+        let mut values: Vec<Vec<Value>> = (-1..4).map(|a| vec![Value::Int(a)]).collect();
+        values.extend(blobs.iter().map(|b| vec![Value::Int(1), Value::Blob(b.clone())]));
+        values.push(vec![Value::Int(1), Value::Null]);
+
+        let mut bounds = vec![Bound::Unbounded];
+        bounds.extend(values.iter().flat_map(|v| [Bound::Included(v.as_slice()), Bound::Excluded(v.as_slice())]));
+
+        //a record is compared with a bound by as many columns as the bound has values.
+        let after = |record: &[Value], bound: &Bound<&[Value]>| match bound{
+            Bound::Included(v)  => key(&record[.. v.len()]) >= key(v),
+            Bound::Excluded(v)  => key(&record[.. v.len()]) > key(v),
+            Bound::Unbounded    => true,
+        };
+        let before = |record: &[Value], bound: &Bound<&[Value]>| match bound{
+            Bound::Included(v)  => key(&record[.. v.len()]) <= key(v),
+            Bound::Excluded(v)  => key(&record[.. v.len()]) < key(v),
+            Bound::Unbounded    => true,
+        };
+        let first_only = |bound: &Bound<&[Value]>| !matches!(bound, Bound::Included(v) | Bound::Excluded(v) if v.len() > 1);
+
+        for from in bounds.iter(){
+            for to in bounds.iter(){
+                let found = table.index_range(&["a", "b"], *from, *to, &mut pt, &mut fm).unwrap().unwrap();
+
+                let mut expected: Vec<(Vec<u8>, Record_ID)> = records.iter()
+                    .filter(|(_, record)| after(record, from) && before(record, to))
+                    .map(|(record_id, record)| (key(record), *record_id))
+                    .collect();
+                expected.sort_by(|x, y| x.0.cmp(&y.0));
+
+                assert_eq!(found, expected.into_iter().map(|(_, record_id)| record_id).collect::<Vec<_>>(), "{:?} {:?}", from, to);
+
+                //bounds on the first column alone can be given for that column alone.
+                if first_only(from) && first_only(to){
+                    assert_eq!(table.index_range(&["a"], *from, *to, &mut pt, &mut fm).unwrap().unwrap(), found);
+                }
+            }
+        }
+
+
+        //selecting by the first column of the index finds the same records as going through all of
+        //them.
+        for predicate in [Predicate::Equal("a".to_string(), Value::Int(1)), Predicate::Between("a".to_string(), Value::Int(1), Value::Int(5))]{
+            let mut selected: Vec<Record_ID> = table.select(&[], Some(&predicate), &mut pt, &mut fm).unwrap().into_iter().map(|(record_id, _)| record_id).collect();
+            let mut scanned: Vec<Record_ID> = records.iter().filter(|(_, record)| predicate.evaluate(&table.column_schema, record) == Some(true)).map(|(record_id, _)| *record_id).collect();
+
+            selected.sort_by_key(|record_id| (record_id.page_num, record_id.slot));
+            scanned.sort_by_key(|record_id| (record_id.page_num, record_id.slot));
+            assert_eq!(selected, scanned);
+        }
     }
 
 
 
+    #[test]
+    fn scans_skip_removed_records_and_find_moved_ones_once(){
+        let (mut fm, mut pt, mut table) = open_test_table("scan_moved");
+        for i in 0..100{
+            table.add_column(format!("c{}", i), Data_type::Int, &mut pt, &mut fm).unwrap();
+        }
+
+        let record_ids: Vec<Record_ID> = (0..1000).map(|_| table.add_record(vec![Value::Null; 100], &mut pt, &mut fm).unwrap().record_id).collect();
+        let mut expected: std::collections::HashMap<Record_ID, Vec<Value>> = record_ids.iter().map(|record_id| (*record_id, vec![Value::Null; 100])).collect();
+
+        //every 50th record grows out of its page, and some of the moved records are removed again
+        //along with records that stayed in place.
+        for (n, record_id) in record_ids.iter().enumerate().step_by(50){
+            let changes = (0..100).map(|i| (format!("c{}", i), Value::Int((n + i) as i64))).collect();
+            table.modify_record(*record_id, changes, &mut pt, &mut fm).unwrap();
+            expected.insert(*record_id, (0..100).map(|i| Value::Int((n + i) as i64)).collect());
+        }
+        assert!(record_ids.iter().step_by(50).any(|record_id| table.locate_record(*record_id, &mut pt, &mut fm).unwrap() != *record_id));
 
+        for record_id in record_ids.iter().step_by(75).chain(record_ids.iter().skip(1).step_by(7)){
+            if expected.remove(record_id).is_some(){
+                table.remove_record(*record_id, &mut pt, &mut fm).unwrap();
+            }
+        }
 
+        let scanned: Vec<(Record_ID, Vec<Value>)> = table.scan(&mut pt, &mut fm).map(|item| item.unwrap()).collect();
 
+        assert_eq!(scanned.len(), expected.len());
+        assert_eq!(scanned.iter().map(|(record_id, _)| *record_id).collect::<Vec<_>>(), table.record_ids(&mut pt, &mut fm).unwrap());
+        for (record_id, record) in scanned{
+            assert_eq!(Some(&record), expected.get(&record_id));
+        }
+    }
 }
@@ -0,0 +1,143 @@
+//Iterator over every record of a table, in record page order. It walks the chain of Record pages
+//from the table's first record page, keeping the page it is reading pinned in the Page_table so
+//it is not replaced while its slots are read. Tombstones are skipped, and moved records are
+//returned once, under the Record_ID of their forwarding record like Table::record_ids.
+
+use crate::file_manager::block::Block_ID;
+use crate::file_manager::file_manager::File_manager;
+use crate::buffer_pool::page_table::Page_table;
+use crate::table::table::Table;
+use crate::table::table::Table_error;
+use crate::table::table::Record_ID;
+use crate::table::table::Value;
+use crate::table::table::RECORD_MOVED;
+
+
+pub struct Table_scan<'a>{
+    table:          &'a Table,
+    page_table:     &'a mut Page_table,
+    file_manager:   &'a mut File_manager,
+    //the page being read, 0 once the end of the chain is reached.
+    page_num:       u32,
+    pinned:         bool,
+    slot:           u16,
+    slot_count:     u16,
+    next_page_num:  u32,
+}
+
+
+
+impl<'a> Table_scan<'a>{
+
+    pub fn new(table: &'a Table, page_table: &'a mut Page_table, file_manager: &'a mut File_manager) -> Table_scan<'a>{
+        return Table_scan{
+            table,
+            page_table,
+            file_manager,
+            page_num:       table.first_record_page_num,
+            pinned:         false,
+            slot:           0,
+            slot_count:     0,
+            next_page_num:  0,
+        }
+    }
+
+
+
+    fn block(&self) -> Block_ID{
+        return Block_ID{file_name: self.table.table_name.clone(), number: self.page_num}
+    }
+
+
+
+    //loads and pins the current page and reads how many slots it has.
+    fn pin_page(&mut self) -> Result<(), Table_error>{
+        let block = self.block();
+
+        let page = match self.page_table.get_mut_page(block.clone(), self.file_manager){
+            None    => return Err(Table_error::Io(std::io::Error::other("Record page could not be loaded."))),
+            Some(p) => p,
+        };
+
+        self.slot = 0;
+        self.slot_count = page.get_record_index_count();
+        self.next_page_num = page.next_index.unwrap_or(0);
+
+        self.page_table.pin(&block);
+        self.pinned = true;
+
+        return Ok(())
+    }
+
+
+
+    fn unpin_page(&mut self){
+        if self.pinned{
+            let block = self.block();
+            self.page_table.unpin(&block);
+            self.pinned = false;
+        }
+    }
+
+
+
+    //whether a slot of the current page holds a record that is not reached through another one.
+    fn is_record(&mut self, slot: u16) -> bool{
+        let page = match self.page_table.get_mut_page(self.block(), self.file_manager){
+            None    => return false,
+            Some(p) => p,
+        };
+
+        return match page.get_record_index_entry(slot){
+            None | Some(0)  => false,
+            Some(offset)    => page.bytes[offset as usize] & RECORD_MOVED == 0,
+        }
+    }
+}
+
+
+
+impl Iterator for Table_scan<'_>{
+    type Item = Result<(Record_ID, Vec<Value>), Table_error>;
+
+    fn next(&mut self) -> Option<Self::Item>{
+        loop{
+            if self.page_num == 0{
+                return None
+            }
+
+            if !self.pinned{
+                //a page that cannot be read ends the scan.
+                if let Err(error) = self.pin_page(){
+                    self.page_num = 0;
+                    return Some(Err(error))
+                }
+            }
+
+            if self.slot >= self.slot_count{
+                self.unpin_page();
+                self.page_num = self.next_page_num;
+                continue
+            }
+
+            let slot = self.slot;
+            self.slot += 1;
+
+            if !self.is_record(slot){
+                continue
+            }
+
+            let record_id = Record_ID{page_num: self.page_num, slot};
+
+            return Some(self.table.get_record(record_id, self.page_table, self.file_manager).map(|record| (record_id, record)))
+        }
+    }
+}
+
+
+
+impl Drop for Table_scan<'_>{
+    fn drop(&mut self){
+        self.unpin_page();
+    }
+}
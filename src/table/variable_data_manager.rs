@@ -7,27 +7,43 @@
 // each data page entry is made up of a 1 byte type, and 4 bytes size then the rest is the actual
 // data.
 //
+// 0 | 0 0 0 0 | ...
+// type  size    data
+//
+// Data too large for a single page is written in page sized chunks to pages that follow each
+// other in the data page chain. The first of them holds the header and is the only entry in its
+// record index.
+//
 // The data will be added to appropriate pages via worst-fit i.e. smaller data goes to the largest
 // blocks of free space
 //
 //
-// GOOD IDEA: instead of storing a reference to variable data's position in records, I can store an
-// ID which links to a ID -> Location hashmap. Then, if a piece of data's location is changed, We
-// only need to change the value in the table. I can also store size and type in the table
+// Records do not store a reference to variable data's position, they store an ID which links to
+// its location through the Variable_data_index_table. If a piece of data's location is changed we
+// only need to change its entry in that table.
 //
 //
 use std::collections::BinaryHeap;
+use std::io::Error;
+use std::io::ErrorKind;
 
 use crate::file_manager::file_manager::File_manager;
 use crate::file_manager::block::Block_ID;
+use crate::file_manager::page::Page_type;
+use crate::file_manager::page::PAGE_HEADER_SIZE;
 
 use crate::buffer_pool::page_table::Page_table;
+use crate::table::free_page_list::{allocate_page, free_page};
+use crate::table::free_space_tracker::{read_entries, set_free_space};
+use crate::table::table::Data_type;
+use crate::table::table::Table_error;
+use crate::database_engine::variable_data_index_table::Variable_data_index_table;
 
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Page_free {
-    free: u16,
-    page_num: u32,
+    pub free: u16,
+    pub page_num: u32,
 }
 
 impl Ord for Page_free{
@@ -46,6 +62,7 @@ impl PartialOrd for Page_free{
 
 }
 
+#[derive(Debug)]
 pub struct Variable_data_manager{
 
     pub free_bytes:                     BinaryHeap<Page_free>,
@@ -55,142 +72,488 @@ pub struct Variable_data_manager{
     pub last_data_page_num:             u32,
     //
     pub free_space_tracker_page_num:    u32,
+    //maps the IDs stored in records to where their data is.
+    pub index_table:                    Variable_data_index_table,
     
 }
 
-pub fn init_variable_data_storage_file(){
 
-}
+
+//size of the type and size header in front of every piece of data.
+pub const DATA_HEADER_SIZE: usize = 5;
 
 
 impl Variable_data_manager{
-//TODO performance cost of clone here
-//CHECK IF VDS file exists and crete one if it doesn't exist.
-    pub fn new(last_data_page_num: u32, free_space_tracker_page_num: &u32, page_table: &mut Page_table, file_manager: &mut File_manager ) -> Variable_data_manager{
+
+    //loads the free space tracker and the index table of a table's variable data.
+    pub fn new(file_name: String, last_data_page_num: u32, free_space_tracker_page_num: &u32, page_table: &mut Page_table, file_manager: &mut File_manager ) -> Result<Variable_data_manager, Table_error>{
 
         //retrieving the free byte tracker from the file.
         let mut free_bytes: BinaryHeap<Page_free> = BinaryHeap::new();
-        let mut page = page_table.get_mut_page(Block_ID{file_name: file_name.clone(), number: *free_space_tracker_page_num}, file_manager).unwrap();//ERROR CHECKING
-        let mut index: usize = 17;
-
-        loop{
-            let data_end_point = page.data_end_point.clone();
-            if index > data_end_point as usize{ //MAGIC NUM
-            
-
-                if page.next_index == None || page.next_index == Some(0){
-                    break;
-                }else{
-                    let next_index = page.next_index.unwrap();
-                    page = page_table.get_mut_page(Block_ID{file_name: file_name.clone(), number: next_index}, file_manager).unwrap(); //ERROR CHECKING
-                    index = 18;
-                }
+        let entries = read_entries(&file_name, *free_space_tracker_page_num, page_table, file_manager)?;
 
-            }else{
-                
-                let page_num   =  u32::from_be_bytes(page.bytes[index..index+4].try_into().unwrap());
-                let free_space =  u16::from_be_bytes(page.bytes[index+4 ..index+6].try_into().unwrap());
+        for (page_num, free_space) in entries{
+            free_bytes.push(Page_free{free: free_space, page_num: page_num});
+        }
 
-                free_bytes.push(Page_free{free: free_space, page_num: page_num});
+        let index_table = Variable_data_index_table::initialise(file_name.clone(), page_table, file_manager)?;
 
-                index += 6;
-            }
-        }
- 
-        return Variable_data_manager{
+        return Ok(Variable_data_manager{
             free_bytes:                  free_bytes,
-            file_name:                   "Variable_Data_Storage",
+            file_name:                   file_name,
             last_data_page_num:          last_data_page_num,
             free_space_tracker_page_num: *free_space_tracker_page_num,
+            index_table:                 index_table,
 
-        }
+        })
     }
 
-    fn allocate(heap: &mut BinaryHeap<Page_free>, data_size: u64) -> Option<u32>{
-        let mut page = heap.pop()?;
-        if (page.free as u64) < data_size {
+    //worst fit, the page with the most free bytes is used. It is taken off the heap and pushed
+    //back once the data is written and its new free bytes are known, or as it was if the data
+    //could not be written.
+    fn allocate(heap: &mut BinaryHeap<Page_free>, data_size: u64) -> Option<Page_free>{
+        if (heap.peek()?.free as u64) < data_size {
             return None
         }
 
-        page.free -= data_size as u16;
-        let chosen = page.page_num;
-        heap.push(page);
-        Some(chosen)
+        return heap.pop()
+    }
 
+
+
+    //adds an empty data page to the end of the data page chain.
+    fn new_data_page(&mut self, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u32, std::io::Error>{
+        let page_num = allocate_page(&self.file_name, Page_type::Data, page_table, file_manager)?;
+        let last_block = Block_ID{file_name: self.file_name.clone(), number: self.last_data_page_num};
+
+        let next_page_num = match page_table.get_mut_page(last_block.clone(), file_manager){
+            None    => return Err(Error::other("Data page could not be loaded.")),
+            Some(p) => p.next_index.unwrap_or(0),
+        };
+
+        page_table.create_overflow_page(&last_block, Page_type::Data, next_page_num, &[], Some(page_num), file_manager)?;
+        self.last_data_page_num = page_num;
+
+        return Ok(page_num)
     }
 
-    fn shrink_top(heap: &mut BinaryHeap<Page_free>, amount: u16) -> Option<u8>{
-        let mut page = heap.pop()?;
-        if page.free < amount{
-            
-            return None; //ERROR CHECKING
+
+
+    //stores the bytes in a data page and returns the page num and the index of the data in the
+    //page's record index.
+    fn store_data(&mut self, data_type: &Data_type, bytes: &[u8], page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<(u32, u16), std::io::Error>{
+        if bytes.len() > u32::MAX as usize{
+            return Err(Error::new(ErrorKind::InvalidInput, "Data is too large to be stored."))
+        }
+
+        //every piece of data starts with its type and size.
+        let mut entry: Vec<u8> = Vec::with_capacity(DATA_HEADER_SIZE + bytes.len());
+        entry.push(data_type.clone() as u8);
+        entry.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+        entry.extend_from_slice(bytes);
+
+        let needed = entry.len() + 2; // the data and its record index entry
+
+        //data larger than a page is spread over as many pages as it needs, taken from the free page
+        //list first and linked in one after the other after the last data page.
+        if entry.len() > page_table.chunk_size(){
+            let mut previous_block = Block_ID{file_name: self.file_name.clone(), number: self.last_data_page_num};
+
+            let next_page_num = match page_table.get_mut_page(previous_block.clone(), file_manager){
+                None    => return Err(Error::other("Data page could not be loaded.")),
+                Some(p) => p.next_index.unwrap_or(0),
+            };
+
+            let mut page_nums = Vec::new();
+
+            for chunk in entry.chunks(page_table.chunk_size()){
+                let page_num = allocate_page(&self.file_name, Page_type::Data, page_table, file_manager)?;
+
+                page_table.create_overflow_page(&previous_block, Page_type::Data, next_page_num, chunk, Some(page_num), file_manager)?;
+                previous_block.number = page_num;
+                page_nums.push(page_num);
+            }
+
+            let first_block = Block_ID{file_name: self.file_name.clone(), number: page_nums[0]};
+
+            match page_table.get_mut_page(first_block.clone(), file_manager){
+                None    => return Err(Error::other("Data page could not be loaded.")),
+                Some(p) => p.add_record_index(PAGE_HEADER_SIZE),
+            };
+
+            page_table.set_dirty(&first_block);
+            self.last_data_page_num = previous_block.number;
+
+            return Ok((page_nums[0], 0))
         }
 
-        page.free -= amount;
-        heap.push(page);
+        let chosen = Self::allocate(&mut self.free_bytes, needed as u64);
+
+        let page_num = match &chosen{
+            Some(page)  => page.page_num,
 
-        return Some(1);
+            //otherwise make a new page and store the data there.
+            None        => self.new_data_page(page_table, file_manager)?,
+        };
 
+        let (index, free) = match Self::write_entry(&self.file_name, page_num, &entry, page_table, file_manager){
+            Err(error)  => {
+                if let Some(page) = chosen{
+                    self.free_bytes.push(page);
+                }
+                return Err(error)
+            },
+            Ok(w)       => w,
+        };
+
+        //update the free space heap
+        self.set_free_bytes(page_num, free, page_table, file_manager)?;
+
+        return Ok((page_num, index))
     }
-    
-    pub fn add_data(&mut self, bytes: &[u8], page_table: &mut Page_table, file_manager: &mut File_manager){
-    //get largest free space and compare. If data is smaller store it there
-    
-    let chosen = Self::allocate(&mut self.free_bytes, bytes.len().try_into().unwrap());
 
 
-    if let Some(page_num) = chosen{
+
+    //writes an entry at the end of a data page's data and returns its index in the page's record
+    //index and the bytes left free in the page.
+    fn write_entry(file_name: &str, page_num: u32, entry: &[u8], page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<(u16, u16), std::io::Error>{
+        let block = Block_ID{file_name: file_name.to_string(), number: page_num};
+        let page = match page_table.get_mut_page(block.clone(), file_manager){
+            None    => return Err(Error::other("Data page could not be loaded.")),
+            Some(p) => p,
+        };
+
+        let offset = page.data_end_point;
+        page.write(offset, entry.to_vec())?;
+        page.data_end_point += entry.len() as u16;
+
+        //entries of removed data are reused before the record index grows.
+        let index = match page.find_empty_record_index_entry(){
+            Some(index) => {
+                page.set_record_index_entry(index, offset);
+                index
+            },
+            None        => {
+                page.add_record_index(offset);
+                page.get_record_index_count() - 1
+            },
+        };
+
+        let free = page.record_index_end_point - page.data_end_point;
+        page_table.set_dirty(&block);
+
+        return Ok((index, free))
+    }
+
+
+
+    //stores a piece of data and returns the ID records refer to it by. Empty data is not stored,
+    //it is referred to as ID 0.
+    pub fn add_data(&mut self, data_type: &Data_type, bytes: &[u8], page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u32, std::io::Error>{
+        if bytes.is_empty(){
+            return Ok(0)
+        }
+
+        let (page_num, index) = self.store_data(data_type, bytes, page_table, file_manager)?;
+
+        return self.index_table.insert(page_num, index, page_table, file_manager)
+    }
+
+
+
+    //reads the data an ID refers to.
+    pub fn get_data(&self, id: u32, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<u8>, std::io::Error>{
+        if id == 0{
+            return Ok(Vec::new())
+        }
+
+        let (page_num, index) = self.index_table.get(id, page_table, file_manager)?;
+
+        return self.read_data(page_num, index, page_table, file_manager)
+    }
+
+
+
+    //removes the data an ID refers to and frees the ID.
+    pub fn remove_data(&mut self, id: u32, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+        if id == 0{
+            return Ok(0)
+        }
+
+        let (page_num, index) = self.index_table.get(id, page_table, file_manager)?;
+        self.delete_data(page_num, index, page_table, file_manager)?;
+
+        return self.index_table.remove(id, page_table, file_manager)
+    }
+
+
+
+    //replaces the data an ID refers to and returns the ID to refer to it by from now on. This is the
+    //same ID unless the data became empty or was empty before.
+    pub fn modify_data(&mut self, id: u32, data_type: &Data_type, bytes: &[u8], page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u32, std::io::Error>{
+        if id == 0{
+            return self.add_data(data_type, bytes, page_table, file_manager)
+        }
+
+        if bytes.is_empty(){
+            self.remove_data(id, page_table, file_manager)?;
+            return Ok(0)
+        }
+
+        let (page_num, index) = self.index_table.get(id, page_table, file_manager)?;
+        let (new_page_num, new_index) = self.replace_data(page_num, index, data_type, bytes, page_table, file_manager)?;
+
+        //the data moved, only its entry in the index table has to follow it.
+        if (new_page_num, new_index) != (page_num, index){
+            self.index_table.set(id, new_page_num, new_index, page_table, file_manager)?;
+        }
+
+        return Ok(id)
+    }
+
+
+
+    //reads the type and size header of the data at the index of a data page and returns the offset
+    //of the header in the page, the data type and the size of the data.
+    fn read_header(&self, page_num: u32, index: u16, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<(u16, Data_type, usize), std::io::Error>{
+        let chunk_size = page_table.chunk_size();
         let block = Block_ID{file_name: self.file_name.clone(), number: page_num};
-        let page = page_table.get_mut_page(block.clone(), file_manager).unwrap(); //ERROR CHECKING
-        page.write_at_end(bytes.to_vec());
+        let page = match page_table.get_mut_page(block, file_manager){
+            None    => return Err(Error::other("Data page could not be loaded.")),
+            Some(p) => p,
+        };
+
+        let offset = match page.get_record_index_entry(index){
+            None | Some(0)  => return Err(Error::new(ErrorKind::NotFound, "No data at this index.")),
+            Some(n)         => n,
+        };
+
+        let start = offset as usize;
+        if start + DATA_HEADER_SIZE > page.data_end_point as usize{
+            return Err(Error::new(ErrorKind::InvalidData, "Data header is out of range."))
+        }
+
+        let data_type = match Data_type::try_from(page.bytes[start]){
+            Err(_)  => return Err(Error::new(ErrorKind::InvalidData, "Unknown data type.")),
+            Ok(t)   => t,
+        };
+
+        let size = u32::from_be_bytes(page.bytes[start + 1 .. start + 5].try_into().unwrap()) as usize;
+
+        //data that is not spread over other pages has to end within the page's data.
+        if DATA_HEADER_SIZE + size <= chunk_size && start + DATA_HEADER_SIZE + size > page.data_end_point as usize{
+            return Err(Error::new(ErrorKind::InvalidData, "Data is out of range."))
+        }
+
+        return Ok((offset, data_type, size))
+    }
+
+
+
+    //the pages a piece of data larger than a page is spread over, starting with the page holding
+    //its header.
+    fn overflow_page_nums(&self, page_num: u32, size: usize, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<u32>, std::io::Error>{
+        let page_count = (DATA_HEADER_SIZE + size).div_ceil(page_table.chunk_size());
+        let mut page_nums = vec![page_num];
+
+        while page_nums.len() < page_count{
+            let block = Block_ID{file_name: self.file_name.clone(), number: *page_nums.last().unwrap()};
+            let next_page_num = match page_table.get_mut_page(block, file_manager){
+                None    => return Err(Error::other("Data page could not be loaded.")),
+                Some(p) => p.next_index.unwrap_or(0),
+            };
+
+            if next_page_num == 0{
+                return Err(Error::new(ErrorKind::InvalidData, "Data overflow pages are missing."))
+            }
+
+            page_nums.push(next_page_num);
+        }
+
+        return Ok(page_nums)
+    }
+
+
+
+    //removes the data at the index of a data page, shifting all the data after it back so the free
+    //bytes of the page stay in one piece. The index entry is left empty so the other indexes in the
+    //page stay valid. Data spread over several pages gives all of its pages back to the free page
+    //list.
+    fn delete_data(&mut self, page_num: u32, index: u16, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+        let (_, _, size) = self.read_header(page_num, index, page_table, file_manager)?;
+
+        if DATA_HEADER_SIZE + size > page_table.chunk_size(){
+            return self.remove_overflow_pages(page_num, size, page_table, file_manager)
+        }
+
+        let block = Block_ID{file_name: self.file_name.clone(), number: page_num};
+        let page = match page_table.get_mut_page(block.clone(), file_manager){
+            None    => return Err(Error::other("Data page could not be loaded.")),
+            Some(p) => p,
+        };
+
+        page.remove_record_index_entry_data(index, (DATA_HEADER_SIZE + size) as u16);
+        let free = page.record_index_end_point - page.data_end_point;
         page_table.set_dirty(&block);
-        Self::shrink_top(&mut self.free_bytes, bytes.len().try_into().unwrap());
-        
-    }else{
-        let res = page_table.create_multiple_overflow_pages_by_data(bytes, Block_ID{file_name: self.file_name.clone(), number: self.last_data_page_num}, file_manager);
-        self.last_data_page_num = res.0;
-        //update the free space heap
-        self.free_bytes.push(Page_free{free: res.1, page_num: res.0});
 
+        //update the free bytes of the page.
+        self.set_free_bytes(page_num, free, page_table, file_manager)?;
 
+        return Ok(1)
     }
-    
 
 
 
-    //otherwise make a new page and store the data there.
+    //unlinks the pages of a piece of data spread over several pages from the data page chain and
+    //frees them.
+    fn remove_overflow_pages(&mut self, page_num: u32, size: usize, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+        let page_nums = self.overflow_page_nums(page_num, size, page_table, file_manager)?;
+        let last_page_num = *page_nums.last().unwrap();
+
+        let previous_page_num = match page_table.get_mut_page(Block_ID{file_name: self.file_name.clone(), number: page_num}, file_manager){
+            None    => return Err(Error::other("Data page could not be loaded.")),
+            Some(p) => p.previous_index.unwrap_or(0),
+        };
+
+        let next_page_num = match page_table.get_mut_page(Block_ID{file_name: self.file_name.clone(), number: last_page_num}, file_manager){
+            None    => return Err(Error::other("Data page could not be loaded.")),
+            Some(p) => p.next_index.unwrap_or(0),
+        };
+
+        let previous_block = Block_ID{file_name: self.file_name.clone(), number: previous_page_num};
+        match page_table.get_mut_page(previous_block.clone(), file_manager){
+            None    => return Err(Error::other("Data page could not be loaded.")),
+            Some(p) => p.set_next_page_num(next_page_num),
+        };
+        page_table.set_dirty(&previous_block);
+
+        if next_page_num != 0{
+            let next_block = Block_ID{file_name: self.file_name.clone(), number: next_page_num};
+            match page_table.get_mut_page(next_block.clone(), file_manager){
+                None    => return Err(Error::other("Data page could not be loaded.")),
+                Some(p) => p.set_previous_page_num(previous_page_num),
+            };
+            page_table.set_dirty(&next_block);
+        }
 
+        if page_nums.contains(&self.last_data_page_num){
+            self.last_data_page_num = previous_page_num;
+        }
 
+        for number in page_nums{
+            free_page(&self.file_name, number, page_table, file_manager)?;
+        }
 
+        return Ok(1)
     }
 
-    pub fn remove_data(&mut self, page_num: u32, index: u16, page_table: &mut Page_table, file_manager: &mut File_manager){
-        //access the index point
-        //
-        //check the size of the data
-        //
-        //then begin the removal process, if the end of the data is before the end of a page then
-        //remove all of that data by dereferncing it, and shifting all the data after back (make
-        //sure to update all references to this data.)
-        //
-        //If the data continues after the end of the page then go to the overflow page and keep
-        //removing data there until end of data or end of page is reached. Repeat that
-        //
-        //update the free bytes aswell after every page.
 
+
+    //replaces the heap entry of a page with its new free bytes and writes them to the free space
+    //tracker.
+    fn set_free_bytes(&mut self, page_num: u32, free: u16, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<u8, std::io::Error>{
+        let mut entries = std::mem::take(&mut self.free_bytes).into_vec();
+        entries.retain(|entry| entry.page_num != page_num);
+        entries.push(Page_free{free, page_num});
+        self.free_bytes = BinaryHeap::from(entries);
+
+        return set_free_space(&self.file_name, self.free_space_tracker_page_num, page_num, free, page_table, file_manager)
     }
 
-    pub fn modify_data(&mut self, page_num: u32, index: u16){
-        //similar to remove data but keep the required bits. If they are larger than the original
-        //data then keep going and replacing data until end is reached. Then shift all data to an
-        //overflow page and keep going. (All indexes must also be updated...)
-        //
-        //if modifying a piece of data at the end of a page and it overflows, make a new page and
-        //store the original in there to avoid fragmentation.
+
+
+    //replaces the data at the index of a data page and returns where the new data is stored. The
+    //data stays at the same index while it still fits in its page, otherwise it is moved to
+    //wherever store_data puts it.
+    fn replace_data(&mut self, page_num: u32, index: u16, data_type: &Data_type, bytes: &[u8], page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<(u32, u16), std::io::Error>{
+        let (offset, _, old_size) = self.read_header(page_num, index, page_table, file_manager)?;
+        let old_entry_size = DATA_HEADER_SIZE + old_size;
+        let new_entry_size = DATA_HEADER_SIZE + bytes.len();
+
+        //data spread over several pages is always stored again.
+        if old_entry_size > page_table.chunk_size() || new_entry_size > page_table.chunk_size(){
+            self.delete_data(page_num, index, page_table, file_manager)?;
+            return self.store_data(data_type, bytes, page_table, file_manager)
+        }
+
+        let mut entry: Vec<u8> = Vec::with_capacity(new_entry_size);
+        entry.push(data_type.clone() as u8);
+        entry.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+        entry.extend_from_slice(bytes);
+
+        let block = Block_ID{file_name: self.file_name.clone(), number: page_num};
+        let page = match page_table.get_mut_page(block.clone(), file_manager){
+            None    => return Err(Error::other("Data page could not be loaded.")),
+            Some(p) => p,
+        };
+
+        //the same size, so the data is simply overwritten.
+        if new_entry_size == old_entry_size{
+            page.write(offset, entry)?;
+            page_table.set_dirty(&block);
+            return Ok((page_num, index))
+        }
+
+        let free = (page.record_index_end_point - page.data_end_point) as usize;
+
+        //still fits in the page, the old data is taken out and the new data is written at the end
+        //of the page's data under the same index.
+        if new_entry_size <= old_entry_size + free{
+            page.remove_record_index_entry_data(index, old_entry_size as u16);
+            let offset = page.data_end_point;
+            page.write(offset, entry)?;
+            page.data_end_point += new_entry_size as u16;
+            page.set_record_index_entry(index, offset);
+
+            let free = page.record_index_end_point - page.data_end_point;
+            page_table.set_dirty(&block);
+            self.set_free_bytes(page_num, free, page_table, file_manager)?;
+
+            return Ok((page_num, index))
+        }
+
+        self.delete_data(page_num, index, page_table, file_manager)?;
+        return self.store_data(data_type, bytes, page_table, file_manager)
     }
 
-    pub fn get_data(&self, page_num: u32, index: u16){
+
+
+    //reads the data stored at the index of a data page. Data larger than a page is read on through
+    //the pages that follow it.
+    fn read_data(&self, page_num: u32, index: u16, page_table: &mut Page_table, file_manager: &mut File_manager) -> Result<Vec<u8>, std::io::Error>{
         //go to index and read the required bytes.
+        let (offset, _, size) = self.read_header(page_num, index, page_table, file_manager)?;
+        let start = offset as usize + DATA_HEADER_SIZE;
+
+        if DATA_HEADER_SIZE + size <= page_table.chunk_size(){
+            let page = match page_table.get_mut_page(Block_ID{file_name: self.file_name.clone(), number: page_num}, file_manager){
+                None    => return Err(Error::other("Data page could not be loaded.")),
+                Some(p) => p,
+            };
+
+            return Ok(page.bytes[start .. start + size].to_vec())
+        }
+
+        //the header and data were written one page after the other, from the start of each page's
+        //data.
+        let mut entry: Vec<u8> = Vec::with_capacity(DATA_HEADER_SIZE + size);
+
+        for number in self.overflow_page_nums(page_num, size, page_table, file_manager)?{
+            let page = match page_table.get_mut_page(Block_ID{file_name: self.file_name.clone(), number}, file_manager){
+                None    => return Err(Error::other("Data page could not be loaded.")),
+                Some(p) => p,
+            };
+
+            let remaining = DATA_HEADER_SIZE + size - entry.len();
+            let end = std::cmp::min(PAGE_HEADER_SIZE as usize + remaining, page.data_end_point as usize);
+            entry.extend_from_slice(&page.bytes[PAGE_HEADER_SIZE as usize .. end]);
+        }
+
+        if entry.len() != DATA_HEADER_SIZE + size{
+            return Err(Error::new(ErrorKind::InvalidData, "Data overflow pages are incomplete."))
+        }
+
+        return Ok(entry.split_off(DATA_HEADER_SIZE))
     }
 
 
@@ -202,4 +565,80 @@ impl Variable_data_manager{
 
 
 
+}
+
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::test_utils::{open_test_table, reopen_test_table};
+
+
+    //the free bytes of every data page, ordered by page.
+    fn free_space(data: &Variable_data_manager) -> Vec<(u32, u16)>{
+        let mut entries: Vec<(u32, u16)> = data.free_bytes.iter().map(|entry| (entry.page_num, entry.free)).collect();
+        entries.sort();
+        return entries
+    }
+
+
+
+    #[test]
+    fn data_larger_than_a_page_is_stored_modified_and_removed(){
+        let (mut fm, mut pt, mut table) = open_test_table("large_data");
+        let data = &mut table.variable_data_manager;
+
+        let first: Vec<u8> = (0..40000).map(|i| i as u8).collect();
+        let second: Vec<u8> = (0..50000).map(|i| (i / 7) as u8).collect();
+
+        let id = data.add_data(&Data_type::Blob, &first, &mut pt, &mut fm).unwrap();
+        assert_eq!(data.get_data(id, &mut pt, &mut fm).unwrap(), first);
+
+        let id = data.modify_data(id, &Data_type::Blob, &second, &mut pt, &mut fm).unwrap();
+        assert_eq!(data.get_data(id, &mut pt, &mut fm).unwrap(), second);
+
+        //small data is stored next to the large data.
+        let small_id = data.add_data(&Data_type::String, b"small", &mut pt, &mut fm).unwrap();
+
+        data.remove_data(id, &mut pt, &mut fm).unwrap();
+        assert!(data.get_data(id, &mut pt, &mut fm).is_err());
+        assert_eq!(data.get_data(small_id, &mut pt, &mut fm).unwrap(), b"small");
+
+        //the pages of removed data are used again before the file grows.
+        let largest_page = pt.largest_page_map["large_data"];
+
+        let id = data.add_data(&Data_type::Blob, &second, &mut pt, &mut fm).unwrap();
+        assert_eq!(data.get_data(id, &mut pt, &mut fm).unwrap(), second);
+        assert_eq!(pt.largest_page_map["large_data"], largest_page);
+    }
+
+
+
+    #[test]
+    fn free_space_survives_a_checkpoint_and_reopen(){
+        let (mut fm, mut pt, mut table) = open_test_table("data_free_space");
+
+        //enough data for a few data pages, with some of it removed again.
+        let ids: Vec<u32> = (0..2000).map(|i| table.variable_data_manager.add_data(&Data_type::String, format!("value {}", i).repeat(5).as_bytes(), &mut pt, &mut fm).unwrap()).collect();
+        for id in ids.iter().step_by(3){
+            table.variable_data_manager.remove_data(*id, &mut pt, &mut fm).unwrap();
+        }
+
+        let free = free_space(&table.variable_data_manager);
+        assert!(free.len() > 1);
+
+        let (mut fm, mut pt, mut table) = reopen_test_table("data_free_space", fm, pt, table);
+        assert_eq!(free_space(&table.variable_data_manager), free);
+
+        //new data goes into the free space without overwriting the data that is left.
+        let new_ids: Vec<u32> = (0..500).map(|i| table.variable_data_manager.add_data(&Data_type::String, format!("new {}", i).as_bytes(), &mut pt, &mut fm).unwrap()).collect();
+
+        for (i, id) in ids.iter().enumerate().filter(|(i, _)| i % 3 != 0){
+            assert_eq!(table.variable_data_manager.get_data(*id, &mut pt, &mut fm).unwrap(), format!("value {}", i).repeat(5).into_bytes());
+        }
+        for (i, id) in new_ids.iter().enumerate(){
+            assert_eq!(table.variable_data_manager.get_data(*id, &mut pt, &mut fm).unwrap(), format!("new {}", i).into_bytes());
+        }
+    }
 }
@@ -0,0 +1,45 @@
+//Helpers shared by the tests. Every test works in its own directory under the system's temp
+//directory, which is emptied first so the test starts without any files.
+
+use crate::file_manager::file_manager::File_manager;
+use crate::file_manager::file_manager::build_file_manager;
+use crate::buffer_pool::page_table::Page_table;
+use crate::table::table::Table;
+
+
+pub const TEST_PAGE_SIZE: u16 = 16384;
+
+
+pub fn temp_directory(name: &str) -> String{
+    let directory = std::env::temp_dir().join(format!("databaseProject_{}_{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+
+    return directory.to_string_lossy().into_owned()
+}
+
+
+
+//a new empty table in its own directory, with a buffer pool of a few pages so pages are written
+//out and read back while the test runs.
+pub fn open_test_table(name: &str) -> (File_manager, Page_table, Table){
+    let mut file_manager = build_file_manager(TEST_PAGE_SIZE, temp_directory(name));
+    let mut page_table = Page_table::new(TEST_PAGE_SIZE as u32 * 16, TEST_PAGE_SIZE, vec![name.to_string()], &mut file_manager);
+    let table = Table::open_or_create(name.to_string(), &mut page_table, &mut file_manager).unwrap();
+
+    return (file_manager, page_table, table)
+}
+
+
+
+//writes every page of a test table to its file and opens the table again with a new file manager
+//and buffer pool, as if the program had been restarted.
+pub fn reopen_test_table(name: &str, mut file_manager: File_manager, mut page_table: Page_table, mut table: Table) -> (File_manager, Page_table, Table){
+    table.checkpoint(&mut page_table, &mut file_manager).unwrap();
+
+    let mut file_manager = build_file_manager(TEST_PAGE_SIZE, file_manager.data_directory.clone());
+    let mut page_table = Page_table::new(TEST_PAGE_SIZE as u32 * 16, TEST_PAGE_SIZE, vec![name.to_string()], &mut file_manager);
+    let table = Table::open(name.to_string(), &mut page_table, &mut file_manager).unwrap();
+
+    return (file_manager, page_table, table)
+}